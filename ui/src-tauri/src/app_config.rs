@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::classifier::ClassifierSettings;
+use crate::command_policy::PolicyRule;
+use crate::file_versions;
+
+const CONFIG_FILE: &str = "config.json";
+
+// Held for each load-modify-save of the config
+static CONFIG_LOCK: Mutex<()> = Mutex::new(());
+
+/// Backend settings persisted in the app config dir
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AppConfig {
+    /// Scout94 checkout chosen by the user
+    pub scout94_path: Option<String>,
+    /// Test runs allowed at once (`general.parallelProcesses`)
+    pub max_parallel_runs: Option<usize>,
+    /// Upper bound for a single run in minutes (`general.maxExecutionTime`)
+    pub max_execution_minutes: Option<u64>,
+    /// Data segment limit for local runs (`general.memoryLimitMB`)
+    pub memory_limit_mb: Option<u64>,
+    pub max_open_files: Option<u64>,
+    pub max_processes: Option<u64>,
+    /// Directories the file commands may access, canonicalized
+    pub project_roots: Vec<String>,
+    /// Gitignore-style globs hiding files from the tree, `None` for the built-in list
+    pub ignore_globs: Option<Vec<String>>,
+    /// File classifier settings per project root
+    pub classifier_settings: HashMap<String, ClassifierSettings>,
+    /// `execute_command` rules per project root, replacing the bundled policy.
+    /// Kept here rather than in the project so nothing the UI can write changes them.
+    pub command_policies: HashMap<String, Vec<PolicyRule>>,
+}
+
+pub fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .map_err(|e| format!("Failed to resolve app config dir: {}", e))
+}
+
+/// Load the persisted config, falling back to defaults if it is missing or unreadable
+pub fn load(app: &AppHandle) -> AppConfig {
+    let Ok(path) = config_path(app) else {
+        return AppConfig::default();
+    };
+
+    read(&path).unwrap_or_else(|e| {
+        println!("⚠️ Ignoring invalid config {:?}: {}", path, e);
+        AppConfig::default()
+    })
+}
+
+/// Load, change and save the config as one step; returns what `change` returns.
+///
+/// Runs under a process-wide lock so concurrent changes do not undo each
+/// other. A config that exists but cannot be parsed is an error here, so its
+/// project roots and command policies are never replaced by the defaults.
+pub fn modify<T>(app: &AppHandle, change: impl FnOnce(&mut AppConfig) -> T) -> Result<T, String> {
+    modify_at(&config_path(app)?, change)
+}
+
+fn modify_at<T>(path: &Path, change: impl FnOnce(&mut AppConfig) -> T) -> Result<T, String> {
+    let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = read(path)?;
+    let result = change(&mut config);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    // Atomic, so a `load` running meanwhile never sees a half-written file
+    file_versions::write_atomic(path, content.as_bytes()).map_err(|e| format!("Failed to write config: {}", e))?;
+    Ok(result)
+}
+
+/// The config at `path`; defaults when it does not exist yet
+fn read(path: &Path) -> Result<AppConfig, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Invalid config {:?}: {}", path, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(AppConfig::default()),
+        Err(e) => Err(format!("Failed to read config {:?}: {}", path, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn changes_are_saved_and_broken_configs_kept() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config/config.json");
+
+        modify_at(&path, |config| config.project_roots.push("/srv/app".to_string())).unwrap();
+        let roots = modify_at(&path, |config| {
+            config.max_parallel_runs = Some(2);
+            config.project_roots.clone()
+        })
+        .unwrap();
+        assert_eq!(roots, ["/srv/app"]);
+        assert_eq!(read(&path).unwrap().max_parallel_runs, Some(2));
+
+        fs::write(&path, "{\"project_roots\": [\"/srv/app\"").unwrap();
+        assert!(modify_at(&path, |config| config.max_parallel_runs = Some(3)).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"project_roots\": [\"/srv/app\"");
+    }
+}
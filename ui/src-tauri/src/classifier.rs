@@ -0,0 +1,407 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::LazyLock;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::app_config;
+use crate::file_reader;
+use crate::scanner;
+use crate::workspace::{Access, FsError, Workspace};
+
+/// Bytes read from the top of a file to classify it
+pub const HEAD_BYTES: usize = 1024;
+/// Text lines longer than this on average mean minified code
+const MINIFIED_AVERAGE_LINE: u64 = 300;
+/// Bytes at the top of a minified file holding fewer than 3 line breaks
+const MINIFIED_SNIFF_BYTES: usize = 500;
+/// Lines at the top of a file searched for a generator comment
+const HEADER_LINES: usize = 20;
+
+const LOCKFILES: &[&str] = &[
+    "composer.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "Cargo.lock",
+    "Gemfile.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "go.sum",
+];
+
+/// Directories holding third-party code
+const VENDOR_DIRS: &[&str] = &["vendor", "node_modules", "bower_components", "third_party", "third-party", "jspm_packages"];
+
+/// Comments tools put at the top of files they write, matched against one
+/// comment line with its comment markers removed
+static GENERATED_MARKER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(@generated\b|<auto-generated\b|\bcode generated by\b|\b(?:this|the) (?:file|code|class) (?:is|was|has been) (?:auto-?|automatically )?generated\b|^(?:auto-?|automatically )?generated (?:by|from|with|using)\b|^auto-?generated (?:file|code)\b)")
+        .expect("valid generated-marker regex")
+});
+
+/// Opening and closing markers of block comments and docstrings
+const BLOCK_COMMENTS: &[(&str, &str)] = &[("/*", "*/"), ("<!--", "-->"), ("{{--", "--}}"), ("\"\"\"", "\"\"\""), ("'''", "'''"), ("(*", "*)")];
+/// Line comment markers
+const LINE_COMMENTS: &[&str] = &["//", "#", "--", ";", "%", "*"];
+
+/// Text of the comment lines a file starts with, up to its first line of
+/// code; opening tags like `<?php` before the comment are skipped
+fn leading_comments(header: &str) -> Vec<&str> {
+    let mut comments = Vec::new();
+    let mut closer: Option<&str> = None;
+    for line in header.lines().take(HEADER_LINES) {
+        let mut text = line.trim();
+        if let Some(end) = closer {
+            if let Some(at) = text.find(end) {
+                text = &text[..at];
+                closer = None;
+            }
+            comments.push(text.trim_start_matches('*').trim());
+            continue;
+        }
+        if text.is_empty() || text.starts_with("<?") || text.starts_with("<!DOCTYPE") || text.starts_with("<!doctype") {
+            continue;
+        }
+        if let Some((open, end)) = BLOCK_COMMENTS.iter().find(|(open, _)| text.starts_with(open)) {
+            text = &text[open.len()..];
+            match text.find(end) {
+                Some(at) => text = &text[..at],
+                None => closer = Some(end),
+            }
+            comments.push(text.trim_start_matches('*').trim());
+        } else if let Some(marker) = LINE_COMMENTS.iter().find(|marker| text.starts_with(*marker)) {
+            comments.push(text.trim_start_matches(*marker).trim_start_matches(['/', '#', '!', '*']).trim());
+        } else {
+            break;
+        }
+    }
+    comments
+}
+
+/// Bundler output with a content hash: `app.3f9a2c1b.js`, `main-5d41402a.css`
+static HASHED_BUNDLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)[.-][0-9a-f]{8,}\.(?:js|mjs|css)$").expect("valid hashed-bundle regex")
+});
+
+/// Bundled third-party chunks and copied-in libraries
+static VENDOR_FILE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:(?:chunk-)?vendors?(?:[.~-].*)?|(?:jquery|bootstrap|lodash|moment|popper|underscore|backbone|angular|react(?:-dom)?|vue)(?:[.-]\d+(?:\.\d+)*)?(?:\.(?:slim|bundle|esm|runtime|production|development))*)\.(?:js|css)$")
+        .expect("valid vendor-file regex")
+});
+
+/// Code generators that mark their output in the file name
+static GENERATED_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:\.pb\.go|_pb2(?:_grpc)?\.py|\.g\.dart|\.generated\.\w+|\.designer\.cs)$").expect("valid generated-name regex")
+});
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FileClass {
+    Minified,
+    Generated,
+    Vendored,
+    Lockfile,
+    Binary,
+}
+
+/// Why a file is not hand-written source
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct Classification {
+    pub class: FileClass,
+    pub reason: String,
+}
+
+/// Per-project classifier settings, stored in the app config
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ClassifierSettings {
+    /// Classes left out of the file tree
+    pub hide_in_tree: Vec<FileClass>,
+    /// Classes left out of search and the analyzers (stats, tech stack, symbols)
+    pub skip_in_analysis: Vec<FileClass>,
+    /// Extra globs (relative to the project root) for generated files
+    pub generated_globs: Vec<String>,
+    /// Extra globs for vendored files
+    pub vendored_globs: Vec<String>,
+    /// Files that are always hand-written source, whatever they look like
+    pub source_globs: Vec<String>,
+}
+
+impl Default for ClassifierSettings {
+    fn default() -> Self {
+        ClassifierSettings {
+            hide_in_tree: vec![FileClass::Minified, FileClass::Generated, FileClass::Vendored],
+            skip_in_analysis: vec![
+                FileClass::Minified,
+                FileClass::Generated,
+                FileClass::Vendored,
+                FileClass::Lockfile,
+                FileClass::Binary,
+            ],
+            generated_globs: Vec::new(),
+            vendored_globs: Vec::new(),
+            source_globs: Vec::new(),
+        }
+    }
+}
+
+fn build_globs(globs: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        // Globs without a slash match file names anywhere, like in .gitignore
+        let pattern = if glob.contains('/') { glob.trim_start_matches('/').to_string() } else { format!("**/{}", glob) };
+        match Glob::new(&pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => println!("⚠️ Skipping invalid classifier glob {:?}: {}", glob, e),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+/// Tags files as minified, generated, vendored, lockfile or binary.
+/// The tree, search, the watcher and the analyzers all decide through it.
+pub struct Classifier {
+    settings: ClassifierSettings,
+    generated: GlobSet,
+    vendored: GlobSet,
+    source: GlobSet,
+}
+
+impl Classifier {
+    pub fn new(settings: ClassifierSettings) -> Classifier {
+        Classifier {
+            generated: build_globs(&settings.generated_globs),
+            vendored: build_globs(&settings.vendored_globs),
+            source: build_globs(&settings.source_globs),
+            settings,
+        }
+    }
+
+    /// Classifier for the project containing `real` (an already resolved path)
+    pub fn for_path(app: &AppHandle, real: &Path) -> Classifier {
+        let workspace = Workspace::current(app);
+        let root = workspace.project_root(real).unwrap_or(real);
+        Classifier::new(settings_for(app, root))
+    }
+
+    /// Classify a file from its path below the project root, its first
+    /// bytes, its size and, when known, its line count
+    pub fn classify(&self, relative: &Path, head: &[u8], size: u64, lines: Option<u64>) -> Option<Classification> {
+        let found = |class: FileClass, reason: String| Some(Classification { class, reason });
+        let name = relative.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let relative_str = relative.to_string_lossy().replace('\\', "/");
+
+        if self.source.is_match(&relative_str) {
+            return None;
+        }
+        if LOCKFILES.contains(&name.as_str()) {
+            return found(FileClass::Lockfile, format!("{} is a dependency lockfile", name));
+        }
+        if file_reader::detect_encoding(&head[..head.len().min(file_reader::SNIFF_BYTES)]).is_none() {
+            return found(FileClass::Binary, "Contains bytes that are not text".to_string());
+        }
+
+        if let Some(dir) = relative.parent().and_then(|p| p.iter().find(|c| VENDOR_DIRS.contains(&c.to_string_lossy().as_ref()))) {
+            return found(FileClass::Vendored, format!("Inside a {}/ directory", dir.to_string_lossy()));
+        }
+        if self.vendored.is_match(&relative_str) {
+            return found(FileClass::Vendored, "Matches a vendored glob of this project".to_string());
+        }
+        if VENDOR_FILE.is_match(&name) {
+            return found(FileClass::Vendored, format!("{} is a bundled third-party file", name));
+        }
+
+        let lower = name.to_lowercase();
+        if lower.ends_with(".min.js") || lower.ends_with(".min.css") {
+            return found(FileClass::Minified, format!("File name ends with .min.{}", lower.rsplit('.').next().unwrap_or_default()));
+        }
+
+        if lower.ends_with(".map") {
+            return found(FileClass::Generated, "Source map".to_string());
+        }
+        if relative_str.starts_with("storage/framework/views/") || relative_str.contains("/storage/framework/views/") {
+            return found(FileClass::Generated, "Compiled Blade view".to_string());
+        }
+        if HASHED_BUNDLE.is_match(&name) {
+            return found(FileClass::Generated, "Bundler output with a content hash in its name".to_string());
+        }
+        if GENERATED_NAME.is_match(&name) {
+            return found(FileClass::Generated, "Name marks it as code generator output".to_string());
+        }
+        if self.generated.is_match(&relative_str) {
+            return found(FileClass::Generated, "Matches a generated glob of this project".to_string());
+        }
+        let header = String::from_utf8_lossy(&head[..head.len().min(HEAD_BYTES)]);
+        if let Some(marker) = leading_comments(&header).into_iter().find_map(|line| GENERATED_MARKER.find(line)) {
+            return found(FileClass::Generated, format!("Header comment contains \"{}\"", marker.as_str()));
+        }
+
+        let extension = lower.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+        if !matches!(extension, "js" | "jsx" | "mjs" | "cjs" | "css") {
+            return None;
+        }
+        let start = &head[..head.len().min(MINIFIED_SNIFF_BYTES)];
+        if start.len() > 100 && start.iter().filter(|b| **b == b'\n').count() < 3 {
+            return found(FileClass::Minified, format!("Fewer than 3 line breaks in the first {} bytes", MINIFIED_SNIFF_BYTES));
+        }
+        if let Some(lines) = lines.filter(|lines| *lines > 0) {
+            let average = size / lines;
+            if average > MINIFIED_AVERAGE_LINE {
+                return found(FileClass::Minified, format!("Lines are {} characters long on average", average));
+            }
+        }
+        None
+    }
+
+    /// `classify` for a file on disk, reading its first bytes
+    pub fn classify_file(&self, path: &Path, relative: &Path) -> Option<Classification> {
+        let mut head = Vec::with_capacity(HEAD_BYTES);
+        let file = File::open(path).ok()?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        file.take(HEAD_BYTES as u64).read_to_end(&mut head).ok()?;
+        self.classify(relative, &head, size, None)
+    }
+
+    pub fn hides_in_tree(&self, classification: &Classification) -> bool {
+        self.settings.hide_in_tree.contains(&classification.class)
+    }
+
+    pub fn skips_in_analysis(&self, classification: &Classification) -> bool {
+        self.settings.skip_in_analysis.contains(&classification.class)
+    }
+
+    /// Whether the tree leaves out the file at `path`
+    pub fn is_hidden(&self, path: &Path, relative: &Path) -> bool {
+        !self.settings.hide_in_tree.is_empty() && self.classify_file(path, relative).is_some_and(|c| self.hides_in_tree(&c))
+    }
+}
+
+/// Settings of the project at `root`, or the defaults
+pub fn settings_for(app: &AppHandle, root: &Path) -> ClassifierSettings {
+    app_config::load(app)
+        .classifier_settings
+        .remove(root.to_string_lossy().as_ref())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FileClassification {
+    pub path: String,
+    pub classification: Option<Classification>,
+    pub hidden_in_tree: bool,
+    pub skipped_in_analysis: bool,
+}
+
+/// Classify one file with its project's settings
+#[tauri::command]
+pub async fn classify_file(app: AppHandle, path: String) -> Result<FileClassification, FsError> {
+    let real = Workspace::current(&app).resolve(&path, Access::Read)?;
+    let workspace = Workspace::current(&app);
+    let root = workspace.project_root(&real).unwrap_or(real.parent().unwrap_or(&real));
+    let classifier = Classifier::new(settings_for(&app, root));
+
+    let relative = real.strip_prefix(root).unwrap_or(&real);
+    let classification = classifier.classify_file(&real, relative);
+    Ok(FileClassification {
+        path,
+        hidden_in_tree: classification.as_ref().is_some_and(|c| classifier.hides_in_tree(c)),
+        skipped_in_analysis: classification.as_ref().is_some_and(|c| classifier.skips_in_analysis(c)),
+        classification,
+    })
+}
+
+/// Classifier settings of a project
+#[tauri::command]
+pub async fn get_classifier_settings(app: AppHandle, project_path: String) -> Result<ClassifierSettings, FsError> {
+    let real = Workspace::current(&app).resolve(&project_path, Access::Read)?;
+    let workspace = Workspace::current(&app);
+    Ok(settings_for(&app, workspace.project_root(&real).unwrap_or(&real)))
+}
+
+/// Replace a project's classifier settings; `None` restores the defaults.
+/// Returns the settings now in effect.
+#[tauri::command]
+pub async fn set_classifier_settings(
+    app: AppHandle,
+    project_path: String,
+    settings: Option<ClassifierSettings>,
+) -> Result<ClassifierSettings, String> {
+    let real = Workspace::current(&app).resolve(&project_path, Access::Read).map_err(|e| e.message().to_string())?;
+    let workspace = Workspace::current(&app);
+    let root = workspace.project_root(&real).unwrap_or(&real).to_string_lossy().to_string();
+
+    app_config::modify(&app, |config| match settings {
+        Some(settings) => config.classifier_settings.insert(root.clone(), settings),
+        None => config.classifier_settings.remove(&root),
+    })?;
+    // Cached scans carry classifications made with the old settings
+    scanner::clear_cache();
+
+    println!("⚙️  Classifier settings updated for {}", root);
+    Ok(settings_for(&app, Path::new(&root)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(relative: &str, content: &str) -> Option<FileClass> {
+        let classifier = Classifier::new(ClassifierSettings::default());
+        let head = content.as_bytes();
+        classifier.classify(Path::new(relative), head, head.len() as u64, None).map(|c| c.class)
+    }
+
+    #[test]
+    fn classifies_by_path_and_name() {
+        assert_eq!(classify("composer.lock", "{}"), Some(FileClass::Lockfile));
+        assert_eq!(classify("public/js/app.js.map", "{}"), Some(FileClass::Generated));
+        assert_eq!(classify("storage/framework/views/3f9a.php", "<?php echo 1;"), Some(FileClass::Generated));
+        assert_eq!(classify("public/build/assets/app.5d41402a.js", "let a = 1;\n"), Some(FileClass::Generated));
+        assert_eq!(classify("public/js/chunk-vendors.js", "let a = 1;\n"), Some(FileClass::Vendored));
+        assert_eq!(classify("assets/jquery-3.6.0.js", "let a = 1;\n"), Some(FileClass::Vendored));
+        assert_eq!(classify("lib/third_party/x.php", "<?php\n"), Some(FileClass::Vendored));
+        assert_eq!(classify("image.png", "\u{89}PNG\r\n\u{1a}\n\0\0\0\rIHDR"), Some(FileClass::Binary));
+        assert_eq!(classify("src/vendors.php", "<?php\n"), None);
+        assert_eq!(classify("src/autoGenerateDB.php", "<?php\n// autoGenerateDB helper\n"), None);
+    }
+
+    #[test]
+    fn generator_comments_in_the_header() {
+        assert_eq!(classify("api/client.go", "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n"), Some(FileClass::Generated));
+        assert_eq!(classify("src/Models.php", "<?php\n\n/**\n * This file is auto-generated.\n */\nclass Models {}\n"), Some(FileClass::Generated));
+        assert_eq!(classify("src/schema.ts", "/* eslint-disable */\n// @generated\nexport type A = 1;\n"), Some(FileClass::Generated));
+        assert_eq!(classify("models.py", "\"\"\"\nGenerated by sqlacodegen\n\"\"\"\nimport x\n"), Some(FileClass::Generated));
+
+        // Prose mentions and markers below the first line of code don't count
+        assert_eq!(classify("src/User.php", "<?php\n// IDs are auto-generated by the DB\nclass User {}\n"), None);
+        assert_eq!(classify("src/Form.php", "<?php\n/**\n * Do not edit the fields after saving.\n */\n"), None);
+        assert_eq!(classify("src/Seed.php", "<?php\nclass Seed {}\n// This file is generated\n"), None);
+    }
+
+    #[test]
+    fn minified_css_and_js_by_content() {
+        let minified = format!("body{{margin:0}}{}", ".a{color:red}".repeat(20));
+        assert_eq!(classify("public/css/site.css", &minified), Some(FileClass::Minified));
+        assert_eq!(classify("public/css/site.css", &"p {\n  color: red;\n}\n".repeat(20)), None);
+    }
+
+    #[test]
+    fn project_globs_override_detection() {
+        let classifier = Classifier::new(ClassifierSettings {
+            generated_globs: vec!["api/*.php".to_string()],
+            source_globs: vec!["jquery-*.js".to_string()],
+            ..ClassifierSettings::default()
+        });
+        let class = |relative: &str| classifier.classify(Path::new(relative), b"x = 1\n", 6, None).map(|c| c.class);
+        assert_eq!(class("api/client.php"), Some(FileClass::Generated));
+        assert_eq!(class("assets/jquery-3.6.0.js"), None);
+    }
+}
@@ -0,0 +1,711 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::app_config::{self, AppConfig};
+use crate::commands::{self, TestResult};
+use crate::test_runner::now_millis;
+use crate::workspace::{Access, FsError, Workspace};
+
+/// Rules used for projects without their own policy in the app config
+const BUNDLED_POLICY: &str = include_str!("../command-policy.json");
+const AUDIT_FILE: &str = "command-audit.jsonl";
+/// How long a command waits for `approve_command` before it is dropped
+const APPROVAL_TTL_MS: u64 = 5 * 60 * 1000;
+
+// Serializes appends so concurrent commands never interleave audit lines
+static AUDIT_LOCK: Mutex<()> = Mutex::new(());
+static PENDING: LazyLock<Mutex<HashMap<String, PendingCommand>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+static APPROVAL_COUNTER: Mutex<u64> = Mutex::new(0);
+
+/// Arguments that always need confirmation, whatever the rules file allows
+static DESTRUCTIVE: LazyLock<Vec<(&'static str, Regex, &'static str)>> = LazyLock::new(|| {
+    [
+        ("rm", r"(^| )(-[a-zA-Z]*[rRf][a-zA-Z]*|--recursive|--force)( |$)", "rm -r / rm -f"),
+        ("git", r"^push( .*)? (-f|--force|--force-with-lease|--mirror|--delete|\+\S+)( |$)", "git push --force"),
+        ("git", r"^reset( .*)? --hard( |$)", "git reset --hard"),
+        ("git", r"^clean( .*)? -[a-zA-Z]*f", "git clean -f"),
+        ("git", r"^branch( .*)? (-D|--delete --force)( |$)", "git branch -D"),
+        ("git", r"^(checkout|restore)( .*)? (--|\.)( |$)", "discarding local changes"),
+        ("chmod", r"(^| )-R( |$)", "recursive chmod"),
+        ("chown", r"(^| )-R( |$)", "recursive chown"),
+        ("find", r"(^| )(-delete|-exec|-execdir)( |$)", "find -delete / -exec"),
+        ("dd", r".*", "dd"),
+    ]
+    .into_iter()
+    .filter_map(|(program, pattern, label)| Regex::new(pattern).ok().map(|re| (program, re, label)))
+    .collect()
+});
+
+/// Programs that run code from the project (package.json scripts, composer
+/// scripts, git hooks, filters and `core.fsmonitor` from a writable .git/config)
+/// always need confirmation. The pattern lists the arguments that do not.
+static SCRIPT_RUNNERS: LazyLock<Vec<(&'static str, Option<Regex>, &'static str)>> = LazyLock::new(|| {
+    [
+        ("npm", Some(r"^(ls|list|view|info|outdated|--version|-v)( |$)"), "npm runs package.json scripts"),
+        ("yarn", Some(r"^(list|info|outdated|--version|-v)( |$)"), "yarn runs package.json scripts"),
+        ("pnpm", Some(r"^(ls|list|outdated|--version|-v)( |$)"), "pnpm runs package.json scripts"),
+        ("npx", None, "npx runs package binaries"),
+        ("composer", Some(r"^(validate|show|outdated|diagnose|--version|-V)( |$)"), "Composer runs composer.json scripts"),
+        ("git", None, "git runs hooks, filters and core.fsmonitor from the project's .git/config"),
+        ("make", None, "make runs Makefile recipes"),
+        ("php", Some(r"^(-l( [^- ][^ ]*)+|--version|-v|-m|-i)$"), "php runs project scripts"),
+        ("node", Some(r"^(--version|-v)$"), "node runs project scripts"),
+        ("python", Some(r"^(--version|-V)$"), "python runs project scripts"),
+        ("python3", Some(r"^(--version|-V)$"), "python runs project scripts"),
+        ("sh", None, "shells run project scripts"),
+        ("bash", None, "shells run project scripts"),
+        ("zsh", None, "shells run project scripts"),
+    ]
+    .into_iter()
+    .filter_map(|(program, safe, label)| match safe {
+        Some(pattern) => Regex::new(pattern).ok().map(|re| (program, Some(re), label)),
+        None => Some((program, None, label)),
+    })
+    .collect()
+});
+
+/// Ordered from least to most restrictive
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    Allow,
+    Confirm,
+    Deny,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PolicyRule {
+    /// Program name as found on PATH (`rm`, not `/bin/rm`)
+    pub program: String,
+    /// Regex matched against the arguments joined with single spaces; any arguments if absent
+    #[serde(default)]
+    pub args: Option<String>,
+    pub decision: Decision,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolicyFile {
+    rules: Vec<PolicyRule>,
+}
+
+pub struct Policy {
+    rules: Vec<(PolicyRule, Option<Regex>)>,
+    /// Where the rules came from, for audit entries
+    source: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub decision: Decision,
+    pub reason: String,
+}
+
+/// Error returned by `execute_command` and `approve_command`, tagged by `kind`
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CommandError {
+    Denied { message: String },
+    /// Call `approve_command` with `approval_id` to run it
+    NeedsApproval { approval_id: String, expires_at: u64, message: String },
+    Failed { message: String },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Denied { message }
+            | CommandError::NeedsApproval { message, .. }
+            | CommandError::Failed { message } => f.write_str(message),
+        }
+    }
+}
+
+impl From<FsError> for CommandError {
+    fn from(error: FsError) -> CommandError {
+        CommandError::Denied { message: error.to_string() }
+    }
+}
+
+/// A command waiting for the user to confirm it
+#[derive(Debug, Serialize, Clone)]
+pub struct PendingCommand {
+    pub approval_id: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: String,
+    pub reason: String,
+    pub requested_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum AuditEvent {
+    Evaluated,
+    Approved,
+    Rejected,
+    Expired,
+    Executed,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditEntry<'a> {
+    timestamp: u64,
+    event: AuditEvent,
+    program: &'a str,
+    args: &'a [String],
+    cwd: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decision: Option<Decision>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    approval_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+/// File name of the program `execute_command` would run, with symlinks in a
+/// path resolved, so `/bin/rm` and `./node_modules/.bin/../../bin/rm` are `rm`
+fn program_name(program: &str, cwd: &Path) -> String {
+    let path = Path::new(program);
+    let real = if path.components().count() > 1 { fs::canonicalize(cwd.join(path)).ok() } else { None };
+    let name = real
+        .as_deref()
+        .unwrap_or(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    normalize_program(&name)
+}
+
+/// macOS and Windows file systems are case-insensitive by default, so `RM` runs `rm`
+fn normalize_program(name: &str) -> String {
+    let name = if cfg!(any(target_os = "macos", windows)) { name.to_lowercase() } else { name.to_string() };
+    if cfg!(windows) {
+        for extension in [".exe", ".cmd", ".bat", ".com"] {
+            if let Some(stem) = name.strip_suffix(extension) {
+                return stem.to_string();
+            }
+        }
+    }
+    name
+}
+
+impl Policy {
+    pub fn parse(content: &str, source: &str) -> Result<Policy, String> {
+        let file: PolicyFile = serde_json::from_str(content)
+            .map_err(|e| format!("Invalid command policy {}: {}", source, e))?;
+        Policy::from_rules(file.rules, source)
+    }
+
+    pub fn from_rules(rules: Vec<PolicyRule>, source: &str) -> Result<Policy, String> {
+        let mut compiled = Vec::new();
+        for rule in rules {
+            // Rules match the bare program name; anything else would never apply
+            if rule.program.is_empty() || rule.program.contains(['/', '\\']) {
+                return Err(format!("Invalid program {:?} in {}: use the bare program name", rule.program, source));
+            }
+            let pattern = match &rule.args {
+                Some(args) => Some(
+                    Regex::new(args)
+                        .map_err(|e| format!("Invalid argument pattern for '{}' in {}: {}", rule.program, source, e))?,
+                ),
+                None => None,
+            };
+            compiled.push((rule, pattern));
+        }
+
+        Ok(Policy { rules: compiled, source: source.to_string() })
+    }
+
+    /// The project's rules from the app config, or the bundled defaults
+    pub fn load(config: &AppConfig, project_root: &Path) -> Result<Policy, String> {
+        match config.command_policies.get(project_root.to_string_lossy().as_ref()) {
+            Some(rules) => Policy::from_rules(rules.clone(), &format!("the command policy of {:?}", project_root)),
+            None => Policy::parse(BUNDLED_POLICY, "bundled defaults"),
+        }
+    }
+
+    pub fn rules(&self) -> Vec<PolicyRule> {
+        self.rules.iter().map(|(rule, _)| rule.clone()).collect()
+    }
+
+    /// The most restrictive matching rule wins; no match means deny.
+    /// Destructive arguments, script runners and programs given as a path
+    /// raise an allow to confirm.
+    pub fn evaluate(&self, program: &str, args: &[String], cwd: &Path) -> Evaluation {
+        let name = program_name(program, cwd);
+        let joined = args.join(" ");
+
+        let matched = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, (rule, pattern))| {
+                normalize_program(&rule.program) == name && pattern.as_ref().is_none_or(|re| re.is_match(&joined))
+            })
+            .max_by_key(|(_, (rule, _))| rule.decision);
+
+        let Some((index, (rule, _))) = matched else {
+            return Evaluation {
+                decision: Decision::Deny,
+                reason: format!("No rule in {} allows `{} {}`", self.source, program, joined),
+            };
+        };
+
+        let describe = || {
+            rule.description
+                .clone()
+                .unwrap_or_else(|| format!("rule {} in {}", index + 1, self.source))
+        };
+
+        if rule.decision == Decision::Allow {
+            let confirm = |why: String| Evaluation { decision: Decision::Confirm, reason: format!("{}, allowed by {}", why, describe()) };
+
+            let destructive = DESTRUCTIVE
+                .iter()
+                .find(|(program, pattern, _)| *program == name && pattern.is_match(&joined));
+            if let Some((_, _, label)) = destructive {
+                return confirm(format!("Destructive pattern ({})", label));
+            }
+            let runner = SCRIPT_RUNNERS
+                .iter()
+                .find(|(program, safe, _)| *program == name && !safe.as_ref().is_some_and(|re| re.is_match(&joined)));
+            if let Some((_, _, label)) = runner {
+                return confirm(format!("Runs project code ({})", label));
+            }
+            if Path::new(program).components().count() > 1 {
+                return confirm(format!("Runs {} rather than the {} found on PATH", program, name));
+            }
+        }
+
+        Evaluation { decision: rule.decision, reason: describe() }
+    }
+}
+
+fn audit_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(AUDIT_FILE))
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))
+}
+
+/// Append one line to the audit log. Commands do not run if this fails.
+fn audit(app: &AppHandle, entry: AuditEntry) -> Result<(), String> {
+    let path = audit_path(app)?;
+    let mut line = serde_json::to_string(&entry).map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+    line.push('\n');
+
+    let _guard = AUDIT_LOCK.lock().map_err(|e| format!("Audit log unavailable: {}", e))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create audit log dir: {}", e))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open audit log {:?}: {}", path, e))?;
+    file.write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to write audit log {:?}: {}", path, e))
+}
+
+fn entry<'a>(event: AuditEvent, program: &'a str, args: &'a [String], cwd: &'a str) -> AuditEntry<'a> {
+    AuditEntry {
+        timestamp: now_millis(),
+        event,
+        program,
+        args,
+        cwd,
+        decision: None,
+        reason: None,
+        approval_id: None,
+        success: None,
+        error: None,
+    }
+}
+
+/// Resolve the working directory and evaluate the command against its project's policy
+fn evaluate(app: &AppHandle, program: &str, args: &[String], cwd: &str) -> Result<(PathBuf, Evaluation), CommandError> {
+    let workspace = Workspace::current(app);
+    let real_cwd = workspace.resolve(cwd, Access::Read)?;
+    let project_root = workspace.project_root(&real_cwd).ok_or_else(|| CommandError::Denied {
+        message: format!("🚫 Commands can only run inside a registered project root, not {}", cwd),
+    })?;
+
+    let policy = Policy::load(&app_config::load(app), project_root).map_err(|message| CommandError::Denied { message })?;
+    let evaluation = policy.evaluate(program, args, &real_cwd);
+    Ok((real_cwd, evaluation))
+}
+
+/// The project root containing `project_path`; policies exist only for those
+fn policy_root(app: &AppHandle, project_path: &str) -> Result<String, String> {
+    let workspace = Workspace::current(app);
+    let real = workspace.resolve(project_path, Access::Read)?;
+    workspace
+        .project_root(&real)
+        .map(|root| root.to_string_lossy().to_string())
+        .ok_or_else(|| format!("🚫 {} is not inside a registered project root", project_path))
+}
+
+/// Run a command that passed the policy and record the outcome
+fn run_audited(app: &AppHandle, program: &str, args: &[String], cwd: &Path, approval_id: Option<&str>) -> Result<TestResult, CommandError> {
+    let cwd_display = cwd.to_string_lossy();
+    let result = commands::run_command(program, args, cwd);
+
+    let (success, error) = match &result {
+        Ok(output) => (Some(output.success), None),
+        Err(e) => (None, Some(e.as_str())),
+    };
+    let logged = audit(app, AuditEntry {
+        approval_id,
+        success,
+        error,
+        ..entry(AuditEvent::Executed, program, args, &cwd_display)
+    });
+    if let Err(e) = logged {
+        println!("⚠️ {}", e);
+    }
+
+    result.map_err(|message| CommandError::Failed { message })
+}
+
+/// Apply the policy to an `execute_command` call.
+///
+/// Allowed commands run immediately; commands needing confirmation are parked
+/// until `approve_command` and everything else is denied.
+pub fn execute(app: &AppHandle, program: &str, args: &[String], cwd: Option<&str>) -> Result<TestResult, CommandError> {
+    let Some(cwd) = cwd else {
+        let message = "🚫 execute_command needs a cwd inside a registered project root".to_string();
+        let _ = audit(app, AuditEntry {
+            decision: Some(Decision::Deny),
+            reason: Some(&message),
+            ..entry(AuditEvent::Evaluated, program, args, "")
+        });
+        return Err(CommandError::Denied { message });
+    };
+
+    let (real_cwd, evaluation) = match evaluate(app, program, args, cwd) {
+        Ok(result) => result,
+        Err(error) => {
+            let message = error.to_string();
+            let _ = audit(app, AuditEntry {
+                decision: Some(Decision::Deny),
+                reason: Some(&message),
+                ..entry(AuditEvent::Evaluated, program, args, cwd)
+            });
+            return Err(error);
+        }
+    };
+
+    let approval_id = (evaluation.decision == Decision::Confirm).then(next_approval_id);
+    audit(app, AuditEntry {
+        decision: Some(evaluation.decision),
+        reason: Some(&evaluation.reason),
+        approval_id: approval_id.as_deref(),
+        ..entry(AuditEvent::Evaluated, program, args, cwd)
+    })
+    .map_err(|message| CommandError::Failed { message })?;
+
+    println!("🛡️  {:?}: {} {} ({})", evaluation.decision, program, args.join(" "), evaluation.reason);
+
+    match (evaluation.decision, approval_id) {
+        (Decision::Allow, _) => run_audited(app, program, args, &real_cwd, None),
+        (Decision::Confirm, Some(approval_id)) => {
+            let requested_at = now_millis();
+            let pending = PendingCommand {
+                approval_id: approval_id.clone(),
+                program: program.to_string(),
+                args: args.to_vec(),
+                cwd: cwd.to_string(),
+                reason: evaluation.reason.clone(),
+                requested_at,
+                expires_at: requested_at + APPROVAL_TTL_MS,
+            };
+            if let Ok(mut commands) = PENDING.lock() {
+                commands.insert(approval_id.clone(), pending.clone());
+            }
+            Err(CommandError::NeedsApproval {
+                approval_id,
+                expires_at: pending.expires_at,
+                message: format!("⏸️ `{} {}` needs confirmation: {}", program, args.join(" "), evaluation.reason),
+            })
+        }
+        _ => Err(CommandError::Denied { message: format!("🚫 {}", evaluation.reason) }),
+    }
+}
+
+fn next_approval_id() -> String {
+    let mut counter = APPROVAL_COUNTER.lock().unwrap_or_else(|e| e.into_inner());
+    *counter += 1;
+    format!("approval-{}-{}", now_millis(), counter)
+}
+
+/// Take a pending command out of the queue, auditing it if it already expired
+fn take_pending(app: &AppHandle, approval_id: &str) -> Result<PendingCommand, CommandError> {
+    let pending = PENDING
+        .lock()
+        .ok()
+        .and_then(|mut commands| commands.remove(approval_id))
+        .ok_or_else(|| CommandError::Denied { message: format!("Unknown or already handled approval: {}", approval_id) })?;
+
+    if now_millis() > pending.expires_at {
+        let _ = audit(app, AuditEntry {
+            approval_id: Some(approval_id),
+            ..entry(AuditEvent::Expired, &pending.program, &pending.args, &pending.cwd)
+        });
+        return Err(CommandError::Denied { message: format!("Approval {} expired", approval_id) });
+    }
+    Ok(pending)
+}
+
+/// Commands waiting for confirmation; expired ones are dropped
+#[tauri::command]
+pub async fn list_pending_commands(app: AppHandle) -> Result<Vec<PendingCommand>, String> {
+    let now = now_millis();
+    let expired: Vec<PendingCommand> = match PENDING.lock() {
+        Ok(mut commands) => {
+            let expired = commands.values().filter(|c| c.expires_at < now).cloned().collect();
+            commands.retain(|_, c| c.expires_at >= now);
+            expired
+        }
+        Err(e) => return Err(format!("Pending commands unavailable: {}", e)),
+    };
+    for command in &expired {
+        let _ = audit(&app, AuditEntry {
+            approval_id: Some(&command.approval_id),
+            ..entry(AuditEvent::Expired, &command.program, &command.args, &command.cwd)
+        });
+    }
+
+    let mut pending: Vec<PendingCommand> = PENDING
+        .lock()
+        .map(|commands| commands.values().cloned().collect())
+        .unwrap_or_default();
+    pending.sort_by_key(|c| c.requested_at);
+    Ok(pending)
+}
+
+/// Run a command that was parked for confirmation.
+///
+/// The policy is evaluated again, so a command that has since been denied by
+/// the rules file does not run.
+#[tauri::command]
+pub async fn approve_command(app: AppHandle, approval_id: String) -> Result<TestResult, CommandError> {
+    let pending = take_pending(&app, &approval_id)?;
+    let (real_cwd, evaluation) = evaluate(&app, &pending.program, &pending.args, &pending.cwd)?;
+
+    if evaluation.decision == Decision::Deny {
+        let _ = audit(&app, AuditEntry {
+            decision: Some(Decision::Deny),
+            reason: Some(&evaluation.reason),
+            approval_id: Some(&approval_id),
+            ..entry(AuditEvent::Rejected, &pending.program, &pending.args, &pending.cwd)
+        });
+        return Err(CommandError::Denied { message: format!("🚫 {}", evaluation.reason) });
+    }
+
+    audit(&app, AuditEntry {
+        decision: Some(evaluation.decision),
+        reason: Some(&evaluation.reason),
+        approval_id: Some(&approval_id),
+        ..entry(AuditEvent::Approved, &pending.program, &pending.args, &pending.cwd)
+    })
+    .map_err(|message| CommandError::Failed { message })?;
+
+    println!("✅ Approved: {} {}", pending.program, pending.args.join(" "));
+    run_audited(&app, &pending.program, &pending.args, &real_cwd, Some(&approval_id))
+}
+
+#[tauri::command]
+pub async fn reject_command(app: AppHandle, approval_id: String) -> Result<(), String> {
+    let pending = take_pending(&app, &approval_id).map_err(|e| e.to_string())?;
+    audit(&app, AuditEntry {
+        approval_id: Some(&approval_id),
+        ..entry(AuditEvent::Rejected, &pending.program, &pending.args, &pending.cwd)
+    })
+}
+
+/// Command rules of the project containing `project_path`: its own from the
+/// app config, or the bundled defaults
+#[tauri::command]
+pub async fn get_command_policy(app: AppHandle, project_path: String) -> Result<Vec<PolicyRule>, String> {
+    let root = policy_root(&app, &project_path)?;
+    Ok(Policy::load(&app_config::load(&app), Path::new(&root))?.rules())
+}
+
+/// Replace the command rules of the project containing `project_path`;
+/// `None` restores the bundled defaults. Returns the rules now in effect.
+///
+/// Rules are validated before anything is saved, and since they can allow
+/// commands, new rules need the user's consent in a native dialog. They are
+/// stored in the app config, out of reach of the file commands.
+#[tauri::command]
+pub async fn set_command_policy(app: AppHandle, project_path: String, rules: Option<Vec<PolicyRule>>) -> Result<Vec<PolicyRule>, String> {
+    let root = policy_root(&app, &project_path)?;
+    if let Some(rules) = &rules {
+        Policy::from_rules(rules.clone(), &format!("the command policy of {:?}", root))?;
+
+        let summary: Vec<String> = rules
+            .iter()
+            .map(|rule| format!("{:?}: {} {}", rule.decision, rule.program, rule.args.as_deref().unwrap_or("(any arguments)")))
+            .collect();
+        let allowed = app
+            .dialog()
+            .message(format!("Replace the command rules of this project?\n\n{}\n\n{}", root, summary.join("\n")))
+            .title("Command policy")
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom("Replace".to_string(), "Cancel".to_string()))
+            .blocking_show();
+        if !allowed {
+            return Err("❌ The command policy change was not allowed".to_string());
+        }
+    }
+
+    app_config::modify(&app, |config| match rules {
+        Some(rules) => config.command_policies.insert(root.clone(), rules),
+        None => config.command_policies.remove(&root),
+    })?;
+
+    println!("⚙️  Command policy updated for {}", root);
+    Ok(Policy::load(&app_config::load(&app), Path::new(&root))?.rules())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn decide(policy: &Policy, program: &str, line: &str, cwd: &Path) -> Decision {
+        policy.evaluate(program, &args(line), cwd).decision
+    }
+
+    fn allow_all(programs: &[&str]) -> Policy {
+        let rules = programs
+            .iter()
+            .map(|program| PolicyRule { program: program.to_string(), args: None, decision: Decision::Allow, description: None })
+            .collect();
+        Policy::from_rules(rules, "test").unwrap()
+    }
+
+    #[test]
+    fn bundled_policy_decisions() {
+        let cwd = TempDir::new().unwrap();
+        let policy = Policy::load(&AppConfig::default(), cwd.path()).unwrap();
+        let cases = [
+            ("ls", "-la", Decision::Allow),
+            ("php", "-l src/index.php", Decision::Allow),
+            ("npm", "ls", Decision::Allow),
+            ("npm", "test", Decision::Confirm),
+            ("npm", "run build", Decision::Confirm),
+            ("composer", "install", Decision::Confirm),
+            ("composer", "require evil/package", Decision::Deny),
+            ("git", "status", Decision::Confirm),
+            ("rm", "-rf build", Decision::Confirm),
+            ("curl", "https://example.com", Decision::Deny),
+        ];
+        for (program, line, expected) in cases {
+            assert_eq!(decide(&policy, program, line, cwd.path()), expected, "{} {}", program, line);
+        }
+    }
+
+    #[test]
+    fn project_policy_comes_from_the_app_config() {
+        let cwd = TempDir::new().unwrap();
+        let mut config = AppConfig::default();
+        config.command_policies.insert(cwd.path().to_string_lossy().to_string(), vec![PolicyRule {
+            program: "curl".to_string(),
+            args: Some("^-I ".to_string()),
+            decision: Decision::Allow,
+            description: Some("Header checks".to_string()),
+        }]);
+
+        let policy = Policy::load(&config, cwd.path()).unwrap();
+        let evaluation = policy.evaluate("curl", &args("-I https://example.com"), cwd.path());
+        assert_eq!(evaluation.decision, Decision::Allow);
+        assert_eq!(evaluation.reason, "Header checks");
+        assert_eq!(decide(&policy, "ls", "", cwd.path()), Decision::Deny);
+        assert_eq!(policy.rules()[0].program, "curl");
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let rule = |program: &str, args: Option<&str>| PolicyRule {
+            program: program.to_string(),
+            args: args.map(str::to_string),
+            decision: Decision::Allow,
+            description: None,
+        };
+        assert!(Policy::from_rules(vec![rule("curl", Some("^-I "))], "test").is_ok());
+        for rules in [vec![rule("curl", Some("(-I"))], vec![rule("/usr/bin/curl", None)], vec![rule("", None)]] {
+            assert!(Policy::from_rules(rules, "test").is_err());
+        }
+    }
+
+    #[test]
+    fn script_runners_and_destructive_arguments_need_confirmation() {
+        let cwd = TempDir::new().unwrap();
+        let policy = allow_all(&["npm", "composer", "git", "php", "rm", "find"]);
+        let cases = [
+            ("npm", "run build", Decision::Confirm),
+            ("npm", "ci", Decision::Confirm),
+            ("npm", "outdated", Decision::Allow),
+            ("composer", "install", Decision::Confirm),
+            ("composer", "validate", Decision::Allow),
+            ("git", "status", Decision::Confirm),
+            ("php", "-l index.php", Decision::Allow),
+            ("php", "artisan migrate", Decision::Confirm),
+            ("php", "-l -r phpinfo();", Decision::Confirm),
+            ("rm", "old.log", Decision::Allow),
+            ("rm", "-rf build", Decision::Confirm),
+            ("find", ". -name *.tmp -delete", Decision::Confirm),
+        ];
+        for (program, line, expected) in cases {
+            assert_eq!(decide(&policy, program, line, cwd.path()), expected, "{} {}", program, line);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn programs_are_matched_on_their_canonical_name() {
+        let dir = TempDir::new().unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(bin.join("rm"), "").unwrap();
+        std::os::unix::fs::symlink(bin.join("rm"), dir.path().join("cleanup")).unwrap();
+
+        let policy = allow_all(&["rm"]);
+        let rm = bin.join("rm").to_string_lossy().to_string();
+        assert_eq!(decide(&policy, &rm, "-rf /", dir.path()), Decision::Confirm);
+        assert_eq!(decide(&policy, "./cleanup", "-rf /", dir.path()), Decision::Confirm);
+        // A path is confirmed even without destructive arguments; it may not be the rm on PATH
+        assert_eq!(decide(&policy, "./cleanup", "old.log", dir.path()), Decision::Confirm);
+        assert_eq!(decide(&policy, "rm", "old.log", dir.path()), Decision::Allow);
+        // Denied programs stay denied under another name
+        assert_eq!(decide(&allow_all(&["ls"]), "./cleanup", "old.log", dir.path()), Decision::Deny);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn program_names_are_case_insensitive_on_macos() {
+        let cwd = TempDir::new().unwrap();
+        assert_eq!(decide(&allow_all(&["rm"]), "RM", "-rf build", cwd.path()), Decision::Confirm);
+        assert_eq!(decide(&allow_all(&["ls"]), "GIT", "status", cwd.path()), Decision::Deny);
+    }
+}
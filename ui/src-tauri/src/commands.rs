@@ -1,15 +1,38 @@
 use std::process::Command;
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
 use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::app_config;
+use crate::classifier::Classifier;
+use crate::command_policy::{self, CommandError};
+use crate::file_reader::{self, TextEncoding};
+use crate::file_versions;
+use crate::ignore_rules::IgnoreRules;
+use crate::installation;
+use crate::limits::{ResourceLimit, RunLimits};
+use crate::report::TestReport;
+use crate::job_queue;
+use crate::test_runner::RunMeta;
+use crate::test_suites::{self, RunMode};
+use crate::workspace::{self, Access, FsError, Workspace};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TestResult {
     pub success: bool,
     pub output: String,
     pub error: Option<String>,
+    /// Parsed Scout94 results; `None` for plain commands
+    pub report: Option<TestReport>,
+    /// Set when a timeout or resource limit killed the run
+    pub limit_exceeded: Option<ResourceLimit>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,89 +44,137 @@ pub struct FileNode {
     pub children: Option<Vec<FileNode>>,
 }
 
+/// Visible entries listed per directory; the rest are reported as truncated
+const MAX_DIRECTORY_ENTRIES: usize = 10_000;
+const DEFAULT_CHILDREN_LIMIT: usize = 200;
+const MAX_CHILDREN_LIMIT: usize = 1_000;
+/// Nodes returned by one `read_directory_tree` call, across all levels
+const MAX_TREE_ENTRIES: usize = 20_000;
+/// Directory listings kept for paging through with `list_children`
+const MAX_CACHED_LISTINGS: usize = 64;
+
+/// Sorted visible children of a directory, reused for its later pages
+struct Listing {
+    /// Real path of the directory
+    dir: PathBuf,
+    /// Modification time of the directory when it was listed
+    modified: Option<SystemTime>,
+    children: Vec<VisibleChild>,
+    truncated: bool,
+    /// Visible entries of each child directory by name, with the child's
+    /// modification time when they were counted
+    counts: Mutex<HashMap<String, (Option<SystemTime>, usize)>>,
+}
+
+// Listings of recently paged directories, oldest first
+static LISTINGS: LazyLock<Mutex<Vec<Arc<Listing>>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChildEntry {
+    pub name: String,
+    pub path: String,
+    pub is_directory: bool,
+    pub language: Option<String>,
+    /// Visible children of a directory, counting at most `MAX_DIRECTORY_ENTRIES`
+    pub child_count: Option<usize>,
+    pub has_children: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChildPage {
+    pub path: String,
+    pub entries: Vec<ChildEntry>,
+    /// Pass back as `cursor` for the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+    /// Visible entries in the directory (up to the cap)
+    pub total: usize,
+    /// The directory has more than `MAX_DIRECTORY_ENTRIES` entries and the rest are not listed
+    pub truncated: bool,
+}
+
+/// Queue a Scout94 test run and return its run id immediately.
+///
+/// Higher `priority` runs start first. Output is streamed through
+/// `test-run-output` events and the final `TestResult` arrives with the
+/// `test-run-complete` event.
 #[tauri::command]
-pub async fn run_scout94_test(project_path: String, test_type: String) -> Result<TestResult, String> {
+pub async fn run_scout94_test(app: AppHandle, project_path: String, test_type: String, priority: Option<i32>) -> Result<String, String> {
     println!("🚀 Running REAL Scout94 test: {} on {}", test_type, project_path);
     
-    // Get Scout94 directory (parent of CascadeProjects)
-    let home_dir = std::env::var("HOME").unwrap_or_else(|_| "/Users/mac".to_string());
-    let scout94_dir = format!("{}/CascadeProjects/scout94", home_dir);
-    
-    // Map test types to Scout94's test scripts
-    let test_script = match test_type.as_str() {
-        "routing" => "test_routing.php",
-        "visitor" => "test_user_journey_visitor.php",
-        "user" => "test_user_journey_user.php",
-        "admin" => "test_user_journey_admin.php",
-        "database" => "test_install_db.php",
-        "audit" => "run_with_audit.php",
-        "all" => "run_all_tests.php",
-        _ => "run_all_tests.php",
-    };
+    let installation = installation::resolve(&app)?;
+    
+    let suites = test_suites::load(Some(&installation))?;
+    let suite = test_suites::find(&suites, &test_type, RunMode::Local)?;
+    
+    let missing_tools = test_suites::missing_tools(&suite);
+    if !missing_tools.is_empty() {
+        return Err(format!("❌ Test suite '{}' needs tools that are not installed: {}", suite.id, missing_tools.join(", ")));
+    }
     
-    let test_runner_path = format!("{}/{}", scout94_dir, test_script);
+    let test_script = suite.script.as_str();
+    let test_runner_path = installation.script_path(test_script);
     
     // Verify Scout94 test script exists
-    if !Path::new(&test_runner_path).exists() {
-        return Err(format!("❌ Test script not found: {}\n\nScout94 tests should be in: {}", test_runner_path, scout94_dir));
+    if !test_runner_path.exists() {
+        return Err(format!("❌ Test script not found: {:?}\n\nScout94 tests should be in: {} (found via {})", test_runner_path, installation.path, installation.source));
     }
     
     println!("📋 Executing: php {} (from Scout94 directory)", test_script);
     println!("📁 Target project: {}", project_path);
     
-    let output = Command::new("php")
+    let mut command = Command::new("php");
+    command
         .arg(&test_runner_path)
-        .current_dir(&project_path)
-        .output();
+        .current_dir(&project_path);
     
-    match output {
-        Ok(result) => {
-            let stdout = String::from_utf8_lossy(&result.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&result.stderr).to_string();
-            
-            println!("✅ Test completed");
-            println!("Output length: {} bytes", stdout.len());
-            
-            Ok(TestResult {
-                success: result.status.success(),
-                output: stdout,
-                error: if stderr.is_empty() { None } else { Some(stderr) },
-            })
-        }
-        Err(e) => {
-            println!("❌ Test execution failed: {}", e);
-            Err(format!("Failed to execute test: {}", e))
-        }
-    }
+    let limits = RunLimits::local(&app_config::load(&app), suite.default_timeout_secs);
+    
+    let meta = RunMeta {
+        test_type,
+        project_path,
+        host: None,
+    };
+    
+    Ok(job_queue::enqueue(&app, command, limits, meta, priority.unwrap_or(0)).run_id)
 }
 
+/// Let the user pick a project folder in a native dialog and register it as
+/// a workspace root; the webview only learns the chosen path.
 #[tauri::command]
-pub async fn select_project_folder() -> Result<String, String> {
-    // This will be implemented with Tauri's dialog API
-    Ok("/Users/mac/CascadeProjects/Viz Venture Group".to_string())
+pub async fn select_project_folder(app: AppHandle) -> Result<String, String> {
+    let picked = app
+        .dialog()
+        .file()
+        .set_title("Select project folder")
+        .blocking_pick_folder()
+        .ok_or_else(|| "No folder selected".to_string())?;
+    let path = picked.into_path().map_err(|e| format!("❌ Unsupported folder: {}", e))?;
+
+    let real = workspace::check_root(&path)?;
+    workspace::add_project_root(&app, &real)?;
+    Ok(real.to_string_lossy().to_string())
 }
 
 #[tauri::command]
-pub async fn read_screenshot(screenshot_path: String) -> Result<Vec<u8>, String> {
-    match std::fs::read(&screenshot_path) {
-        Ok(data) => Ok(data),
-        Err(e) => Err(format!("Failed to read screenshot: {}", e)),
-    }
+pub async fn read_screenshot(app: AppHandle, screenshot_path: String) -> Result<Vec<u8>, FsError> {
+    let path = Workspace::current(&app).resolve(&screenshot_path, Access::Read)?;
+    
+    std::fs::read(&path).map_err(|e| FsError::io(&path, "read screenshot", e))
 }
 
 #[tauri::command]
-pub async fn list_screenshots(project_path: String) -> Result<Vec<String>, String> {
+pub async fn list_screenshots(app: AppHandle, project_path: String) -> Result<Vec<String>, FsError> {
     let screenshots_dir = format!("{}/screenshots", project_path);
+    let resolved = Workspace::current(&app).resolve(&screenshots_dir, Access::Read)?;
     
-    match std::fs::read_dir(&screenshots_dir) {
+    match std::fs::read_dir(&resolved) {
         Ok(entries) => {
             let mut screenshots = Vec::new();
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    if let Some(path) = entry.path().to_str() {
-                        if path.ends_with(".png") || path.ends_with(".jpg") {
-                            screenshots.push(path.to_string());
-                        }
+            for entry in entries.flatten() {
+                let path = Path::new(&screenshots_dir).join(entry.file_name());
+                if let Some(path) = path.to_str() {
+                    if path.ends_with(".png") || path.ends_with(".jpg") {
+                        screenshots.push(path.to_string());
                     }
                 }
             }
@@ -113,36 +184,137 @@ pub async fn list_screenshots(project_path: String) -> Result<Vec<String>, Strin
     }
 }
 
-#[tauri::command]
-pub async fn read_file_content(file_path: String) -> Result<String, String> {
-    match fs::read_to_string(&file_path) {
-        Ok(content) => Ok(content),
-        Err(e) => Err(format!("Failed to read file: {}", e)),
+/// What a read saw on disk; pass `hash` back to `write_file_content` to
+/// detect changes made in between
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileStamp {
+    /// SHA-256 of the content, hex encoded
+    pub hash: String,
+    /// Milliseconds since the Unix epoch, if the platform reports it
+    pub modified_at: Option<u64>,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileContent {
+    pub content: String,
+    /// `None` when the content does not encode back to the bytes on disk
+    /// (binary files, broken text); `write_file_content` will not save those
+    pub encoding: Option<TextEncoding>,
+    /// The file starts with a byte order mark
+    pub bom: bool,
+    #[serde(flatten)]
+    pub stamp: FileStamp,
+}
+
+fn stamp(path: &Path, content: &[u8]) -> FileStamp {
+    let modified_at = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64);
+
+    FileStamp {
+        hash: file_versions::sha256_hex(content),
+        modified_at,
+        size: content.len() as u64,
     }
 }
 
+/// Read a whole file as text. Non-UTF-8 files are decoded (UTF-16,
+/// Windows-1252); binary content is returned with invalid bytes replaced.
+/// Pass `encoding` and `bom` back to `write_file_content` to save the file
+/// the way it was.
 #[tauri::command]
-pub async fn write_file_content(file_path: String, content: String) -> Result<(), String> {
-    // Create parent directories if they don't exist
-    if let Some(parent) = Path::new(&file_path).parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
-    }
+pub async fn read_file_content(app: AppHandle, file_path: String) -> Result<FileContent, FsError> {
+    let path = Workspace::current(&app).resolve(&file_path, Access::Read)?;
     
-    match fs::write(&file_path, content) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to write file: {}", e)),
-    }
+    let bytes = fs::read(&path).map_err(|e| FsError::io(&path, "read file", e))?;
+    let (content, encoding, bom) = match file_reader::decode_exact(&bytes) {
+        Some((encoding, bom, text)) => (text, Some(encoding), bom),
+        // Shown as well as possible, but never written back
+        None => match file_reader::decode_text(&bytes) {
+            Some((_, text)) => (text, None, false),
+            None => (String::from_utf8_lossy(&bytes).to_string(), None, false),
+        },
+    };
+    // The hash covers the bytes on disk, not the decoded text
+    let stamp = stamp(&path, &bytes);
+    Ok(FileContent { content, encoding, bom, stamp })
 }
 
+/// `content` encoded for saving over `current`: in the given encoding and
+/// BOM, else the current file's, else UTF-8. Files that are binary or do not
+/// decode exactly are refused, since saving text over them would corrupt them.
+fn encode_content(current: Option<&[u8]>, content: &str, encoding: Option<TextEncoding>, bom: Option<bool>) -> Result<Vec<u8>, String> {
+    let format = match current {
+        Some(bytes) => Some(
+            file_reader::decode_exact(bytes)
+                .map(|(encoding, bom, _)| (encoding, bom))
+                .ok_or("is binary or not valid text in its encoding and cannot be saved as text")?,
+        ),
+        None => None,
+    };
+    let encoding = encoding.or(format.map(|(encoding, _)| encoding)).unwrap_or(TextEncoding::Utf8);
+    let bom = bom.or(format.map(|(_, bom)| bom)).unwrap_or(false);
+    encoding
+        .encode(content, bom)
+        .ok_or_else(|| format!("has characters that {:?} cannot represent", encoding))
+}
+
+/// Atomically replace a file, keeping its previous content in the version history.
+///
+/// With `expected_hash` (from `read_file_content` or `read_file_range`, or
+/// empty for a new file) the write fails with a `conflict` error carrying
+/// both versions if the file changed on disk in the meantime.
+///
+/// The content is saved in `encoding` with or without a BOM, as returned by
+/// the read; without them the file keeps its current encoding. Binary files
+/// and text that does not decode exactly are refused with an `encoding` error.
 #[tauri::command]
-pub async fn execute_command(command: String, args: Vec<String>, cwd: Option<String>) -> Result<TestResult, String> {
-    let mut cmd = Command::new(&command);
-    
-    if let Some(working_dir) = cwd {
-        cmd.current_dir(working_dir);
-    }
-    
-    cmd.args(&args);
+pub async fn write_file_content(
+    app: AppHandle,
+    file_path: String,
+    content: String,
+    expected_hash: Option<String>,
+    encoding: Option<TextEncoding>,
+    bom: Option<bool>,
+) -> Result<FileStamp, FsError> {
+    let path = Workspace::current(&app).resolve(&file_path, Access::Write)?;
+
+    let current = match fs::read(&path) {
+        Ok(bytes) => Some(bytes),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(FsError::io(&path, "read file", e)),
+    };
+    // A file changed since the read is reported as a conflict below instead
+    let changed = expected_hash
+        .as_deref()
+        .is_some_and(|expected| current.as_deref().map(file_versions::sha256_hex).unwrap_or_default() != expected);
+    let bytes = match encode_content(current.as_deref().filter(|_| !changed), &content, encoding, bom) {
+        Ok(bytes) => bytes,
+        Err(reason) => return Err(FsError::Encoding { message: format!("{} {}", file_path, reason), path: file_path }),
+    };
+
+    // Missing parent directories are created once the conflict check passed
+    file_versions::write_if_unchanged(&app, &path, &bytes, expected_hash.as_deref())?;
+    Ok(stamp(&path, &bytes))
+}
+
+/// Run a program through the command policy of the project containing `cwd`.
+///
+/// Destructive commands come back as `needs_approval` and only run after
+/// `approve_command`; every decision is written to the audit log.
+#[tauri::command]
+pub async fn execute_command(app: AppHandle, command: String, args: Vec<String>, cwd: Option<String>) -> Result<TestResult, CommandError> {
+    command_policy::execute(&app, &command, &args, cwd.as_deref())
+}
+
+/// Spawn a program that already passed the policy and collect its output
+pub fn run_command(command: &str, args: &[String], cwd: &Path) -> Result<TestResult, String> {
+    let mut cmd = Command::new(command);
+    cmd.current_dir(cwd);
+    cmd.args(args);
     
     match cmd.output() {
         Ok(output) => {
@@ -153,6 +325,8 @@ pub async fn execute_command(command: String, args: Vec<String>, cwd: Option<Str
                 success: output.status.success(),
                 output: stdout,
                 error: if stderr.is_empty() { None } else { Some(stderr) },
+                report: None,
+                limit_exceeded: None,
             })
         }
         Err(e) => Err(format!("Failed to execute command: {}", e)),
@@ -160,161 +334,341 @@ pub async fn execute_command(command: String, args: Vec<String>, cwd: Option<Str
 }
 
 
+/// Symlinked directories are listed but never descended into, so the tree
+/// stays inside the workspace root it was requested for. Entries matching the
+/// project's ignore rules (see `explain_ignore`) are left out, and at most
+/// `MAX_TREE_ENTRIES` nodes are returned; use `list_children` for large trees.
 #[tauri::command]
-pub async fn read_directory_tree(directory_path: String, max_depth: Option<usize>) -> Result<Vec<FileNode>, String> {
+pub async fn read_directory_tree(app: AppHandle, directory_path: String, max_depth: Option<usize>) -> Result<Vec<FileNode>, FsError> {
+    let resolved = Workspace::current(&app).resolve(&directory_path, Access::Read)?;
     let path = PathBuf::from(&directory_path);
     
-    if !path.exists() {
-        return Err(format!("Directory does not exist: {}", directory_path));
+    if !resolved.exists() {
+        return Err(FsError::InvalidPath { message: format!("Directory does not exist: {}", directory_path), path: directory_path });
     }
     
-    if !path.is_dir() {
-        return Err(format!("Path is not a directory: {}", directory_path));
+    if !resolved.is_dir() {
+        return Err(FsError::InvalidPath { message: format!("Path is not a directory: {}", directory_path), path: directory_path });
     }
     
     let max_depth = max_depth.unwrap_or(5); // Default max depth of 5
-    read_directory_recursive(&path, 0, max_depth)
+    let rules = IgnoreRules::for_path(&app, &resolved);
+    let classifier = Classifier::for_path(&app, &resolved);
+    let relative = resolved.strip_prefix(rules.root()).unwrap_or(Path::new("")).to_path_buf();
+    let mut budget = MAX_TREE_ENTRIES;
+    let tree = read_directory_recursive(&path, &relative, &rules, &classifier, 0, max_depth, &mut budget)
+        .map_err(|message| FsError::Io { path: directory_path.clone(), message })?;
+    if budget == 0 {
+        println!("⚠️ Tree of {} cut off at {} entries", directory_path, MAX_TREE_ENTRIES);
+    }
+    Ok(tree)
+}
+
+/// Directories first, then case-insensitive by name; also the cursor order
+fn child_order(is_directory: bool, name: &str) -> (bool, String, String) {
+    (!is_directory, name.to_lowercase(), name.to_string())
 }
 
-fn is_minified_file(file_path: &Path) -> bool {
-    // Check if filename indicates minification
-    if let Some(name) = file_path.file_name() {
-        let name_str = name.to_string_lossy();
-        if name_str.ends_with(".min.js") || name_str.ends_with(".min.css") {
-            return true;
+fn encode_cursor(is_directory: bool, name: &str) -> String {
+    format!("{}:{}", if is_directory { "d" } else { "f" }, name)
+}
+
+fn decode_cursor(cursor: &str) -> Option<(bool, String, String)> {
+    let (kind, name) = cursor.split_once(':')?;
+    let is_directory = match kind {
+        "d" => true,
+        "f" => false,
+        _ => return None,
+    };
+    Some(child_order(is_directory, name))
+}
+
+struct VisibleChild {
+    name: String,
+    is_directory: bool,
+    /// Listed but never followed or read
+    is_symlink: bool,
+}
+
+/// Visible entries of `dir` in `child_order`, at most `MAX_DIRECTORY_ENTRIES`
+/// of them. Every name is read and sorted before the cap applies, so the
+/// listed entries do not depend on the order the OS returns them in.
+///
+/// Files are classified when `classifier` is set; symlinks never are since
+/// their target may be outside the root.
+fn visible_children(dir: &Path, relative: &Path, rules: &IgnoreRules, classifier: Option<&Classifier>) -> std::io::Result<(Vec<VisibleChild>, bool)> {
+    let mut entries: Vec<VisibleChild> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| VisibleChild {
+            name: entry.file_name().to_string_lossy().to_string(),
+            is_directory: entry.metadata().is_ok_and(|m| m.is_dir()),
+            is_symlink: entry.file_type().is_ok_and(|t| t.is_symlink()),
+        })
+        .collect();
+    entries.sort_by_cached_key(|child| child_order(child.is_directory, &child.name));
+    
+    let mut children = Vec::new();
+    for child in entries {
+        let entry_relative = relative.join(&child.name);
+        if rules.is_ignored(&entry_relative, child.is_directory) {
+            continue;
+        }
+        if !child.is_directory && !child.is_symlink && classifier.is_some_and(|c| c.is_hidden(&dir.join(&child.name), &entry_relative)) {
+            continue;
         }
+        if children.len() == MAX_DIRECTORY_ENTRIES {
+            return Ok((children, true));
+        }
+        children.push(child);
     }
-    
-    // Check if it's a JS/JSX file that might be minified
-    if let Some(ext) = file_path.extension() {
-        let ext_str = ext.to_string_lossy();
-        if ext_str == "js" || ext_str == "jsx" {
-            // Read first 500 bytes to check if minified
-            if let Ok(mut file) = File::open(file_path) {
-                let mut buffer = vec![0; 500];
-                if let Ok(bytes_read) = file.read(&mut buffer) {
-                    if bytes_read > 100 {
-                        let content = String::from_utf8_lossy(&buffer[..bytes_read]);
-                        // Minified code has very long lines and no formatting
-                        let newline_count = content.chars().filter(|&c| c == '\n').count();
-                        // If less than 3 newlines in first 500 bytes, likely minified
-                        if newline_count < 3 {
-                            return true;
-                        }
-                    }
-                }
+    Ok((children, false))
+}
+
+fn modified(dir: &Path) -> Option<SystemTime> {
+    fs::metadata(dir).and_then(|m| m.modified()).ok()
+}
+
+/// The listing of `dir` cached by an earlier page, if the directory has not
+/// changed since
+fn cached_listing(dir: &Path) -> Option<Arc<Listing>> {
+    let modified = modified(dir)?;
+    let listings = LISTINGS.lock().unwrap_or_else(|e| e.into_inner());
+    listings
+        .iter()
+        .find(|listing| listing.dir == dir && listing.modified == Some(modified))
+        .cloned()
+}
+
+/// Cache a fresh listing, keeping the child counts of the one it
+/// replaces; each count is checked against its directory before reuse
+fn cache_listing(listing: Listing) -> Arc<Listing> {
+    let mut listings = LISTINGS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(index) = listings.iter().position(|cached| cached.dir == listing.dir) {
+        let previous = listings.remove(index);
+        let counts = std::mem::take(&mut *previous.counts.lock().unwrap_or_else(|e| e.into_inner()));
+        *listing.counts.lock().unwrap_or_else(|e| e.into_inner()) = counts;
+    }
+    if listings.len() >= MAX_CACHED_LISTINGS {
+        listings.remove(0);
+    }
+    let listing = Arc::new(listing);
+    listings.push(listing.clone());
+    listing
+}
+
+impl Listing {
+    /// Visible entries of the child directory `name` of `dir`, classified like
+    /// the listing itself and counted again only once that directory changed
+    fn child_count(&self, dir: &Path, relative: &Path, name: &str, rules: &IgnoreRules, classifier: &Classifier) -> usize {
+        let child = dir.join(name);
+        let modified = modified(&child);
+        if let Some((counted_at, count)) = self.counts.lock().unwrap_or_else(|e| e.into_inner()).get(name) {
+            if modified.is_some() && *counted_at == modified {
+                return *count;
             }
         }
+
+        let count = visible_children(&child, &relative.join(name), rules, Some(classifier)).map_or(0, |(children, _)| children.len());
+        self.counts.lock().unwrap_or_else(|e| e.into_inner()).insert(name.to_string(), (modified, count));
+        count
     }
-    false
 }
 
-fn read_directory_recursive(path: &Path, current_depth: usize, max_depth: usize) -> Result<Vec<FileNode>, String> {
-    if current_depth >= max_depth {
-        return Ok(Vec::new());
+/// Sorted visible children of `dir` for `list_children`. The first page lists
+/// the directory again, so rule and content changes show up on refresh; later
+/// pages reuse that listing while the directory is unchanged.
+fn sorted_listing(dir: &Path, relative: &Path, rules: &IgnoreRules, classifier: &Classifier, first_page: bool) -> std::io::Result<Arc<Listing>> {
+    if !first_page {
+        if let Some(listing) = cached_listing(dir) {
+            return Ok(listing);
+        }
     }
     
-    // Patterns to ignore (including build artifacts and minified code)
-    let ignore_patterns = vec![
-        "node_modules", ".git", ".next", ".vscode", "dist", "build", 
-        "target", ".DS_Store", "vendor", ".idea", "__pycache__", 
-        ".cache", "coverage", ".env", ".venv", "venv",
-        "out", ".output", ".nuxt", ".vercel", ".netlify",
-        ".turbo", ".parcel-cache"
-    ];
+    let modified = modified(dir);
+    let (children, truncated) = visible_children(dir, relative, rules, Some(classifier))?;
+    Ok(cache_listing(Listing { dir: dir.to_path_buf(), modified, children, truncated, counts: Mutex::new(HashMap::new()) }))
+}
+
+/// Positions in sorted `children` of the `limit` entries after the cursor `after`
+fn page_range(children: &[VisibleChild], after: Option<(bool, String, String)>, limit: usize) -> std::ops::Range<usize> {
+    let start = after.map_or(0, |after| children.partition_point(|child| child_order(child.is_directory, &child.name) <= after));
+    start..(start + limit).min(children.len())
+}
+
+/// One page of a directory's children, for expanding the tree on demand.
+///
+/// Ordering is stable (directories first, then by name) and `cursor` names the
+/// last entry already seen, so pages stay consistent while files are added or
+/// removed. Symlinks are listed but not followed, as in `read_directory_tree`.
+#[tauri::command]
+pub async fn list_children(app: AppHandle, path: String, cursor: Option<String>, limit: Option<usize>) -> Result<ChildPage, FsError> {
+    let resolved = Workspace::current(&app).resolve(&path, Access::Read)?;
+    if !resolved.is_dir() {
+        return Err(FsError::InvalidPath { message: format!("Path is not a directory: {}", path), path });
+    }
     
-    let entries = match fs::read_dir(path) {
-        Ok(entries) => entries,
-        Err(e) => return Err(format!("Failed to read directory: {}", e)),
-    };
+    let rules = IgnoreRules::for_path(&app, &resolved);
+    let classifier = Classifier::for_path(&app, &resolved);
+    let relative = resolved.strip_prefix(rules.root()).unwrap_or(Path::new("")).to_path_buf();
+    let listing = sorted_listing(&resolved, &relative, &rules, &classifier, cursor.is_none())
+        .map_err(|e| FsError::io(&resolved, "read directory", e))?;
+    let children = &listing.children;
     
-    let mut nodes = Vec::new();
+    let after = match cursor.as_deref() {
+        Some(cursor) => Some(decode_cursor(cursor).ok_or_else(|| FsError::InvalidPath {
+            path: path.clone(),
+            message: format!("Invalid cursor: {}", cursor),
+        })?),
+        None => None,
+    };
+    let limit = limit.unwrap_or(DEFAULT_CHILDREN_LIMIT).clamp(1, MAX_CHILDREN_LIMIT);
+    let range = page_range(children, after, limit);
+    let page = &children[range.clone()];
     
-    for entry in entries {
-        if let Ok(entry) = entry {
-            let file_name = entry.file_name();
-            let name = file_name.to_string_lossy().to_string();
-            
-            // Skip ignored patterns
-            if ignore_patterns.iter().any(|pattern| name.contains(pattern)) {
-                continue;
-            }
-            
-            let entry_path = entry.path();
-            
-            // Skip minified files
-            if !entry_path.is_dir() && is_minified_file(&entry_path) {
-                continue;
-            }
-            
-            // Skip hidden files (starting with .)
-            if name.starts_with('.') && name != "." && name != ".." {
-                continue;
-            }
-            
-            let path_str = entry_path.to_string_lossy().to_string();
-            
-            let metadata = match entry.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
-            };
-            
-            let is_directory = metadata.is_dir();
-            
-            let language = if !is_directory {
-                detect_language(&name)
-            } else {
-                None
-            };
-            
-            let children = if is_directory {
-                match read_directory_recursive(&entry_path, current_depth + 1, max_depth) {
-                    Ok(children) => {
-                        if children.is_empty() {
-                            None
-                        } else {
-                            Some(children)
-                        }
-                    }
-                    Err(_) => None,
-                }
-            } else {
-                None
+    let base = PathBuf::from(&path);
+    let entries: Vec<ChildEntry> = page
+        .iter()
+        .map(|child| {
+            let entry_path = base.join(&child.name);
+            let child_count = child
+                .is_directory
+                .then(|| listing.child_count(&resolved, &relative, &child.name, &rules, &classifier));
+            let language = match (child.is_directory, child.is_symlink) {
+                (true, _) => None,
+                (false, true) => detect_language(&child.name),
+                (false, false) => detect_file_language(&resolved.join(&child.name)),
             };
             
-            nodes.push(FileNode {
-                name,
-                path: path_str,
-                is_directory,
+            ChildEntry {
+                name: child.name.clone(),
+                path: entry_path.to_string_lossy().to_string(),
+                is_directory: child.is_directory,
                 language,
-                children,
-            });
-        }
+                has_children: child_count.is_some_and(|count| count > 0),
+                child_count,
+            }
+        })
+        .collect();
+    
+    let next_cursor = (range.end < children.len())
+        .then(|| page.last().map(|child| encode_cursor(child.is_directory, &child.name)))
+        .flatten();
+    
+    Ok(ChildPage {
+        path,
+        entries,
+        next_cursor,
+        total: children.len(),
+        truncated: listing.truncated,
+    })
+}
+
+/// `relative` is `path` relative to the root of `rules`; stops adding nodes
+/// once `budget` is used up
+fn read_directory_recursive(
+    path: &Path,
+    relative: &Path,
+    rules: &IgnoreRules,
+    classifier: &Classifier,
+    current_depth: usize,
+    max_depth: usize,
+    budget: &mut usize,
+) -> Result<Vec<FileNode>, String> {
+    if current_depth >= max_depth {
+        return Ok(Vec::new());
     }
     
-    // Sort: directories first, then files, alphabetically
-    nodes.sort_by(|a, b| {
-        match (a.is_directory, b.is_directory) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+    // Sorted directories first, then files; minified, generated and vendored
+    // files are skipped per the project's settings
+    let (children, _) = visible_children(path, relative, rules, Some(classifier))
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+    
+    let mut nodes = Vec::new();
+    for child in children {
+        if *budget == 0 {
+            break;
         }
-    });
+        *budget -= 1;
+        
+        let entry_path = path.join(&child.name);
+        let entry_relative = relative.join(&child.name);
+        let path_str = entry_path.to_string_lossy().to_string();
+        
+        // Symlink targets may be outside the root, so only their names are looked at
+        let language = if child.is_directory {
+            None
+        } else if child.is_symlink {
+            detect_language(&child.name)
+        } else {
+            detect_file_language(&entry_path)
+        };
+        
+        let children = if child.is_directory {
+            match read_directory_recursive(&entry_path, &entry_relative, rules, classifier, current_depth + 1, max_depth, budget) {
+                Ok(children) => {
+                    if children.is_empty() {
+                        None
+                    } else {
+                        Some(children)
+                    }
+                }
+                Err(_) => None,
+            }
+        } else {
+            None
+        };
+        
+        nodes.push(FileNode {
+            name: child.name,
+            path: path_str,
+            is_directory: child.is_directory,
+            language,
+            children,
+        });
+    }
     
     Ok(nodes)
 }
 
-fn detect_language(filename: &str) -> Option<String> {
-    let extension = filename.split('.').last()?;
+/// Bytes read from extension-less files to look for a shebang
+const SHEBANG_SNIFF_BYTES: usize = 256;
+
+/// Language from well-known file names, checked before the extension
+fn language_from_name(filename: &str) -> Option<&'static str> {
+    let lower = filename.to_lowercase();
+    let language = match lower.as_str() {
+        "makefile" | "gnumakefile" => "makefile",
+        "dockerfile" | "containerfile" => "dockerfile",
+        ".htaccess" => "apacheconf",
+        "composer.lock" | "package-lock.json" | ".babelrc" | ".eslintrc" => "json",
+        "cargo.lock" | "poetry.lock" => "toml",
+        "gemfile" | "rakefile" | "vagrantfile" => "ruby",
+        "cmakelists.txt" => "cmake",
+        "artisan" => "php",
+        ".gitignore" | ".scout94ignore" | ".dockerignore" => "gitignore",
+        _ if lower.starts_with("dockerfile.") => "dockerfile",
+        _ if lower == ".env" || lower.starts_with(".env.") => "dotenv",
+        _ if lower.ends_with(".blade.php") => "blade",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Language from the file name and extension alone; "text" when unknown
+pub fn detect_language(filename: &str) -> Option<String> {
+    if let Some(language) = language_from_name(filename) {
+        return Some(language.to_string());
+    }
+    let extension = filename.rsplit('.').next()?;
     
     let language = match extension.to_lowercase().as_str() {
         "rs" => "rust",
-        "js" => "javascript",
+        "js" | "mjs" | "cjs" => "javascript",
         "jsx" => "jsx",
-        "ts" => "typescript",
+        "ts" | "mts" | "cts" => "typescript",
         "tsx" => "tsx",
         "py" => "python",
-        "php" => "php",
+        "php" | "phtml" => "php",
         "java" => "java",
         "c" => "c",
         "cpp" | "cc" | "cxx" => "cpp",
@@ -324,7 +678,8 @@ fn detect_language(filename: &str) -> Option<String> {
         "rb" => "ruby",
         "swift" => "swift",
         "kt" => "kotlin",
-        "html" => "html",
+        "html" | "htm" => "html",
+        "twig" => "twig",
         "css" => "css",
         "scss" | "sass" => "scss",
         "json" => "json",
@@ -332,9 +687,11 @@ fn detect_language(filename: &str) -> Option<String> {
         "yaml" | "yml" => "yaml",
         "md" | "markdown" => "markdown",
         "sql" => "sql",
-        "sh" | "bash" => "bash",
+        "sh" | "bash" | "zsh" => "bash",
+        "pl" | "pm" => "perl",
         "dockerfile" => "dockerfile",
         "toml" => "toml",
+        "ini" => "ini",
         "vue" => "vue",
         "svelte" => "svelte",
         _ => "text",
@@ -343,6 +700,61 @@ fn detect_language(filename: &str) -> Option<String> {
     Some(language.to_string())
 }
 
+/// Language named by a `#!` line or a leading `<?php` tag
+fn language_from_content(head: &[u8]) -> Option<&'static str> {
+    let first_line = head.split(|b| *b == b'\n').next()?;
+    let first_line = String::from_utf8_lossy(first_line);
+    if first_line.trim_start().starts_with("<?php") {
+        return Some("php");
+    }
+    
+    let interpreter_line = first_line.strip_prefix("#!")?;
+    let mut words = interpreter_line.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    // #!/usr/bin/env [-S] [NAME=value] python3
+    if interpreter == "env" {
+        interpreter = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    
+    let language = match interpreter {
+        "python" | "pypy" => "python",
+        "node" | "nodejs" | "deno" | "bun" => "javascript",
+        "ts-node" | "tsx" => "typescript",
+        "php" => "php",
+        "ruby" => "ruby",
+        "perl" => "perl",
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => "bash",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// `detect_language`, falling back to the file's first bytes for files the
+/// name does not explain (scripts without an extension)
+pub fn detect_language_with_content(filename: &str, head: &[u8]) -> Option<String> {
+    match detect_language(filename) {
+        Some(language) if language != "text" => Some(language),
+        fallback => language_from_content(head).map(str::to_string).or(fallback),
+    }
+}
+
+/// `detect_language_with_content` for a file on disk, reading its first
+/// bytes only when the name is not enough
+pub fn detect_file_language(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy();
+    let language = detect_language(&name);
+    if language.as_deref() != Some("text") {
+        return language;
+    }
+    
+    let mut head = Vec::with_capacity(SHEBANG_SNIFF_BYTES);
+    if let Ok(file) = File::open(path) {
+        let _ = file.take(SHEBANG_SNIFF_BYTES as u64).read_to_end(&mut head);
+    }
+    detect_language_with_content(&name, &head)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileInfo {
     pub name: String,
@@ -352,26 +764,201 @@ pub struct FileInfo {
 }
 
 #[tauri::command]
-pub async fn list_directory(directory_path: String) -> Result<Vec<FileInfo>, String> {
-    match fs::read_dir(&directory_path) {
+pub async fn list_directory(app: AppHandle, directory_path: String) -> Result<Vec<FileInfo>, FsError> {
+    let resolved = Workspace::current(&app).resolve(&directory_path, Access::Read)?;
+    
+    match fs::read_dir(&resolved) {
         Ok(entries) => {
             let mut files = Vec::new();
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    if let Ok(metadata) = entry.metadata() {
-                        if let Some(name) = entry.file_name().to_str() {
-                            files.push(FileInfo {
-                                name: name.to_string(),
-                                path: entry.path().to_string_lossy().to_string(),
-                                is_directory: metadata.is_dir(),
-                                size: if metadata.is_file() { Some(metadata.len()) } else { None },
-                            });
-                        }
+            for entry in entries.flatten() {
+                if let Ok(metadata) = entry.metadata() {
+                    if let Some(name) = entry.file_name().to_str() {
+                        files.push(FileInfo {
+                            name: name.to_string(),
+                            path: Path::new(&directory_path).join(name).to_string_lossy().to_string(),
+                            is_directory: metadata.is_dir(),
+                            size: if metadata.is_file() { Some(metadata.len()) } else { None },
+                        });
                     }
                 }
             }
             Ok(files)
         }
-        Err(e) => Err(format!("Failed to read directory: {}", e)),
+        Err(e) => Err(FsError::io(&resolved, "read directory", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ClassifierSettings;
+    use crate::ignore_rules::DEFAULT_IGNORE_GLOBS;
+    use crate::test_support::project_dir;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_listed_without_reading_their_target() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("project");
+        let outside = dir.path().join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        let minified = "var a=1;".repeat(100);
+        fs::write(outside.join("bundle.js"), &minified).unwrap();
+        fs::write(root.join("app.js"), &minified).unwrap();
+        std::os::unix::fs::symlink(outside.join("bundle.js"), root.join("linked.js")).unwrap();
+
+        let defaults: Vec<String> = DEFAULT_IGNORE_GLOBS.iter().map(|g| g.to_string()).collect();
+        let rules = IgnoreRules::new(&root, &defaults);
+        let classifier = Classifier::new(ClassifierSettings::default());
+        let (children, _) = visible_children(&root, Path::new(""), &rules, Some(&classifier)).unwrap();
+
+        // The minified file inside the root is hidden; the link is not opened to find out
+        let names: Vec<(&str, bool)> = children.iter().map(|c| (c.name.as_str(), c.is_symlink)).collect();
+        assert_eq!(names, vec![("linked.js", true)]);
+
+        let mut budget = MAX_TREE_ENTRIES;
+        let tree = read_directory_recursive(&root, Path::new(""), &rules, &classifier, 0, 2, &mut budget).unwrap();
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].language.as_deref(), Some("javascript"));
+    }
+
+    #[test]
+    fn languages_from_names() {
+        let cases = [
+            ("Makefile", Some("makefile")),
+            ("GNUmakefile", Some("makefile")),
+            (".htaccess", Some("apacheconf")),
+            ("artisan", Some("php")),
+            ("welcome.blade.php", Some("blade")),
+            ("Dockerfile.prod", Some("dockerfile")),
+            (".env.local", Some("dotenv")),
+            ("composer.lock", Some("json")),
+            ("index.php", None),
+            ("deploy", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(language_from_name(name), expected, "{}", name);
+        }
+        assert_eq!(detect_language("index.PHP").as_deref(), Some("php"));
+        assert_eq!(detect_language("notes").as_deref(), Some("text"));
+    }
+
+    #[test]
+    fn languages_from_content() {
+        let cases: [(&str, Option<&str>); 10] = [
+            ("#!/bin/bash\necho hi", Some("bash")),
+            ("#!/usr/bin/env python3\n", Some("python")),
+            ("#!/usr/bin/env -S node --no-warnings\n", Some("javascript")),
+            ("#!/usr/bin/env -S NODE_ENV=test deno run\n", Some("javascript")),
+            ("#!/usr/local/bin/php8.2\n<?php", Some("php")),
+            ("#! /usr/bin/perl -w\n", Some("perl")),
+            ("  <?php\nrequire 'vendor/autoload.php';", Some("php")),
+            ("#!/usr/bin/env\n", None),
+            ("#!/usr/bin/awk -f\n", None),
+            ("just text\n", None),
+        ];
+        for (head, expected) in cases {
+            assert_eq!(language_from_content(head.as_bytes()), expected, "{:?}", head);
+        }
+    }
+
+    #[test]
+    fn names_win_over_content() {
+        let cases = [
+            ("artisan", "#!/usr/bin/env php\n", Some("php")),
+            ("deploy", "#!/bin/sh\n", Some("bash")),
+            ("deploy", "no shebang\n", Some("text")),
+            ("run.py", "#!/bin/sh\n", Some("python")),
+            ("Makefile", "#!/bin/sh\n", Some("makefile")),
+            ("layout.blade.php", "<?php", Some("blade")),
+        ];
+        for (name, head, expected) in cases {
+            assert_eq!(detect_language_with_content(name, head.as_bytes()).as_deref(), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        assert_eq!(encode_cursor(true, "src"), "d:src");
+        assert_eq!(encode_cursor(false, "a:b.php"), "f:a:b.php");
+        assert_eq!(decode_cursor("f:a:b.php"), Some(child_order(false, "a:b.php")));
+        assert_eq!(decode_cursor("d:src"), Some(child_order(true, "src")));
+        assert_eq!(decode_cursor("x:src"), None);
+        assert_eq!(decode_cursor("src"), None);
+    }
+
+    #[test]
+    fn children_are_sorted_and_paged_by_cursor() {
+        let dir = project_dir(&[("b.php", ""), ("A.php", ""), ("a.php", ""), ("Zeta/x", ""), ("alpha/x", ""), ("node_modules/x", "")]);
+        let defaults: Vec<String> = DEFAULT_IGNORE_GLOBS.iter().map(|g| g.to_string()).collect();
+        let rules = IgnoreRules::new(dir.path(), &defaults);
+        let classifier = Classifier::new(ClassifierSettings::default());
+
+        let (children, truncated) = visible_children(dir.path(), Path::new(""), &rules, Some(&classifier)).unwrap();
+        let names: Vec<&str> = children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "Zeta", "A.php", "a.php", "b.php"]);
+        assert!(!truncated);
+
+        let first = page_range(&children, None, 2);
+        assert_eq!(first, 0..2);
+        let last = &children[first.end - 1];
+        let second = page_range(&children, decode_cursor(&encode_cursor(last.is_directory, &last.name)), 2);
+        assert_eq!(second, 2..4);
+        assert_eq!(page_range(&children, decode_cursor("f:a.php"), 10), 4..5);
+
+        // The cursor names a position, so removing that entry does not skip the next one
+        let without_a: Vec<VisibleChild> = children.into_iter().filter(|c| c.name != "a.php").collect();
+        assert_eq!(without_a[page_range(&without_a, decode_cursor("f:a.php"), 10)].first().map(|c| c.name.as_str()), Some("b.php"));
+    }
+
+    #[test]
+    fn child_counts_skip_hidden_files_and_are_cached() {
+        let minified = format!("var a=1;{}\n", "x".repeat(2000));
+        let dir = project_dir(&[("dist/app.min.js", &minified), ("src/app.js", "")]);
+        let rules = IgnoreRules::new(dir.path(), &[]);
+        let classifier = Classifier::new(ClassifierSettings::default());
+        let count = |listing: &Listing, name: &str| listing.child_count(dir.path(), Path::new(""), name, &rules, &classifier);
+        // So the change below gives `dist` a new mtime however coarse the clock
+        File::open(dir.path().join("dist")).unwrap().set_modified(SystemTime::UNIX_EPOCH).unwrap();
+
+        let listing = sorted_listing(dir.path(), Path::new(""), &rules, &classifier, true).unwrap();
+        assert_eq!((count(&listing, "dist"), count(&listing, "src")), (0, 1));
+        assert_eq!(listing.counts.lock().unwrap().len(), 2);
+
+        // A refreshed listing keeps the counts; a changed directory is counted again
+        fs::write(dir.path().join("dist/readme.md"), "").unwrap();
+        let refreshed = sorted_listing(dir.path(), Path::new(""), &rules, &classifier, true).unwrap();
+        assert_eq!(refreshed.counts.lock().unwrap().len(), 2);
+        assert_eq!(count(&refreshed, "dist"), 1);
+    }
+
+    #[test]
+    fn listing_is_capped_after_sorting() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..MAX_DIRECTORY_ENTRIES + 5 {
+            fs::write(dir.path().join(format!("{:05}.txt", i)), "").unwrap();
+        }
+        let rules = IgnoreRules::new(dir.path(), &[]);
+        let (children, truncated) = visible_children(dir.path(), Path::new(""), &rules, None).unwrap();
+        assert!(truncated);
+        assert_eq!(children.len(), MAX_DIRECTORY_ENTRIES);
+        assert_eq!(children.last().unwrap().name, format!("{:05}.txt", MAX_DIRECTORY_ENTRIES - 1));
+    }
+
+    #[test]
+    fn saves_keep_the_file_encoding() {
+        let latin1 = encode_content(Some(b"caf\xE9\n"), "café crème\n", None, None).unwrap();
+        assert_eq!(latin1, b"caf\xE9 cr\xE8me\n");
+        assert!(encode_content(Some(b"caf\xE9\n"), "日本\n", None, None).is_err());
+
+        let utf16 = encode_content(Some(&[0xFF, 0xFE, b'a', 0]), "b", None, None).unwrap();
+        assert_eq!(utf16, [0xFF, 0xFE, b'b', 0]);
+        assert_eq!(encode_content(None, "é", None, None).unwrap(), "é".as_bytes());
+        assert_eq!(encode_content(None, "a", Some(TextEncoding::Utf8), Some(true)).unwrap(), b"\xEF\xBB\xBFa");
+
+        assert!(encode_content(Some(b"\x00\x01\x02\x03\x04"), "text", None, None).is_err());
+        assert!(encode_content(Some(&[0xFF, 0xFE, 0x00, 0xD8]), "text", None, None).is_err());
     }
 }
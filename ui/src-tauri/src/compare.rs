@@ -0,0 +1,282 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use tauri::AppHandle;
+
+use crate::history::{self, HistoryRun};
+use crate::report::{CheckStatus, TestReport};
+
+/// Lines of unchanged output around each hunk of the diff
+const DIFF_CONTEXT: usize = 3;
+/// Give up on an exact diff for huge outputs and fall back to a coarser one
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckChange {
+    NewlyFailing,
+    NewlyPassing,
+    StillFailing,
+    Unchanged,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CheckComparison {
+    pub suite: String,
+    pub name: String,
+    pub change: CheckChange,
+    pub base_status: Option<CheckStatus>,
+    pub head_status: Option<CheckStatus>,
+    pub base_duration_ms: Option<u64>,
+    pub head_duration_ms: Option<u64>,
+    /// Head minus base, when both runs timed the check
+    pub duration_delta_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ComparisonSummary {
+    pub newly_failing: usize,
+    pub newly_passing: usize,
+    pub still_failing: usize,
+    pub unchanged: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunComparison {
+    pub base_run_id: String,
+    pub head_run_id: String,
+    pub summary: ComparisonSummary,
+    /// Regressions first, then the remaining checks in head order
+    pub checks: Vec<CheckComparison>,
+    pub base_duration_ms: Option<u64>,
+    pub head_duration_ms: Option<u64>,
+    pub duration_delta_ms: Option<i64>,
+    /// Unified diff of the raw outputs, empty when they are identical
+    pub output_diff: String,
+}
+
+/// Checks of a report keyed by (suite, check name), in report order
+fn checks_of(report: &TestReport) -> Vec<((String, String), CheckStatus, Option<u64>)> {
+    report
+        .suites
+        .iter()
+        .flat_map(|suite| {
+            suite
+                .checks
+                .iter()
+                .map(move |check| ((suite.name.clone(), check.name.clone()), check.status, check.duration_ms))
+        })
+        .collect()
+}
+
+fn report_of(run: &HistoryRun) -> TestReport {
+    run.report.clone().unwrap_or_else(|| TestReport::parse(&run.output))
+}
+
+/// Wall-clock duration of a run, preferring the parsed report's own timing
+fn run_duration(run: &HistoryRun, report: &TestReport) -> Option<u64> {
+    report.duration_ms.or_else(|| {
+        let started = run.entry.started_at?;
+        run.entry.finished_at.map(|finished| finished.saturating_sub(started))
+    })
+}
+
+fn delta(base: Option<u64>, head: Option<u64>) -> Option<i64> {
+    Some(head? as i64 - base? as i64)
+}
+
+fn classify(base: Option<CheckStatus>, head: Option<CheckStatus>) -> CheckChange {
+    let failed = |s: CheckStatus| s == CheckStatus::Failed;
+    match (base, head) {
+        (None, _) => CheckChange::Added,
+        (_, None) => CheckChange::Removed,
+        (Some(b), Some(h)) if failed(b) && failed(h) => CheckChange::StillFailing,
+        (Some(_), Some(h)) if failed(h) => CheckChange::NewlyFailing,
+        (Some(b), Some(_)) if failed(b) => CheckChange::NewlyPassing,
+        _ => CheckChange::Unchanged,
+    }
+}
+
+/// Match the checks of two runs and classify how each one changed
+pub fn compare(base: &HistoryRun, head: &HistoryRun) -> RunComparison {
+    let base_report = report_of(base);
+    let head_report = report_of(head);
+
+    let base_checks = checks_of(&base_report);
+    let head_checks = checks_of(&head_report);
+    let base_by_key: HashMap<&(String, String), (CheckStatus, Option<u64>)> =
+        base_checks.iter().map(|(key, status, duration)| (key, (*status, *duration))).collect();
+    let head_keys: HashSet<&(String, String)> = head_checks.iter().map(|(key, _, _)| key).collect();
+
+    let mut checks = Vec::new();
+    for (key, status, duration) in &head_checks {
+        let (suite, name) = key;
+        let base_check = base_by_key.get(key);
+        let base_status = base_check.map(|(s, _)| *s);
+        let base_duration = base_check.and_then(|(_, d)| *d);
+        checks.push(CheckComparison {
+            suite: suite.clone(),
+            name: name.clone(),
+            change: classify(base_status, Some(*status)),
+            base_status,
+            head_status: Some(*status),
+            base_duration_ms: base_duration,
+            head_duration_ms: *duration,
+            duration_delta_ms: delta(base_duration, *duration),
+        });
+    }
+    for (key, status, duration) in &base_checks {
+        if head_keys.contains(key) {
+            continue;
+        }
+        checks.push(CheckComparison {
+            suite: key.0.clone(),
+            name: key.1.clone(),
+            change: CheckChange::Removed,
+            base_status: Some(*status),
+            head_status: None,
+            base_duration_ms: *duration,
+            head_duration_ms: None,
+            duration_delta_ms: None,
+        });
+    }
+
+    // Stable sort keeps report order within each kind of change
+    checks.sort_by_key(|c| match c.change {
+        CheckChange::NewlyFailing => 0,
+        CheckChange::StillFailing => 1,
+        CheckChange::NewlyPassing => 2,
+        CheckChange::Added => 3,
+        CheckChange::Removed => 4,
+        CheckChange::Unchanged => 5,
+    });
+
+    let mut summary = ComparisonSummary::default();
+    for check in &checks {
+        match check.change {
+            CheckChange::NewlyFailing => summary.newly_failing += 1,
+            CheckChange::NewlyPassing => summary.newly_passing += 1,
+            CheckChange::StillFailing => summary.still_failing += 1,
+            CheckChange::Unchanged => summary.unchanged += 1,
+            CheckChange::Added => summary.added += 1,
+            CheckChange::Removed => summary.removed += 1,
+        }
+    }
+
+    let base_duration = run_duration(base, &base_report);
+    let head_duration = run_duration(head, &head_report);
+
+    let diff = TextDiff::configure()
+        .timeout(DIFF_TIMEOUT)
+        .diff_lines(base.output.as_str(), head.output.as_str());
+    let output_diff = diff
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT)
+        .header(&base.entry.run_id, &head.entry.run_id)
+        .to_string();
+
+    RunComparison {
+        base_run_id: base.entry.run_id.clone(),
+        head_run_id: head.entry.run_id.clone(),
+        summary,
+        checks,
+        base_duration_ms: base_duration,
+        head_duration_ms: head_duration,
+        duration_delta_ms: delta(base_duration, head_duration),
+        output_diff,
+    }
+}
+
+/// Compare two recorded runs: `base` is the older run, `head` the newer one
+#[tauri::command]
+pub async fn compare_runs(app: AppHandle, base_run_id: String, head_run_id: String) -> Result<RunComparison, String> {
+    let base = history::load_run(&app, &base_run_id)?;
+    let head = history::load_run(&app, &head_run_id)?;
+
+    println!("🔀 Comparing runs {} → {}", base_run_id, head_run_id);
+    Ok(compare(&base, &head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::HistoryEntry;
+    use crate::test_runner::RunState;
+    use crate::test_suites::RunMode;
+
+    fn run(run_id: &str, output: &str) -> HistoryRun {
+        HistoryRun {
+            entry: HistoryEntry {
+                run_id: run_id.to_string(),
+                project_path: "/p".to_string(),
+                suite: "routing".to_string(),
+                mode: RunMode::Local,
+                host: None,
+                status: RunState::Failed,
+                success: false,
+                queued_at: 0,
+                started_at: Some(100),
+                finished_at: Some(400),
+                exit_code: Some(1),
+                limit_exceeded: None,
+            },
+            output: output.to_string(),
+            error: None,
+            report: None,
+        }
+    }
+
+    fn change_of(comparison: &RunComparison, name: &str) -> CheckChange {
+        comparison.checks.iter().find(|c| c.name == name).map(|c| c.change).unwrap()
+    }
+
+    #[test]
+    fn classifies_each_check_change() {
+        let base = run(
+            "base",
+            "Test 1: index...\n✅ ok\n\nTest 2: htaccess...\n❌ missing\n\nTest 3: admin...\n❌ missing\n\nTest 4: dist...\n✅ ok\n\nTest 5: old...\n✅ ok\n",
+        );
+        let head = run(
+            "head",
+            "Test 1: index...\n❌ broken\n\nTest 2: htaccess...\n✅ ok\n\nTest 3: admin...\n❌ missing\n\nTest 4: dist...\n✅ ok\n\nTest 6: new...\n✅ ok\n",
+        );
+        let comparison = compare(&base, &head);
+
+        assert_eq!(change_of(&comparison, "index"), CheckChange::NewlyFailing);
+        assert_eq!(change_of(&comparison, "htaccess"), CheckChange::NewlyPassing);
+        assert_eq!(change_of(&comparison, "admin"), CheckChange::StillFailing);
+        assert_eq!(change_of(&comparison, "dist"), CheckChange::Unchanged);
+        assert_eq!(change_of(&comparison, "new"), CheckChange::Added);
+        assert_eq!(change_of(&comparison, "old"), CheckChange::Removed);
+
+        // Regressions come first, unchanged checks last
+        assert_eq!(comparison.checks.first().unwrap().name, "index");
+        assert_eq!(comparison.checks.last().unwrap().name, "dist");
+        let summary = &comparison.summary;
+        assert_eq!(
+            (summary.newly_failing, summary.newly_passing, summary.still_failing, summary.unchanged, summary.added, summary.removed),
+            (1, 1, 1, 1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn diffs_outputs_and_falls_back_to_run_timing() {
+        let base = run("base", "Test 1: index...\n✅ ok\n");
+        let mut head = run("head", "Test 1: index...\n✅ ok\n");
+        head.entry.finished_at = Some(250);
+
+        let same = compare(&base, &head);
+        assert!(same.output_diff.is_empty());
+        assert_eq!(same.base_duration_ms, Some(300));
+        assert_eq!(same.duration_delta_ms, Some(-150));
+
+        head.output.push_str("⚠️ slow\n");
+        let changed = compare(&base, &head);
+        assert!(changed.output_diff.contains("+⚠️ slow"));
+    }
+}
@@ -0,0 +1,625 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::file_versions;
+use crate::history::{self, HistoryRun};
+use crate::report::{CheckStatus, TestCheck, TestReport, TestSuite};
+use crate::workspace::{Access, Workspace};
+
+const TOOL_NAME: &str = "Scout94";
+const TOOL_URI: &str = "https://github.com/mromk94/scout94";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// Name of the synthetic check reported when a run failed without a failing check
+const RUN_CHECK: &str = "Scout94 run";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Junit,
+    Tap,
+    Sarif,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Junit => "xml",
+            ExportFormat::Tap => "tap",
+            ExportFormat::Sarif => "sarif",
+        }
+    }
+}
+
+/// Render a recorded run in one of the CI formats
+pub fn render(run: &HistoryRun, format: ExportFormat) -> String {
+    let report = run.report.clone().unwrap_or_else(|| TestReport::parse(&run.output));
+    match format {
+        ExportFormat::Junit => to_junit(run, &report),
+        ExportFormat::Tap => to_tap(run, &report),
+        ExportFormat::Sarif => to_sarif(run, &report),
+    }
+}
+
+/// Error to report for a run that failed without any failing check (crash, timeout, ...)
+fn run_failure(run: &HistoryRun, report: &TestReport) -> Option<String> {
+    if run.entry.success || report.counts.failed > 0 {
+        return None;
+    }
+    Some(
+        run.error
+            .clone()
+            .filter(|e| !e.trim().is_empty())
+            .unwrap_or_else(|| format!("Run ended as {:?} (exit code: {:?})", run.entry.status, run.entry.exit_code)),
+    )
+}
+
+fn run_duration_ms(run: &HistoryRun, report: &TestReport) -> Option<u64> {
+    report.duration_ms.or_else(|| {
+        let started = run.entry.started_at?;
+        run.entry.finished_at.map(|finished| finished.saturating_sub(started))
+    })
+}
+
+fn seconds(ms: Option<u64>) -> String {
+    format!("{:.3}", ms.unwrap_or(0) as f64 / 1000.0)
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if (c as u32) < 0x20 && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn class_name(suite: &TestSuite) -> String {
+    format!("scout94.{}", suite.name)
+}
+
+fn to_junit(run: &HistoryRun, report: &TestReport) -> String {
+    let failure = run_failure(run, report);
+    let errors = usize::from(failure.is_some());
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\">\n",
+        xml_escape(&format!("{} {} ({})", TOOL_NAME, run.entry.suite, run.entry.run_id)),
+        report.counts.total + errors,
+        report.counts.failed,
+        errors,
+        report.counts.skipped,
+        seconds(run_duration_ms(run, report)),
+    ));
+
+    for suite in &report.suites {
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{}\">\n",
+            xml_escape(&suite.name),
+            suite.counts.total,
+            suite.counts.failed,
+            suite.counts.skipped,
+            seconds(suite.duration_ms),
+        ));
+        for check in &suite.checks {
+            push_junit_case(&mut xml, &class_name(suite), check);
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+
+    if let Some(message) = failure {
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"1\" failures=\"0\" errors=\"1\" skipped=\"0\" time=\"0.000\">\n",
+            TOOL_NAME
+        ));
+        xml.push_str(&format!(
+            "    <testcase name=\"{}\" classname=\"scout94\" time=\"0.000\">\n      <error message=\"{}\">{}</error>\n    </testcase>\n",
+            RUN_CHECK,
+            xml_escape(message.lines().next().unwrap_or_default()),
+            xml_escape(&message),
+        ));
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn push_junit_case(xml: &mut String, class_name: &str, check: &TestCheck) {
+    let details = xml_escape(&check.messages.join("\n"));
+    let summary = xml_escape(check.messages.first().map(String::as_str).unwrap_or_default());
+
+    xml.push_str(&format!(
+        "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+        xml_escape(&check.name),
+        xml_escape(class_name),
+        seconds(check.duration_ms),
+    ));
+
+    match check.status {
+        CheckStatus::Passed => xml.push_str("/>\n"),
+        CheckStatus::Failed => xml.push_str(&format!(
+            ">\n      <failure message=\"{}\" type=\"failed\">{}</failure>\n    </testcase>\n",
+            summary, details
+        )),
+        CheckStatus::Skipped => xml.push_str(&format!(
+            ">\n      <skipped message=\"{}\"/>\n    </testcase>\n",
+            summary
+        )),
+        // JUnit has no warning outcome; keep the check passing and attach the details
+        CheckStatus::Warning => xml.push_str(&format!(
+            ">\n      <system-out>{}</system-out>\n    </testcase>\n",
+            details
+        )),
+    }
+}
+
+/// TAP descriptions must not contain an unescaped `#`, which starts a directive
+fn tap_description(suite: &str, check: &str) -> String {
+    format!("{}: {}", suite, check).replace('\\', "\\\\").replace('#', "\\#")
+}
+
+fn push_tap_yaml(tap: &mut String, severity: &str, messages: &[String], duration_ms: Option<u64>) {
+    tap.push_str("  ---\n");
+    tap.push_str(&format!("  severity: {}\n", severity));
+    if let Some(ms) = duration_ms {
+        tap.push_str(&format!("  duration_ms: {}\n", ms));
+    }
+    if !messages.is_empty() {
+        tap.push_str("  message: |\n");
+        for message in messages {
+            for line in message.lines() {
+                tap.push_str(&format!("    {}\n", line));
+            }
+        }
+    }
+    tap.push_str("  ...\n");
+}
+
+fn to_tap(run: &HistoryRun, report: &TestReport) -> String {
+    let failure = run_failure(run, report);
+    let total = report.counts.total + usize::from(failure.is_some());
+
+    let mut tap = String::from("TAP version 13\n");
+    tap.push_str(&format!("1..{}\n", total));
+    tap.push_str(&format!("# {} {} ({})\n", TOOL_NAME, run.entry.suite, run.entry.run_id));
+
+    let mut number = 0;
+    for suite in &report.suites {
+        for check in &suite.checks {
+            number += 1;
+            let description = tap_description(&suite.name, &check.name);
+            match check.status {
+                CheckStatus::Passed => tap.push_str(&format!("ok {} - {}\n", number, description)),
+                CheckStatus::Skipped => {
+                    let reason = check.messages.first().cloned().unwrap_or_default();
+                    tap.push_str(&format!("ok {} - {} # SKIP {}\n", number, description, reason.replace('#', "\\#")));
+                }
+                CheckStatus::Warning => {
+                    tap.push_str(&format!("ok {} - {}\n", number, description));
+                    push_tap_yaml(&mut tap, "warning", &check.messages, check.duration_ms);
+                }
+                CheckStatus::Failed => {
+                    tap.push_str(&format!("not ok {} - {}\n", number, description));
+                    push_tap_yaml(&mut tap, "fail", &check.messages, check.duration_ms);
+                }
+            }
+        }
+    }
+
+    if let Some(message) = failure {
+        number += 1;
+        tap.push_str(&format!("not ok {} - {}\n", number, RUN_CHECK));
+        push_tap_yaml(&mut tap, "error", &[message], None);
+    }
+
+    tap
+}
+
+/// `file://` URI of a local path, percent-encoding everything but unreserved characters
+fn file_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut uri = String::from("file://");
+    // Windows drive paths ("C:/...") need the empty authority plus a slash
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn sarif_level(status: CheckStatus) -> Option<&'static str> {
+    match status {
+        CheckStatus::Failed => Some("error"),
+        CheckStatus::Warning => Some("warning"),
+        CheckStatus::Passed | CheckStatus::Skipped => None,
+    }
+}
+
+/// Failed and warning checks become SARIF results; passing checks only define rules
+fn to_sarif(run: &HistoryRun, report: &TestReport) -> String {
+    let mut rules = Vec::new();
+    let mut results = Vec::new();
+
+    for suite in &report.suites {
+        for check in &suite.checks {
+            let rule_id = format!("{}/{}", suite.name, check.name);
+            rules.push(json!({
+                "id": rule_id,
+                "name": check.name,
+                "shortDescription": { "text": check.name },
+                "properties": { "suite": suite.name },
+            }));
+
+            if let Some(level) = sarif_level(check.status) {
+                let text = if check.messages.is_empty() {
+                    check.name.clone()
+                } else {
+                    check.messages.join("\n")
+                };
+                results.push(json!({
+                    "ruleId": rule_id,
+                    "ruleIndex": rules.len() - 1,
+                    "level": level,
+                    "message": { "text": text },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": file_uri(&run.entry.project_path) },
+                        },
+                    }],
+                    "properties": {
+                        "suite": suite.name,
+                        "outputLine": check.line,
+                        "durationMs": check.duration_ms,
+                    },
+                }));
+            }
+        }
+    }
+
+    let failure = run_failure(run, report);
+    let mut invocation = json!({
+        "executionSuccessful": failure.is_none(),
+        "exitCode": run.entry.exit_code,
+    });
+    if let Some(message) = failure {
+        invocation["toolExecutionNotifications"] = json!([{
+            "level": "error",
+            "message": { "text": message },
+        }]);
+    }
+
+    let sarif: Value = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "informationUri": TOOL_URI,
+                    "rules": rules,
+                },
+            },
+            "automationDetails": { "id": format!("scout94/{}/{}", run.entry.suite, run.entry.run_id) },
+            "invocations": [invocation],
+            "results": results,
+        }],
+    });
+
+    serde_json::to_string_pretty(&sarif).unwrap_or_default()
+}
+
+/// Render a recorded run as JUnit XML, TAP 13 or SARIF 2.1.
+///
+/// Writes the result to `output_path` when given (a directory gets
+/// `<run_id>.<ext>`); the path must be writable in the workspace. The
+/// rendered document is returned either way.
+#[tauri::command]
+pub async fn export_run(app: AppHandle, run_id: String, format: ExportFormat, output_path: Option<String>) -> Result<String, String> {
+    let run = history::load_run(&app, &run_id)?;
+    let document = render(&run, format);
+
+    if let Some(path) = output_path {
+        let workspace = Workspace::current(&app);
+        let mut path = workspace.resolve(&path, Access::Write)?;
+        if path.is_dir() {
+            let file = path.join(format!("{}.{}", run_id, format.extension()));
+            path = workspace.resolve(&file.to_string_lossy(), Access::Write)?;
+        }
+        file_versions::write_atomic(&path, document.as_bytes()).map_err(|e| format!("Failed to write export {:?}: {}", path, e))?;
+        println!("📤 Exported run {} as {:?} to {:?}", run_id, format, path);
+    }
+
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::HistoryEntry;
+    use crate::test_runner::RunState;
+    use crate::test_suites::RunMode;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    const JUNIT_SAMPLE: &str = include_str!("../tests/fixtures/export/junit-sample.xml");
+    const TAP_SAMPLE: &str = include_str!("../tests/fixtures/export/tap13-sample.tap");
+    const SARIF_SAMPLE: &str = include_str!("../tests/fixtures/export/sarif-2.1.0-sample.json");
+
+    const OUTPUT: &str = "\
+🔍 Scout94 - Routing Validation Test
+
+Test 1: .htaccess exists...
+✅ .htaccess found
+Test 2: Admin route <login> & \"dashboard\"...
+❌ /admin/login returns 404
+   This will cause 404 on /admin/login
+Test 3: Cache headers # optional...
+⚠️ Cache-Control header missing
+Test 4: Mail server...
+⚠️ Skipped: no SMTP configured
+";
+
+    /// Outcome of one test as read back from an exported document
+    #[derive(Debug, PartialEq)]
+    struct ReadCase {
+        suite: String,
+        name: String,
+        outcome: &'static str,
+    }
+
+    fn run(output: &str, success: bool, error: Option<&str>) -> HistoryRun {
+        HistoryRun {
+            entry: HistoryEntry {
+                run_id: "run-1-0".to_string(),
+                project_path: "/srv/project".to_string(),
+                suite: "routing".to_string(),
+                mode: RunMode::Local,
+                host: None,
+                status: if success { RunState::Succeeded } else { RunState::Failed },
+                success,
+                queued_at: 1_000,
+                started_at: Some(1_000),
+                finished_at: Some(3_500),
+                exit_code: Some(if success { 0 } else { 1 }),
+                limit_exceeded: None,
+            },
+            output: output.to_string(),
+            error: error.map(str::to_string),
+            report: None,
+        }
+    }
+
+    fn expected_cases(run: &HistoryRun) -> Vec<ReadCase> {
+        let report = TestReport::parse(&run.output);
+        report
+            .suites
+            .iter()
+            .flat_map(|suite| {
+                suite.checks.iter().map(|check| ReadCase {
+                    suite: suite.name.clone(),
+                    name: check.name.clone(),
+                    outcome: match check.status {
+                        CheckStatus::Passed | CheckStatus::Warning => "passed",
+                        CheckStatus::Failed => "failed",
+                        CheckStatus::Skipped => "skipped",
+                    },
+                })
+            })
+            .collect()
+    }
+
+    fn attribute(event: &quick_xml::events::BytesStart, name: &str) -> Option<String> {
+        event
+            .attributes()
+            .flatten()
+            .find(|a| a.key.as_ref() == name.as_bytes())
+            .and_then(|a| a.unescape_value().ok())
+            .map(|v| v.to_string())
+    }
+
+    /// Minimal JUnit reader written for these tests: testcases with their outcome,
+    /// plus the root counters. Not a validation against the JUnit XSD.
+    fn read_junit(xml: &str) -> (Vec<ReadCase>, (usize, usize, usize)) {
+        let mut reader = Reader::from_str(xml);
+        let mut cases: Vec<ReadCase> = Vec::new();
+        let mut suite = String::new();
+        let mut totals = (0, 0, 0);
+
+        loop {
+            match reader.read_event().expect("well-formed XML") {
+                Event::Start(e) | Event::Empty(e) => match e.name().as_ref() {
+                    b"testsuites" => {
+                        let count = |name| attribute(&e, name).and_then(|v| v.parse().ok()).unwrap_or(0);
+                        totals = (count("tests"), count("failures") + count("errors"), count("skipped"));
+                    }
+                    b"testsuite" => suite = attribute(&e, "name").unwrap_or_default(),
+                    b"testcase" => cases.push(ReadCase {
+                        suite: suite.clone(),
+                        name: attribute(&e, "name").expect("testcase name"),
+                        outcome: "passed",
+                    }),
+                    b"failure" | b"error" => cases.last_mut().expect("open testcase").outcome = "failed",
+                    b"skipped" => cases.last_mut().expect("open testcase").outcome = "skipped",
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        (cases, totals)
+    }
+
+    /// Minimal TAP 13 reader written for these tests: version, plan and test points
+    fn read_tap(tap: &str) -> (usize, Vec<(bool, String, bool)>) {
+        let mut lines = tap.lines();
+        assert_eq!(lines.next(), Some("TAP version 13"));
+
+        let mut plan = None;
+        let mut points = Vec::new();
+        let mut in_yaml = false;
+        for line in lines {
+            if in_yaml {
+                in_yaml = line.trim() != "...";
+                continue;
+            }
+            if line.trim() == "---" {
+                in_yaml = true;
+            } else if let Some(count) = line.strip_prefix("1..") {
+                plan = Some(count.parse().expect("numeric plan"));
+            } else if line.starts_with("ok ") || line.starts_with("not ok ") {
+                let ok = line.starts_with("ok ");
+                let rest = line.trim_start_matches("not ").trim_start_matches("ok ");
+                let (_, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+                let rest = rest.strip_prefix("- ").unwrap_or(rest);
+
+                // Split at the first unescaped '#'
+                let mut description = String::new();
+                let mut directive = None;
+                let mut chars = rest.char_indices();
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '\\' => description.extend(chars.next().map(|(_, c)| c)),
+                        '#' => {
+                            directive = Some(rest[i + 1..].trim().to_string());
+                            break;
+                        }
+                        c => description.push(c),
+                    }
+                }
+                let skipped = directive.is_some_and(|d| d.to_uppercase().starts_with("SKIP"));
+                points.push((ok, description.trim().to_string(), skipped));
+            }
+        }
+
+        (plan.expect("TAP plan"), points)
+    }
+
+    /// Spot-checks properties SARIF 2.1.0 requires of a log, its runs and its
+    /// results; not a validation against the full schema
+    fn assert_sarif_basics(sarif: &Value) {
+        assert_eq!(sarif["version"], "2.1.0");
+        let runs = sarif["runs"].as_array().expect("runs array");
+        for run in runs {
+            assert!(run["tool"]["driver"]["name"].is_string(), "tool.driver.name is required");
+            for result in run["results"].as_array().expect("results array") {
+                assert!(result["message"]["text"].is_string(), "result.message.text is required");
+                let level = result["level"].as_str().unwrap_or("warning");
+                assert!(["none", "note", "warning", "error"].contains(&level));
+                if let Some(index) = result["ruleIndex"].as_u64() {
+                    let rule = &run["tool"]["driver"]["rules"][index as usize];
+                    assert_eq!(rule["id"], result["ruleId"], "ruleIndex must point at ruleId");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn junit_sample_is_readable() {
+        let (cases, totals) = read_junit(JUNIT_SAMPLE);
+        assert_eq!(cases.len(), 3);
+        assert_eq!(totals, (3, 1, 1));
+        assert_eq!(cases.iter().filter(|c| c.outcome == "failed").count(), 1);
+    }
+
+    #[test]
+    fn junit_export_lists_every_check() {
+        let run = run(OUTPUT, false, None);
+        let xml = render(&run, ExportFormat::Junit);
+        let (cases, totals) = read_junit(&xml);
+
+        let expected = expected_cases(&run);
+        assert_eq!(cases, expected);
+        assert_eq!(totals, (4, 1, 1));
+        assert!(cases.iter().any(|c| c.name.contains("<login> & \"dashboard\"")));
+    }
+
+    #[test]
+    fn junit_reports_runs_that_failed_without_checks() {
+        let run = run("", false, Some("⏱️ Run stopped: exceeded the 300s time limit"));
+        let (cases, totals) = read_junit(&render(&run, ExportFormat::Junit));
+
+        assert_eq!(totals, (1, 1, 0));
+        assert_eq!(cases, vec![ReadCase { suite: TOOL_NAME.to_string(), name: RUN_CHECK.to_string(), outcome: "failed" }]);
+    }
+
+    #[test]
+    fn tap_sample_is_readable() {
+        let (plan, points) = read_tap(TAP_SAMPLE);
+        assert_eq!(plan, points.len());
+        assert_eq!(points.iter().filter(|(ok, _, _)| !ok).count(), 1);
+        assert_eq!(points.iter().filter(|(_, _, skipped)| *skipped).count(), 1);
+    }
+
+    #[test]
+    fn tap_export_lists_every_check() {
+        let run = run(OUTPUT, false, None);
+        let (plan, points) = read_tap(&render(&run, ExportFormat::Tap));
+
+        let expected = expected_cases(&run);
+        assert_eq!(plan, expected.len());
+        assert_eq!(points.len(), expected.len());
+        for ((ok, description, skipped), case) in points.iter().zip(&expected) {
+            assert_eq!(description, &format!("{}: {}", case.suite, case.name));
+            assert_eq!(*ok, case.outcome != "failed");
+            assert_eq!(*skipped, case.outcome == "skipped");
+        }
+    }
+
+    #[test]
+    fn sarif_sample_has_required_properties() {
+        let sample: Value = serde_json::from_str(SARIF_SAMPLE).expect("sample is JSON");
+        assert_sarif_basics(&sample);
+    }
+
+    #[test]
+    fn sarif_export_reports_failures_and_warnings() {
+        let run = run(OUTPUT, false, None);
+        let sarif: Value = serde_json::from_str(&render(&run, ExportFormat::Sarif)).expect("export is JSON");
+        assert_sarif_basics(&sarif);
+
+        let report = TestReport::parse(&run.output);
+        let findings: Vec<(String, String)> = report
+            .suites
+            .iter()
+            .flat_map(|s| s.checks.iter().map(move |c| (s, c)))
+            .filter_map(|(s, c)| sarif_level(c.status).map(|level| (format!("{}/{}", s.name, c.name), level.to_string())))
+            .collect();
+
+        let results: Vec<(String, String)> = sarif["runs"][0]["results"]
+            .as_array()
+            .expect("results")
+            .iter()
+            .map(|r| (r["ruleId"].as_str().unwrap().to_string(), r["level"].as_str().unwrap().to_string()))
+            .collect();
+
+        assert_eq!(results, findings);
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["rules"].as_array().map(Vec::len), Some(report.counts.total));
+        assert_eq!(sarif["runs"][0]["invocations"][0]["executionSuccessful"], true);
+        let location = &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"];
+        assert_eq!(location["uri"], "file:///srv/project");
+    }
+
+    #[test]
+    fn file_uris_are_percent_encoded() {
+        assert_eq!(file_uri("/Users/dev/Viz Venture Group"), "file:///Users/dev/Viz%20Venture%20Group");
+        assert_eq!(file_uri("/srv/a#b?c%"), "file:///srv/a%23b%3Fc%25");
+        assert_eq!(file_uri("/srv/caf\u{e9}"), "file:///srv/caf%C3%A9");
+        assert_eq!(file_uri("C:\\work\\site"), "file:///C:/work/site");
+    }
+}
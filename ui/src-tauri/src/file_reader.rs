@@ -0,0 +1,659 @@
+use std::fs::{File, Metadata};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use crate::file_versions;
+use crate::workspace::{Access, FsError, Workspace};
+
+/// Bytes returned when the caller does not ask for a cap
+const DEFAULT_MAX_BYTES: u64 = 1024 * 1024;
+/// Upper bound for any single read, whatever the caller asks for
+const HARD_MAX_BYTES: u64 = 16 * 1024 * 1024;
+/// Bytes from the start of the file used to detect encoding and line endings
+pub const SNIFF_BYTES: usize = 8192;
+/// Files whose whole-file hash and UTF-8 check are kept for paging
+const MAX_CACHED_CHECKS: usize = 32;
+
+// Whole-file checks of recently read files, oldest first
+static FILE_CHECKS: LazyLock<Mutex<Vec<FileCheck>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+struct FileCheck {
+    path: PathBuf,
+    size: u64,
+    modified: Option<SystemTime>,
+    hash: String,
+    utf8: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+}
+
+impl TextEncoding {
+    pub fn encoding(self) -> &'static Encoding {
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => UTF_8,
+            TextEncoding::Utf16Le => UTF_16LE,
+            TextEncoding::Utf16Be => UTF_16BE,
+            TextEncoding::Windows1252 => WINDOWS_1252,
+        }
+    }
+
+    /// Length of the byte order mark `head` starts with; UTF-16 may have none
+    fn bom_len(self, head: &[u8]) -> u64 {
+        Encoding::for_bom(head)
+            .filter(|(encoding, _)| *encoding == self.encoding())
+            .map_or(0, |(_, len)| len as u64)
+    }
+
+    pub fn has_bom(self, head: &[u8]) -> bool {
+        self.bom_len(head) > 0
+    }
+
+    /// `text` in this encoding, starting with a BOM if `bom` (always for
+    /// `Utf8Bom`, never for Windows-1252); `None` if it holds characters the
+    /// encoding cannot represent
+    pub fn encode(self, text: &str, bom: bool) -> Option<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => {
+                if bom || self == TextEncoding::Utf8Bom {
+                    bytes.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+                }
+                bytes.extend_from_slice(text.as_bytes());
+            }
+            // encoding_rs only encodes to UTF-8 for these, as the WHATWG spec says
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+                let little_endian = self == TextEncoding::Utf16Le;
+                for unit in bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16()) {
+                    bytes.extend_from_slice(&if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() });
+                }
+            }
+            TextEncoding::Windows1252 => {
+                let (encoded, _, unmappable) = WINDOWS_1252.encode(text);
+                if unmappable {
+                    return None;
+                }
+                bytes.extend_from_slice(&encoded);
+            }
+        }
+        Some(bytes)
+    }
+
+    /// Bytes per code unit, so ranges never split one
+    fn unit(self) -> u64 {
+        match self {
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => 2,
+            _ => 1,
+        }
+    }
+
+    /// How '\n' is encoded
+    pub fn newline(self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf16Le => b"\n\0",
+            TextEncoding::Utf16Be => b"\0\n",
+            _ => b"\n",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+    Mixed,
+    /// No line break in the sampled content
+    None,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeUnit {
+    #[default]
+    Bytes,
+    Lines,
+}
+
+/// Part of a file to read. Byte ranges count from the start of the file
+/// (including any BOM); line ranges are 0-based.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ReadRange {
+    pub unit: RangeUnit,
+    pub start: u64,
+    /// Bytes or lines to read; until the cap if absent
+    pub count: Option<u64>,
+    /// Size cap for the returned chunk, `DEFAULT_MAX_BYTES` if absent
+    pub max_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FileChunk {
+    pub path: String,
+    /// Size of the whole file in bytes
+    pub size: u64,
+    /// SHA-256 of the whole file, hex encoded; pass it to `write_file_content`
+    /// as `expected_hash` to detect changes made since this read
+    pub hash: String,
+    pub binary: bool,
+    /// `None` for binary files
+    pub encoding: Option<TextEncoding>,
+    /// The file starts with a byte order mark; pass it to `write_file_content`
+    /// along with `encoding` to save the file the way it was
+    pub bom: bool,
+    pub line_ending: LineEnding,
+    /// Decoded text, `None` for binary files
+    pub content: Option<String>,
+    /// Byte range of the file covered by `content`
+    pub start_byte: u64,
+    pub end_byte: u64,
+    /// First line of `content` when reading by lines
+    pub start_line: Option<u64>,
+    /// Line breaks in `content`
+    pub lines: u64,
+    /// The read stopped at the size cap before the requested range ended
+    pub truncated: bool,
+    /// `end_byte` is the end of the file
+    pub eof: bool,
+}
+
+/// Guess the encoding of text from its first bytes; `None` means binary
+pub fn detect_encoding(sample: &[u8]) -> Option<TextEncoding> {
+    if sample.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Some(TextEncoding::Utf8Bom);
+    }
+    if sample.starts_with(&[0xFF, 0xFE]) {
+        return Some(TextEncoding::Utf16Le);
+    }
+    if sample.starts_with(&[0xFE, 0xFF]) {
+        return Some(TextEncoding::Utf16Be);
+    }
+    if sample.is_empty() {
+        return Some(TextEncoding::Utf8);
+    }
+
+    // BOM-less UTF-16: ASCII-range text leaves every other byte zero
+    let pairs = sample.len() / 2;
+    if pairs >= 2 {
+        let zero_even = sample.iter().step_by(2).filter(|b| **b == 0).count();
+        let zero_odd = sample.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+        if zero_odd * 10 >= pairs * 7 && zero_even * 10 <= pairs {
+            return Some(TextEncoding::Utf16Le);
+        }
+        if zero_even * 10 >= pairs * 7 && zero_odd * 10 <= pairs {
+            return Some(TextEncoding::Utf16Be);
+        }
+    }
+
+    if sample.contains(&0) {
+        return None;
+    }
+
+    match std::str::from_utf8(sample) {
+        Ok(_) => return Some(TextEncoding::Utf8),
+        // The sample may end in the middle of a character
+        Err(e) if e.error_len().is_none() => return Some(TextEncoding::Utf8),
+        Err(_) => {}
+    }
+
+    // Not UTF-8: treat as Windows-1252 unless it is mostly control bytes
+    let control = sample
+        .iter()
+        .filter(|b| **b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B))
+        .count();
+    if control * 10 > sample.len() {
+        None
+    } else {
+        Some(TextEncoding::Windows1252)
+    }
+}
+
+/// Encoding of the whole of `bytes`, sniffed from their start. BOM-less text
+/// that turns out not to be UTF-8 further on is Windows-1252.
+pub fn detect_file_encoding(bytes: &[u8]) -> Option<TextEncoding> {
+    match detect_encoding(&bytes[..bytes.len().min(SNIFF_BYTES)])? {
+        TextEncoding::Utf8 if std::str::from_utf8(bytes).is_err() => Some(TextEncoding::Windows1252),
+        encoding => Some(encoding),
+    }
+}
+
+/// Decode the complete content of a file; `None` for binary content
+pub fn decode_text(bytes: &[u8]) -> Option<(TextEncoding, String)> {
+    let encoding = detect_file_encoding(bytes)?;
+    let bom = encoding.bom_len(bytes) as usize;
+    let (text, _) = encoding.encoding().decode_without_bom_handling(&bytes[bom..]);
+    Some((encoding, text.into_owned()))
+}
+
+/// A file's text that encodes back to exactly `bytes`, with its encoding and
+/// BOM; `None` for binary content and decodes that lost or replaced bytes
+pub fn decode_exact(bytes: &[u8]) -> Option<(TextEncoding, bool, String)> {
+    let (encoding, text) = decode_text(bytes)?;
+    let bom = encoding.has_bom(bytes);
+    (encoding.encode(&text, bom)? == bytes).then_some((encoding, bom, text))
+}
+
+/// `hash_and_check_utf8` of the file at `path`, reused while its size and
+/// mtime stay the same, so paging through a large file reads it only once
+fn checked(path: &Path, file: &mut File, metadata: &Metadata) -> std::io::Result<(String, bool)> {
+    let modified = metadata.modified().ok();
+    let fresh = |check: &FileCheck| check.path == path && check.size == metadata.len() && check.modified == modified;
+    if let Some(check) = FILE_CHECKS.lock().unwrap_or_else(|e| e.into_inner()).iter().find(|check| fresh(check)) {
+        return Ok((check.hash.clone(), check.utf8));
+    }
+
+    let (hash, utf8) = hash_and_check_utf8(file)?;
+    let mut checks = FILE_CHECKS.lock().unwrap_or_else(|e| e.into_inner());
+    checks.retain(|check| check.path != path);
+    if checks.len() >= MAX_CACHED_CHECKS {
+        checks.remove(0);
+    }
+    checks.push(FileCheck { path: path.to_path_buf(), size: metadata.len(), modified, hash: hash.clone(), utf8 });
+    Ok((hash, utf8))
+}
+
+/// SHA-256 of the rest of `file`, hex encoded, and whether all of it is UTF-8
+fn hash_and_check_utf8(file: &mut File) -> std::io::Result<(String, bool)> {
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    // Bytes of a character cut by the end of the previous read
+    let mut pending: Vec<u8> = Vec::new();
+    let mut utf8 = true;
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        if utf8 {
+            pending.extend_from_slice(&buffer[..read]);
+            match std::str::from_utf8(&pending) {
+                Ok(_) => pending.clear(),
+                Err(e) if e.error_len().is_none() => {
+                    pending.drain(..e.valid_up_to());
+                }
+                Err(_) => utf8 = false,
+            }
+        }
+    }
+    Ok((file_versions::to_hex(&hasher.finalize()), utf8 && pending.is_empty()))
+}
+
+fn detect_line_ending(text: &str) -> LineEnding {
+    let bytes = text.as_bytes();
+    let (mut lf, mut crlf, mut cr) = (0, 0, 0);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                crlf += 1;
+                i += 1;
+            }
+            b'\r' => cr += 1,
+            b'\n' => lf += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    match (lf > 0, crlf > 0, cr > 0) {
+        (false, false, false) => LineEnding::None,
+        (true, false, false) => LineEnding::Lf,
+        (false, true, false) => LineEnding::Crlf,
+        (false, false, true) => LineEnding::Cr,
+        _ => LineEnding::Mixed,
+    }
+}
+
+/// Drop bytes at either end of `bytes` that belong to a character cut by the range
+fn trim_partial_chars(bytes: &[u8], encoding: TextEncoding, at_file_start: bool) -> (usize, usize) {
+    let mut start = 0;
+    let mut end = bytes.len();
+
+    match encoding {
+        TextEncoding::Utf8 | TextEncoding::Utf8Bom => {
+            if !at_file_start {
+                while start < end && start < 3 && bytes[start] & 0xC0 == 0x80 {
+                    start += 1;
+                }
+            }
+            // Find the last lead byte and check its sequence is complete
+            if let Some(lead) = (start..end).rev().take(4).find(|i| bytes[*i] & 0xC0 != 0x80) {
+                let len = match bytes[lead] {
+                    b if b >= 0xF0 => 4,
+                    b if b >= 0xE0 => 3,
+                    b if b >= 0xC0 => 2,
+                    _ => 1,
+                };
+                if lead + len > end {
+                    end = lead;
+                }
+            }
+        }
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            end -= (end - start) % 2;
+            if end - start >= 2 {
+                let (hi, lo) = if encoding == TextEncoding::Utf16Le { (end - 1, end - 2) } else { (end - 2, end - 1) };
+                let unit = u16::from_be_bytes([bytes[hi], bytes[lo]]);
+                // A high surrogate whose pair lies beyond the range
+                if (0xD800..0xDC00).contains(&unit) {
+                    end -= 2;
+                }
+            }
+        }
+        TextEncoding::Windows1252 => {}
+    }
+
+    (start, end)
+}
+
+/// Byte offsets of lines `start_line..start_line + count`, stopping at `max_bytes`.
+/// Returns (start_byte, end_byte, truncated).
+fn line_range(file: &mut File, encoding: TextEncoding, bom: u64, start_line: u64, count: Option<u64>, max_bytes: u64) -> std::io::Result<(u64, u64, bool)> {
+    let newline = encoding.newline();
+    let unit = encoding.unit() as usize;
+    file.seek(SeekFrom::Start(bom))?;
+    let mut reader = BufReader::with_capacity(64 * 1024, file);
+
+    let mut offset = bom;
+    let mut line = 0;
+    let mut start_byte = if start_line == 0 { Some(bom) } else { None };
+    // Half of a UTF-16 code unit left over from the previous buffer
+    let mut carry: Vec<u8> = Vec::new();
+
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok((start_byte.unwrap_or(offset), offset, false));
+        }
+
+        let mut data = std::mem::take(&mut carry);
+        data.extend_from_slice(buffer);
+        let consumed = buffer.len();
+        let usable = data.len() - data.len() % unit;
+        let base = offset - (data.len() - consumed) as u64;
+
+        let mut i = 0;
+        while i < usable {
+            let here = base + i as u64;
+            if let Some(start) = start_byte {
+                if here - start >= max_bytes {
+                    return Ok((start, here, true));
+                }
+            }
+            if data[i..i + unit] == *newline {
+                line += 1;
+                let next = here + unit as u64;
+                if line == start_line {
+                    start_byte = Some(next);
+                }
+                if let (Some(start), Some(count)) = (start_byte, count) {
+                    if line >= start_line + count {
+                        return Ok((start, next, false));
+                    }
+                }
+            }
+            i += unit;
+        }
+
+        carry = data[usable..].to_vec();
+        offset += consumed as u64;
+        reader.consume(consumed);
+    }
+}
+
+fn read_range(path: &Path, range: &ReadRange) -> Result<FileChunk, FsError> {
+    let mut file = File::open(path).map_err(|e| FsError::io(path, "open file", e))?;
+    let metadata = file.metadata().map_err(|e| FsError::io(path, "read metadata", e))?;
+    let size = metadata.len();
+    let max_bytes = range.max_bytes.unwrap_or(DEFAULT_MAX_BYTES).clamp(1, HARD_MAX_BYTES);
+
+    // Hashed before the content is read: a change in between makes the next
+    // write conflict instead of going unnoticed. Checking UTF-8 over the whole
+    // file keeps the encoding the same for every page of it.
+    let (hash, utf8) = checked(path, &mut file, &metadata).map_err(|e| FsError::io(path, "read file", e))?;
+    file.rewind().map_err(|e| FsError::io(path, "seek in file", e))?;
+
+    let mut sample = Vec::with_capacity(SNIFF_BYTES);
+    (&mut file)
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut sample)
+        .map_err(|e| FsError::io(path, "read file", e))?;
+    let encoding = match detect_encoding(&sample) {
+        Some(TextEncoding::Utf8) if !utf8 => Some(TextEncoding::Windows1252),
+        encoding => encoding,
+    };
+
+    let line_ending = match encoding {
+        Some(encoding) => {
+            let bom = encoding.bom_len(&sample) as usize;
+            let (start, end) = trim_partial_chars(&sample[bom..], encoding, true);
+            let (text, _) = encoding.encoding().decode_without_bom_handling(&sample[bom..][start..end]);
+            detect_line_ending(&text)
+        }
+        None => LineEnding::None,
+    };
+
+    // Binary files get metadata only
+    let Some(encoding) = encoding else {
+        return Ok(FileChunk {
+            path: path.to_string_lossy().to_string(),
+            size,
+            hash,
+            binary: true,
+            encoding: None,
+            bom: false,
+            line_ending,
+            content: None,
+            start_byte: 0,
+            end_byte: 0,
+            start_line: None,
+            lines: 0,
+            truncated: false,
+            eof: size == 0,
+        });
+    };
+
+    let bom = encoding.bom_len(&sample);
+    let (start, end, truncated) = match range.unit {
+        RangeUnit::Bytes => {
+            let start = range.start.max(bom).min(size);
+            let start = start - (start - bom.min(start)) % encoding.unit();
+            let wanted = range.count.map_or(size, |count| range.start.saturating_add(count).min(size));
+            let end = wanted.min(start + max_bytes).max(start);
+            (start, end, end < wanted)
+        }
+        RangeUnit::Lines => line_range(&mut file, encoding, bom, range.start, range.count, max_bytes)
+            .map_err(|e| FsError::io(path, "read file", e))?,
+    };
+
+    let mut bytes = Vec::with_capacity((end - start) as usize);
+    file.seek(SeekFrom::Start(start)).map_err(|e| FsError::io(path, "seek in file", e))?;
+    (&mut file)
+        .take(end - start)
+        .read_to_end(&mut bytes)
+        .map_err(|e| FsError::io(path, "read file", e))?;
+
+    let (trim_start, trim_end) = trim_partial_chars(&bytes, encoding, start <= bom);
+    let (text, _) = encoding.encoding().decode_without_bom_handling(&bytes[trim_start..trim_end]);
+    let text = text.into_owned();
+    let end = start + trim_end as u64;
+
+    Ok(FileChunk {
+        path: path.to_string_lossy().to_string(),
+        size,
+        hash,
+        binary: false,
+        encoding: Some(encoding),
+        bom: bom > 0,
+        line_ending,
+        lines: text.matches('\n').count() as u64,
+        content: Some(text),
+        start_byte: start + trim_start as u64,
+        end_byte: end,
+        start_line: (range.unit == RangeUnit::Lines).then_some(range.start),
+        truncated,
+        eof: end >= size,
+    })
+}
+
+/// Read part of a file, detecting binary content, encoding and line endings.
+///
+/// Reads at most `max_bytes` (capped at 16 MiB) so the IDE can page through
+/// large files by following `end_byte` or the next line number.
+#[tauri::command]
+pub async fn read_file_range(app: AppHandle, file_path: String, range: Option<ReadRange>) -> Result<FileChunk, FsError> {
+    let path = Workspace::current(&app).resolve(&file_path, Access::Read)?;
+    read_range(&path, &range.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn read(bytes: &[u8], range: ReadRange) -> FileChunk {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("file");
+        std::fs::write(&path, bytes).unwrap();
+        read_range(&path, &range).unwrap()
+    }
+
+    fn utf16(text: &str, little_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() })
+            .collect()
+    }
+
+    #[test]
+    fn decodes_boms_and_utf16() {
+        let chunk = read(b"\xEF\xBB\xBFh\xC3\xA9llo\r\nworld\r\n", ReadRange::default());
+        assert_eq!(chunk.encoding, Some(TextEncoding::Utf8Bom));
+        assert_eq!(chunk.content.as_deref(), Some("héllo\r\nworld\r\n"));
+        assert_eq!((chunk.line_ending, chunk.lines), (LineEnding::Crlf, 2));
+
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(utf16("<?php\necho 'ü';\n", true));
+        let chunk = read(&le, ReadRange::default());
+        assert_eq!(chunk.encoding, Some(TextEncoding::Utf16Le));
+        assert_eq!(chunk.content.as_deref(), Some("<?php\necho 'ü';\n"));
+
+        // Without a BOM, from the zero bytes of ASCII text
+        let be = utf16("line one\nline two\n", false);
+        let chunk = read(&be, ReadRange { unit: RangeUnit::Lines, start: 0, count: Some(1), ..Default::default() });
+        assert_eq!(chunk.encoding, Some(TextEncoding::Utf16Be));
+        assert_eq!(chunk.content.as_deref(), Some("line one\n"));
+        let chunk = read(&be, ReadRange { unit: RangeUnit::Lines, start: 1, ..Default::default() });
+        assert_eq!(chunk.content.as_deref(), Some("line two\n"));
+        assert_eq!(read(&be, ReadRange::default()).start_byte, 0);
+        assert_eq!(decode_text(&utf16("abc\n", true)).map(|(_, text)| text).as_deref(), Some("abc\n"));
+    }
+
+    #[test]
+    fn exact_decodes_encode_back_to_the_same_bytes() {
+        let mut le_bom = vec![0xFF, 0xFE];
+        le_bom.extend(utf16("<?php\necho 'ü';\n", true));
+        let samples: [(&[u8], TextEncoding, bool); 5] = [
+            (b"h\xC3\xA9llo\n", TextEncoding::Utf8, false),
+            (b"\xEF\xBB\xBFhi\n", TextEncoding::Utf8Bom, true),
+            (&le_bom, TextEncoding::Utf16Le, true),
+            (&utf16("plain text\n", false), TextEncoding::Utf16Be, false),
+            (b"caf\xE9 \x80\n", TextEncoding::Windows1252, false),
+        ];
+        for (bytes, encoding, bom) in samples {
+            let (found, has_bom, text) = decode_exact(bytes).unwrap_or_else(|| panic!("{:?} was not exact", encoding));
+            assert_eq!((found, has_bom), (encoding, bom));
+            assert_eq!(encoding.encode(&text, bom).as_deref(), Some(bytes));
+        }
+
+        assert!(decode_exact(b"\x00\x01\x02\x03binary").is_none());
+        // An unpaired surrogate decodes to U+FFFD, which would not write back the same
+        assert!(decode_exact(&[0xFF, 0xFE, 0x00, 0xD8, 0x41, 0x00]).is_none());
+        assert!(TextEncoding::Windows1252.encode("日本", false).is_none());
+    }
+
+    #[test]
+    fn pages_reuse_the_whole_file_check() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("log.txt");
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+
+        let first = read_range(&path, &ReadRange { max_bytes: Some(4), ..Default::default() }).unwrap();
+        let check = |path: &Path| FILE_CHECKS.lock().unwrap().iter().filter(|c| c.path == path).map(|c| c.hash.clone()).collect::<Vec<_>>();
+        assert_eq!(check(&path), std::slice::from_ref(&first.hash));
+        let second = read_range(&path, &ReadRange { start: 4, ..Default::default() }).unwrap();
+        assert_eq!((second.hash, second.content.as_deref()), (first.hash.clone(), Some("two\n")));
+
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        let changed = read_range(&path, &ReadRange::default()).unwrap();
+        assert_ne!(changed.hash, first.hash);
+        assert_eq!(check(&path), [changed.hash]);
+    }
+
+    #[test]
+    fn falls_back_to_latin1_when_utf8_breaks_past_the_sniffed_bytes() {
+        let mut bytes = vec![b'a'; SNIFF_BYTES + 100];
+        bytes.extend(b"caf\xE9\n");
+
+        let (encoding, text) = decode_text(&bytes).unwrap();
+        assert_eq!(encoding, TextEncoding::Windows1252);
+        assert!(text.ends_with("café\n"));
+
+        // Every page of the file uses the same encoding, even one that is plain ASCII
+        let first = read(&bytes, ReadRange { count: Some(10), ..Default::default() });
+        assert_eq!(first.encoding, Some(TextEncoding::Windows1252));
+        let last = read(&bytes, ReadRange { start: SNIFF_BYTES as u64 + 100, ..Default::default() });
+        assert_eq!(last.content.as_deref(), Some("café\n"));
+    }
+
+    #[test]
+    fn utf8_split_across_reads_stays_utf8() {
+        // The hashing pass reads 64 KiB at a time; put a character across the boundary
+        let mut bytes = vec![b'x'; 64 * 1024 - 1];
+        bytes.extend("é\n".as_bytes());
+        let chunk = read(&bytes, ReadRange { start: 64 * 1024 - 1, ..Default::default() });
+        assert_eq!(chunk.encoding, Some(TextEncoding::Utf8));
+        assert_eq!(chunk.content.as_deref(), Some("é\n"));
+        assert_eq!(chunk.hash, file_versions::sha256_hex(&bytes));
+    }
+
+    #[test]
+    fn binary_files_get_metadata_only() {
+        let bytes = [0x89, b'P', b'N', b'G', 0, 0, 0, 0x0D, 1, 2, 3, 0, 0, 4];
+        let chunk = read(&bytes, ReadRange::default());
+        assert!(chunk.binary);
+        assert_eq!((chunk.encoding, chunk.content), (None, None));
+        assert_eq!(chunk.size, bytes.len() as u64);
+        assert!(decode_text(&bytes).is_none());
+    }
+
+    #[test]
+    fn detects_line_endings() {
+        let cases = [
+            ("a\nb\n", LineEnding::Lf),
+            ("a\r\nb\r\n", LineEnding::Crlf),
+            ("a\rb\r", LineEnding::Cr),
+            ("a\nb\r\n", LineEnding::Mixed),
+            ("no break", LineEnding::None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(detect_line_ending(text), expected, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::test_runner::now_millis;
+use crate::workspace::{Access, FsError, Workspace, WriteConflict};
+
+const VERSIONS_DIR: &str = "file-versions";
+const INDEX_FILE: &str = "index.json";
+/// Backups kept per file; the oldest are deleted first
+const MAX_VERSIONS_PER_FILE: usize = 20;
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+// Guards the backup indexes against concurrent writes
+static VERSIONS_LOCK: Mutex<()> = Mutex::new(());
+// Makes the hash check and the write of `write_if_unchanged` one step for our own writers
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Content of a file as it was before one of our writes replaced it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileVersion {
+    pub version_id: String,
+    /// Milliseconds since the Unix epoch
+    pub created_at: u64,
+    pub size: u64,
+    /// SHA-256 of the content, hex encoded
+    pub hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct VersionIndex {
+    path: String,
+    /// Oldest first
+    versions: Vec<FileVersion>,
+}
+
+pub fn sha256_hex(content: &[u8]) -> String {
+    to_hex(&Sha256::digest(content))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Replace `path` with `content` so readers see either the old or the new file.
+///
+/// Writes a temp file next to the target with the target's permissions,
+/// fsyncs and renames it over the target, then fsyncs the directory.
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let dir = path.parent().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no parent directory"))?;
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp_path = dir.join(format!(
+        ".{}.scout94-{}-{}.tmp",
+        name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+
+    let permissions = fs::metadata(path).ok().map(|metadata| metadata.permissions());
+    let result = (|| {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        // Never more open than the target, even before the permissions are copied
+        #[cfg(unix)]
+        if let Some(permissions) = &permissions {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(permissions.mode() & 0o777);
+        }
+        let mut temp = options.open(&temp_path)?;
+        // The umask may have narrowed the mode above; set it exactly before any content lands
+        if let Some(permissions) = permissions {
+            temp.set_permissions(permissions)?;
+        }
+        temp.write_all(content)?;
+        temp.sync_all()?;
+        drop(temp);
+        fs::rename(&temp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+        return result;
+    }
+
+    // Persist the rename itself; directories cannot be opened for syncing on Windows
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+
+    Ok(())
+}
+
+/// Where the backups of every file are kept, under the app data dir
+pub fn versions_root(app: &AppHandle) -> Result<PathBuf, FsError> {
+    let data_dir = app.path().app_data_dir().map_err(|e| FsError::Io {
+        path: VERSIONS_DIR.to_string(),
+        message: format!("Failed to resolve app data dir: {}", e),
+    })?;
+    Ok(data_dir.join(VERSIONS_DIR))
+}
+
+/// Backup directory of `path` under `root`, one per file
+fn versions_dir(root: &Path, path: &Path) -> PathBuf {
+    let key = sha256_hex(path.to_string_lossy().as_bytes());
+    root.join(&key[..32])
+}
+
+fn load_index(dir: &Path) -> VersionIndex {
+    fs::read_to_string(dir.join(INDEX_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(dir: &Path, index: &VersionIndex) -> Result<(), FsError> {
+    let path = dir.join(INDEX_FILE);
+    let content = serde_json::to_vec_pretty(index).map_err(|e| FsError::Io {
+        path: path.to_string_lossy().to_string(),
+        message: format!("Failed to serialize version index: {}", e),
+    })?;
+    write_atomic(&path, &content).map_err(|e| FsError::io(&path, "write version index", e))
+}
+
+/// Copy the current content of `path` into its backup history under `root`
+fn backup(root: &Path, path: &Path, current: &[u8]) -> Result<Option<FileVersion>, FsError> {
+    let dir = versions_dir(root, path);
+    fs::create_dir_all(&dir).map_err(|e| FsError::io(&dir, "create backup directory", e))?;
+
+    let _guard = VERSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut index = load_index(&dir);
+    index.path = path.to_string_lossy().to_string();
+
+    let hash = sha256_hex(current);
+    // Writing the same content twice should not push real history out
+    if index.versions.last().is_some_and(|v| v.hash == hash) {
+        return Ok(None);
+    }
+
+    let version = FileVersion {
+        version_id: format!("{}-{}", now_millis(), TEMP_COUNTER.fetch_add(1, Ordering::SeqCst)),
+        created_at: now_millis(),
+        size: current.len() as u64,
+        hash,
+    };
+    let backup_path = dir.join(&version.version_id);
+    write_atomic(&backup_path, current).map_err(|e| FsError::io(&backup_path, "write backup", e))?;
+    index.versions.push(version.clone());
+
+    while index.versions.len() > MAX_VERSIONS_PER_FILE {
+        let oldest = index.versions.remove(0);
+        let _ = fs::remove_file(dir.join(&oldest.version_id));
+    }
+
+    save_index(&dir, &index)?;
+    Ok(Some(version))
+}
+
+/// Current content of `path`, `None` when it does not exist
+fn read_current(path: &Path, action: &str) -> Result<Option<Vec<u8>>, FsError> {
+    match fs::read(path) {
+        Ok(current) => Ok(Some(current)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(FsError::io(path, action, e)),
+    }
+}
+
+/// `FsError::Conflict` unless `current` hashes to `expected_hash` (when given)
+fn check_unchanged(path: &Path, current: Option<&[u8]>, expected_hash: Option<&str>, proposed: &[u8]) -> Result<(), FsError> {
+    let Some(expected) = expected_hash else {
+        return Ok(());
+    };
+    let current_hash = current.map(sha256_hex);
+    if current_hash.as_deref().unwrap_or_default() == expected {
+        return Ok(());
+    }
+    Err(FsError::Conflict(Box::new(WriteConflict {
+        path: path.to_string_lossy().to_string(),
+        message: match current_hash {
+            Some(_) if expected.is_empty() => format!("{} was created by someone else", path.display()),
+            Some(_) => format!("{} changed on disk since it was read", path.display()),
+            None => format!("{} was deleted since it was read", path.display()),
+        },
+        expected_hash: expected.to_string(),
+        current_hash,
+        current_content: current.map(|c| String::from_utf8_lossy(c).to_string()),
+        proposed_content: String::from_utf8_lossy(proposed).to_string(),
+    })))
+}
+
+/// Back up the current content of `path` (if any) and replace it atomically,
+/// creating missing parent directories. Fails with `FsError::Conflict` when
+/// the file on disk no longer hashes to `expected_hash`; no hash skips the
+/// check.
+///
+/// An empty expected hash means the caller expects the file not to exist yet.
+pub fn write_if_unchanged(app: &AppHandle, path: &Path, content: &[u8], expected_hash: Option<&str>) -> Result<(), FsError> {
+    write_checked(&versions_root(app)?, path, content, expected_hash)
+}
+
+/// `write_if_unchanged` with the backups kept under `root` (see `versions_root`)
+pub fn write_checked(root: &Path, path: &Path, content: &[u8], expected_hash: Option<&str>) -> Result<(), FsError> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let current = read_current(path, "read file before writing")?;
+    check_unchanged(path, current.as_deref(), expected_hash, content)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| FsError::io(parent, "create directories", e))?;
+    }
+    if let Some(current) = current.filter(|c| c != content) {
+        backup(root, path, &current)?;
+    }
+
+    write_atomic(path, content).map_err(|e| FsError::io(path, "write file", e))
+}
+
+/// Back up the current content of `path` and delete it, with the same
+/// `expected_hash` check as `write_checked`
+pub fn remove_checked(root: &Path, path: &Path, expected_hash: Option<&str>) -> Result<(), FsError> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let current = read_current(path, "read file before deleting")?;
+    check_unchanged(path, current.as_deref(), expected_hash, b"")?;
+
+    if let Some(current) = current {
+        backup(root, path, &current)?;
+    }
+    fs::remove_file(path).map_err(|e| FsError::io(path, "delete file", e))
+}
+
+/// Earlier versions of a file written from the IDE, newest first
+#[tauri::command]
+pub async fn list_file_versions(app: AppHandle, file_path: String) -> Result<Vec<FileVersion>, FsError> {
+    let path = Workspace::current(&app).resolve(&file_path, Access::Read)?;
+    let dir = versions_dir(&versions_root(&app)?, &path);
+
+    let _guard = VERSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut versions = load_index(&dir).versions;
+    versions.reverse();
+    Ok(versions)
+}
+
+/// Put an earlier version back. The content being replaced is backed up
+/// first, so a restore can itself be undone.
+#[tauri::command]
+pub async fn restore_file_version(app: AppHandle, file_path: String, version_id: String) -> Result<FileVersion, FsError> {
+    let path = Workspace::current(&app).resolve(&file_path, Access::Write)?;
+    let version = restore(&versions_root(&app)?, &path, &version_id)?;
+
+    println!("⏪ Restored {} to version {}", file_path, version_id);
+    Ok(version)
+}
+
+/// Write version `version_id` of `path` from the backups under `root` back to it
+fn restore(root: &Path, path: &Path, version_id: &str) -> Result<FileVersion, FsError> {
+    let dir = versions_dir(root, path);
+    let version = {
+        let _guard = VERSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        load_index(&dir).versions.into_iter().find(|v| v.version_id == version_id)
+    };
+    let version = version.ok_or_else(|| FsError::InvalidPath {
+        path: path.to_string_lossy().to_string(),
+        message: format!("No version {} recorded for {}", version_id, path.display()),
+    })?;
+
+    let backup_path = dir.join(&version.version_id);
+    let content = fs::read(&backup_path).map_err(|e| FsError::io(&backup_path, "read backup", e))?;
+    if sha256_hex(&content) != version.hash {
+        return Err(FsError::Io {
+            path: backup_path.to_string_lossy().to_string(),
+            message: format!("Backup {} is corrupted (hash mismatch)", version_id),
+        });
+    }
+
+    write_checked(root, path, &content, None)?;
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().to_string()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn keeps_only_the_newest_versions() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("versions");
+        let path = temp.path().join("notes.txt");
+        let versions = versions_dir(&root, &path);
+
+        for i in 0..MAX_VERSIONS_PER_FILE + 5 {
+            write_checked(&root, &path, format!("draft {}", i).as_bytes(), None).unwrap();
+        }
+        // Rewriting the same content does not add a version
+        write_checked(&root, &path, b"draft 24", None).unwrap();
+
+        let index = load_index(&versions);
+        assert_eq!(index.versions.len(), MAX_VERSIONS_PER_FILE);
+        assert_eq!(index.versions.first().unwrap().hash, sha256_hex(b"draft 4"));
+        assert_eq!(index.versions.last().unwrap().hash, sha256_hex(b"draft 23"));
+        // One backup file per version plus the index
+        assert_eq!(entries(&versions).len(), MAX_VERSIONS_PER_FILE + 1);
+    }
+
+    #[test]
+    fn restore_puts_a_version_back_and_can_be_undone() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("versions");
+        let path = temp.path().join("app.php");
+        let versions = versions_dir(&root, &path);
+        for content in ["one", "two", "three"] {
+            write_checked(&root, &path, content.as_bytes(), None).unwrap();
+        }
+
+        let first = load_index(&versions).versions.remove(0);
+        let restored = restore(&root, &path, &first.version_id).unwrap();
+        assert_eq!(restored.hash, sha256_hex(b"one"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one");
+        assert_eq!(load_index(&versions).versions.last().unwrap().hash, sha256_hex(b"three"));
+
+        assert!(matches!(restore(&root, &path, "missing"), Err(FsError::InvalidPath { .. })));
+        fs::write(versions.join(&first.version_id), "tampered").unwrap();
+        assert!(matches!(restore(&root, &path, &first.version_id), Err(FsError::Io { .. })));
+    }
+
+    fn conflict(result: Result<(), FsError>) -> WriteConflict {
+        match result {
+            Err(FsError::Conflict(conflict)) => *conflict,
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn writes_over_changed_files_conflict() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("versions");
+        let path = temp.path().join("config.php");
+        fs::write(&path, "v1").unwrap();
+        let read_hash = sha256_hex(b"v1");
+
+        // Someone else saves in between
+        fs::write(&path, "theirs").unwrap();
+        let changed = conflict(write_checked(&root, &path, b"mine", Some(&read_hash)));
+        assert_eq!(changed.current_hash, Some(sha256_hex(b"theirs")));
+        assert_eq!(changed.current_content.as_deref(), Some("theirs"));
+        assert_eq!(changed.proposed_content, "mine");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "theirs");
+        assert!(!root.exists(), "no backup is taken for a rejected write");
+
+        write_checked(&root, &path, b"mine", Some(&sha256_hex(b"theirs"))).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "mine");
+
+        fs::remove_file(&path).unwrap();
+        let deleted = conflict(write_checked(&root, &path, b"again", Some(&sha256_hex(b"mine"))));
+        assert!(deleted.message.contains("deleted"));
+        assert_eq!((deleted.current_hash, deleted.current_content), (None, None));
+    }
+
+    #[test]
+    fn new_files_conflict_with_files_created_meanwhile() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("versions");
+        let path = temp.path().join("lib").join("new.php");
+
+        write_checked(&root, &path, b"first", Some("")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+        let created = conflict(write_checked(&root, &path, b"second", Some("")));
+        assert!(created.message.contains("created by someone else"));
+
+        // A rejected write does not leave directories behind
+        let nested = temp.path().join("a").join("b.php");
+        conflict(write_checked(&root, &nested, b"x", Some("0000")));
+        assert!(!temp.path().join("a").exists());
+    }
+
+    #[test]
+    fn failed_write_leaves_no_temp_file() {
+        let temp = TempDir::new().unwrap();
+        // A non-empty directory cannot be replaced by a file
+        let target = temp.path().join("target");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("keep"), "").unwrap();
+
+        assert!(write_atomic(&target, b"content").is_err());
+        assert_eq!(entries(temp.path()), vec!["target"]);
+        assert_eq!(entries(&target), vec!["keep"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn replacement_keeps_the_target_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("run.sh");
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o751)).unwrap();
+
+        write_atomic(&path, b"#!/bin/sh\necho hi\n").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o751);
+    }
+}
@@ -0,0 +1,350 @@
+use std::fs;
+use std::sync::{LazyLock, Mutex};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::commands::TestResult;
+use crate::limits::ResourceLimit;
+use crate::report::TestReport;
+use crate::test_runner::{RunInfo, RunState};
+use crate::test_suites::RunMode;
+
+const DATABASE_FILE: &str = "history.db";
+/// Rows returned by `list_history` when the filter sets no limit
+const DEFAULT_PAGE_SIZE: u32 = 200;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        run_id          TEXT PRIMARY KEY,
+        project_path    TEXT NOT NULL,
+        suite           TEXT NOT NULL,
+        mode            TEXT NOT NULL,
+        host            TEXT,
+        status          TEXT NOT NULL,
+        success         INTEGER NOT NULL,
+        queued_at       INTEGER NOT NULL,
+        started_at      INTEGER,
+        finished_at     INTEGER,
+        exit_code       INTEGER,
+        limit_exceeded  TEXT,
+        output          TEXT NOT NULL,
+        error           TEXT,
+        report          TEXT
+    );
+    CREATE INDEX IF NOT EXISTS runs_project ON runs (project_path, queued_at);
+    CREATE INDEX IF NOT EXISTS runs_suite ON runs (suite, queued_at);
+";
+
+/// Summary columns shared by `list_history` and `get_run`
+const SUMMARY_COLUMNS: &str =
+    "run_id, project_path, suite, mode, host, status, success, queued_at, started_at, finished_at, exit_code, limit_exceeded";
+
+// Opened on first use, once the app data dir is known
+static DATABASE: LazyLock<Mutex<Option<Connection>>> = LazyLock::new(|| Mutex::new(None));
+
+/// One finished run, without its output
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub run_id: String,
+    pub project_path: String,
+    pub suite: String,
+    pub mode: RunMode,
+    pub host: Option<String>,
+    pub status: RunState,
+    pub success: bool,
+    pub queued_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub limit_exceeded: Option<ResourceLimit>,
+}
+
+/// A stored run with its raw output and parsed results
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryRun {
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+    pub output: String,
+    pub error: Option<String>,
+    pub report: Option<TestReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct HistoryFilter {
+    pub project_path: Option<String>,
+    pub suite: Option<String>,
+    pub status: Option<RunState>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Run `f` against the history database, opening it on first use
+pub fn with_database<T>(app: &AppHandle, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+    let mut database = DATABASE.lock().map_err(|e| format!("History database unavailable: {}", e))?;
+
+    if database.is_none() {
+        let dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+
+        let path = dir.join(DATABASE_FILE);
+        let connection = Connection::open(&path)
+            .map_err(|e| format!("Failed to open history database {:?}: {}", path, e))?;
+        connection
+            .execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to initialise history database: {}", e))?;
+
+        println!("🗄️  Run history: {:?}", path);
+        *database = Some(connection);
+    }
+
+    match database.as_ref() {
+        Some(connection) => f(connection).map_err(|e| format!("History query failed: {}", e)),
+        None => Err("History database unavailable".to_string()),
+    }
+}
+
+/// Store a finished run. Failures are logged, never surfaced to the run itself.
+pub fn record(app: &AppHandle, info: &RunInfo, result: &TestResult) {
+    if let Err(e) = with_database(app, |db| insert_run(db, info, result)) {
+        println!("⚠️ Could not record run {} in history: {}", info.run_id, e);
+    }
+}
+
+fn insert_run(db: &Connection, info: &RunInfo, result: &TestResult) -> rusqlite::Result<usize> {
+    let mode = if info.meta.host.is_some() { RunMode::Remote } else { RunMode::Local };
+    let report = result.report.as_ref().and_then(|r| serde_json::to_string(r).ok());
+
+    db.execute(
+        "INSERT OR REPLACE INTO runs (run_id, project_path, suite, mode, host, status, success, queued_at,
+            started_at, finished_at, exit_code, limit_exceeded, output, error, report)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            info.run_id,
+            info.meta.project_path,
+            info.meta.test_type,
+            to_column(&mode),
+            info.meta.host,
+            to_column(&info.state),
+            result.success,
+            info.queued_at,
+            info.started_at,
+            info.finished_at,
+            info.exit_code,
+            info.limit_exceeded.map(|l| to_column(&l)),
+            result.output,
+            result.error,
+            report,
+        ],
+    )
+}
+
+/// Serialize a unit enum to the same string serde uses in JSON
+fn to_column<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+fn from_column<T: DeserializeOwned>(value: String) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(value)).ok()
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        run_id: row.get(0)?,
+        project_path: row.get(1)?,
+        suite: row.get(2)?,
+        mode: from_column(row.get(3)?).unwrap_or(RunMode::Local),
+        host: row.get(4)?,
+        status: from_column(row.get(5)?).unwrap_or(RunState::Failed),
+        success: row.get(6)?,
+        queued_at: row.get(7)?,
+        started_at: row.get(8)?,
+        finished_at: row.get(9)?,
+        exit_code: row.get(10)?,
+        limit_exceeded: row.get::<_, Option<String>>(11)?.and_then(from_column),
+    })
+}
+
+/// Load a stored run with its output and report
+pub fn load_run(app: &AppHandle, run_id: &str) -> Result<HistoryRun, String> {
+    with_database(app, |db| select_run(db, run_id))?.ok_or_else(|| format!("No run {} in history", run_id))
+}
+
+fn select_run(db: &Connection, run_id: &str) -> rusqlite::Result<Option<HistoryRun>> {
+    let query = format!("SELECT {}, output, error, report FROM runs WHERE run_id = ?1", SUMMARY_COLUMNS);
+    db.query_row(&query, [run_id], |row| {
+        Ok(HistoryRun {
+            entry: entry_from_row(row)?,
+            output: row.get(12)?,
+            error: row.get(13)?,
+            report: row
+                .get::<_, Option<String>>(14)?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+        })
+    })
+    .optional()
+}
+
+fn select_runs(db: &Connection, filter: HistoryFilter) -> rusqlite::Result<Vec<HistoryEntry>> {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if let Some(project_path) = filter.project_path {
+        conditions.push("project_path = ?");
+        values.push(project_path);
+    }
+    if let Some(suite) = filter.suite {
+        conditions.push("suite = ?");
+        values.push(suite);
+    }
+    if let Some(status) = filter.status {
+        conditions.push("status = ?");
+        values.push(to_column(&status));
+    }
+
+    let mut query = format!("SELECT {} FROM runs", SUMMARY_COLUMNS);
+    if !conditions.is_empty() {
+        query.push_str(" WHERE ");
+        query.push_str(&conditions.join(" AND "));
+    }
+    query.push_str(&format!(
+        " ORDER BY queued_at DESC, run_id DESC LIMIT {} OFFSET {}",
+        filter.limit.unwrap_or(DEFAULT_PAGE_SIZE),
+        filter.offset.unwrap_or(0)
+    ));
+
+    let mut statement = db.prepare(&query)?;
+    let rows = statement.query_map(params_from_iter(values.iter()), entry_from_row)?;
+    rows.collect()
+}
+
+fn delete(db: &Connection, run_ids: &[String]) -> rusqlite::Result<usize> {
+    if run_ids.is_empty() {
+        return Ok(0);
+    }
+    let placeholders = vec!["?"; run_ids.len()].join(", ");
+    let query = format!("DELETE FROM runs WHERE run_id IN ({})", placeholders);
+    db.execute(&query, params_from_iter(run_ids.iter()))
+}
+
+/// Recorded runs, newest first, filtered by project, suite and status
+#[tauri::command]
+pub async fn list_history(app: AppHandle, filter: Option<HistoryFilter>) -> Result<Vec<HistoryEntry>, String> {
+    with_database(&app, |db| select_runs(db, filter.unwrap_or_default()))
+}
+
+/// A recorded run including its output and parsed results
+#[tauri::command]
+pub async fn get_run(app: AppHandle, run_id: String) -> Result<HistoryRun, String> {
+    load_run(&app, &run_id)
+}
+
+/// Delete recorded runs and return how many were removed
+#[tauri::command]
+pub async fn delete_runs(app: AppHandle, run_ids: Vec<String>) -> Result<usize, String> {
+    if run_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let deleted = with_database(&app, |db| delete(db, &run_ids))?;
+    println!("🗑️  Deleted {} run(s) from history", deleted);
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_runner::RunMeta;
+
+    fn database() -> Connection {
+        let db = Connection::open_in_memory().unwrap();
+        db.execute_batch(SCHEMA).unwrap();
+        db
+    }
+
+    fn run(run_id: &str, project: &str, suite: &str, state: RunState, queued_at: u64) -> (RunInfo, TestResult) {
+        let info = RunInfo {
+            run_id: run_id.to_string(),
+            meta: RunMeta { test_type: suite.to_string(), project_path: project.to_string(), host: None },
+            state,
+            pid: None,
+            queued_at,
+            started_at: Some(queued_at + 5),
+            finished_at: Some(queued_at + 50),
+            exit_code: Some(if state == RunState::Succeeded { 0 } else { 1 }),
+            limit_exceeded: None,
+        };
+        let output = "Test 1: Root index.html exists...\n✅ Root index.html found\n".to_string();
+        let result = TestResult {
+            success: state == RunState::Succeeded,
+            report: Some(TestReport::parse(&output)),
+            output,
+            error: None,
+            limit_exceeded: None,
+        };
+        (info, result)
+    }
+
+    fn insert(db: &Connection, run: (RunInfo, TestResult)) {
+        insert_run(db, &run.0, &run.1).unwrap();
+    }
+
+    #[test]
+    fn stores_and_loads_a_run() {
+        let db = database();
+        let (mut info, result) = run("run-1", "/p", "routing", RunState::Failed, 100);
+        info.meta.host = Some("ci.example.com".to_string());
+        info.limit_exceeded = Some(ResourceLimit::Timeout);
+        insert_run(&db, &info, &result).unwrap();
+
+        let stored = select_run(&db, "run-1").unwrap().unwrap();
+        assert_eq!(stored.entry.mode, RunMode::Remote);
+        assert_eq!(stored.entry.status, RunState::Failed);
+        assert_eq!(stored.entry.limit_exceeded, Some(ResourceLimit::Timeout));
+        assert_eq!(stored.entry.finished_at, Some(150));
+        assert_eq!(stored.output, result.output);
+        assert_eq!(stored.report.unwrap().counts.passed, 1);
+        assert!(select_run(&db, "missing").unwrap().is_none());
+
+        // Recording the same run again replaces it
+        info.state = RunState::Succeeded;
+        insert_run(&db, &info, &result).unwrap();
+        assert_eq!(select_run(&db, "run-1").unwrap().unwrap().entry.status, RunState::Succeeded);
+    }
+
+    #[test]
+    fn lists_newest_first_with_filters_and_paging() {
+        let db = database();
+        insert(&db, run("a", "/p", "routing", RunState::Succeeded, 1));
+        insert(&db, run("b", "/p", "visitor", RunState::Failed, 2));
+        insert(&db, run("c", "/q", "routing", RunState::Failed, 3));
+
+        let ids = |filter: HistoryFilter| -> Vec<String> {
+            select_runs(&db, filter).unwrap().into_iter().map(|e| e.run_id).collect()
+        };
+        assert_eq!(ids(HistoryFilter::default()), vec!["c", "b", "a"]);
+        assert_eq!(ids(HistoryFilter { project_path: Some("/p".to_string()), ..Default::default() }), vec!["b", "a"]);
+        assert_eq!(ids(HistoryFilter { suite: Some("routing".to_string()), ..Default::default() }), vec!["c", "a"]);
+        assert_eq!(ids(HistoryFilter { status: Some(RunState::Failed), ..Default::default() }), vec!["c", "b"]);
+        assert_eq!(ids(HistoryFilter { limit: Some(1), offset: Some(1), ..Default::default() }), vec!["b"]);
+    }
+
+    #[test]
+    fn deletes_runs() {
+        let db = database();
+        insert(&db, run("a", "/p", "routing", RunState::Succeeded, 1));
+        insert(&db, run("b", "/p", "routing", RunState::Succeeded, 2));
+
+        assert_eq!(delete(&db, &[]).unwrap(), 0);
+        assert_eq!(delete(&db, &["a".to_string(), "missing".to_string()]).unwrap(), 1);
+        assert!(select_run(&db, "a").unwrap().is_none());
+        assert!(select_run(&db, "b").unwrap().is_some());
+    }
+}
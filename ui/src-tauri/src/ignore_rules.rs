@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::app_config;
+use crate::classifier::{Classification, Classifier};
+use crate::workspace::{Access, FsError, Workspace};
+
+pub const SCOUT94_IGNORE_FILE: &str = ".scout94ignore";
+const GITIGNORE_FILE: &str = ".gitignore";
+const GIT_EXCLUDE_FILE: &str = ".git/info/exclude";
+
+/// Built-in gitignore-style globs, used unless the user configured their own.
+/// A leading `!` re-includes what an earlier glob excluded.
+pub const DEFAULT_IGNORE_GLOBS: &[&str] = &[
+    ".*",
+    "!.gitignore",
+    "!.htaccess",
+    "!.env.example",
+    "!.scout94ignore",
+    "node_modules/",
+    "__pycache__/",
+    "venv/",
+    "coverage/",
+    "vendor/",
+    "dist/",
+    "build/",
+    "target/",
+    "out/",
+    "*.min.js",
+    "*.min.css",
+];
+
+/// Where the rule deciding a path came from, lowest precedence first
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IgnoreSource {
+    Default,
+    GitExclude,
+    Gitignore,
+    Scout94ignore,
+}
+
+/// The last rule matching a path; a negated rule means it is included again
+#[derive(Debug, Serialize, Clone)]
+pub struct IgnoreRule {
+    pub source: IgnoreSource,
+    /// Ignore file the rule was read from, `None` for default globs
+    pub file: Option<String>,
+    pub pattern: String,
+    pub negated: bool,
+}
+
+/// Ignore semantics for one project: default globs, `.git/info/exclude`,
+/// every `.gitignore` from the root down, then `.scout94ignore`.
+/// Later sources and deeper `.gitignore` files win, as in git.
+pub struct IgnoreRules {
+    root: PathBuf,
+    defaults: Gitignore,
+    exclude: Gitignore,
+    scout94: Gitignore,
+    /// `.gitignore` per directory (relative to the root), loaded on first use
+    gitignores: Mutex<HashMap<PathBuf, Option<Arc<Gitignore>>>>,
+}
+
+fn load_file(dir: &Path, file: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(dir);
+    if file.is_file() {
+        if let Some(e) = builder.add(file) {
+            println!("⚠️ Skipping invalid rules in {:?}: {}", file, e);
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+impl IgnoreRules {
+    pub fn new(root: &Path, default_globs: &[String]) -> IgnoreRules {
+        let mut builder = GitignoreBuilder::new(root);
+        for glob in default_globs {
+            if let Err(e) = builder.add_line(None, glob) {
+                println!("⚠️ Skipping invalid ignore glob {:?}: {}", glob, e);
+            }
+        }
+
+        IgnoreRules {
+            root: root.to_path_buf(),
+            defaults: builder.build().unwrap_or_else(|_| Gitignore::empty()),
+            exclude: load_file(root, &root.join(GIT_EXCLUDE_FILE)),
+            scout94: load_file(root, &root.join(SCOUT94_IGNORE_FILE)),
+            gitignores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Rules for the project containing `real` (an already resolved path), or
+    /// for `real` itself when it is not inside a registered project
+    pub fn for_path(app: &AppHandle, real: &Path) -> IgnoreRules {
+        let workspace = Workspace::current(app);
+        let root = workspace.project_root(real).unwrap_or(real);
+        IgnoreRules::new(root, &default_globs(app))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn gitignore(&self, dir: &Path) -> Option<Arc<Gitignore>> {
+        let mut cache = self.gitignores.lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let absolute = self.root.join(dir);
+                let file = absolute.join(GITIGNORE_FILE);
+                file.is_file().then(|| Arc::new(load_file(&absolute, &file)))
+            })
+            .clone()
+    }
+
+    /// The rule that decides `relative` (a path below the root), if any
+    pub fn check(&self, relative: &Path, is_dir: bool) -> Option<IgnoreRule> {
+        let mut decided = None;
+        let mut consider = |source: IgnoreSource, matched: Match<&ignore::gitignore::Glob>| {
+            let glob = match matched {
+                Match::None => return,
+                Match::Ignore(glob) | Match::Whitelist(glob) => glob,
+            };
+            decided = Some(IgnoreRule {
+                source,
+                file: glob.from().map(|f| f.to_string_lossy().to_string()),
+                pattern: glob.original().to_string(),
+                negated: glob.is_whitelist(),
+            });
+        };
+
+        consider(IgnoreSource::Default, self.defaults.matched_path_or_any_parents(relative, is_dir));
+        consider(IgnoreSource::GitExclude, self.exclude.matched_path_or_any_parents(relative, is_dir));
+
+        // .gitignore files from the root down to the path's own directory
+        let mut dirs: Vec<&Path> = relative.ancestors().skip(1).collect();
+        dirs.reverse();
+        for dir in dirs {
+            if let Some(gitignore) = self.gitignore(dir) {
+                let below = relative.strip_prefix(dir).unwrap_or(relative);
+                consider(IgnoreSource::Gitignore, gitignore.matched_path_or_any_parents(below, is_dir));
+            }
+        }
+
+        consider(IgnoreSource::Scout94ignore, self.scout94.matched_path_or_any_parents(relative, is_dir));
+        decided
+    }
+
+    pub fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        self.check(relative, is_dir).is_some_and(|rule| !rule.negated)
+    }
+
+    /// `is_ignored` for an absolute path; paths outside the root are ignored
+    pub fn is_ignored_path(&self, path: &Path, is_dir: bool) -> bool {
+        match path.strip_prefix(&self.root) {
+            Ok(relative) if relative.as_os_str().is_empty() => false,
+            Ok(relative) => self.is_ignored(relative, is_dir),
+            Err(_) => true,
+        }
+    }
+}
+
+/// The configured default globs, or the built-in ones
+pub fn default_globs(app: &AppHandle) -> Vec<String> {
+    app_config::load(app)
+        .ignore_globs
+        .unwrap_or_else(|| DEFAULT_IGNORE_GLOBS.iter().map(|g| g.to_string()).collect())
+}
+
+/// Whether an ignore file changed, so cached rules must be rebuilt
+pub fn is_ignore_file(path: &Path) -> bool {
+    path.ends_with(GIT_EXCLUDE_FILE)
+        || path.file_name().is_some_and(|name| name == GITIGNORE_FILE || name == SCOUT94_IGNORE_FILE)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct IgnoreExplanation {
+    pub path: String,
+    /// Project root the rules were read from
+    pub root: String,
+    pub ignored: bool,
+    /// The deciding rule, also set when a negation re-included the path
+    pub rule: Option<IgnoreRule>,
+    /// Minified, generated, vendored, ... independent of the rules
+    pub classification: Option<Classification>,
+    /// Hidden from the tree because of its classification
+    pub hidden_by_class: bool,
+}
+
+/// Explain why a path is (or is not) left out of the file tree
+#[tauri::command]
+pub async fn explain_ignore(app: AppHandle, path: String) -> Result<IgnoreExplanation, FsError> {
+    let real = Workspace::current(&app).resolve(&path, Access::Read)?;
+    let rules = IgnoreRules::for_path(&app, &real);
+    let is_dir = real.is_dir();
+
+    let rule = real
+        .strip_prefix(rules.root())
+        .ok()
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .and_then(|relative| rules.check(relative, is_dir));
+    let classifier = Classifier::for_path(&app, &real);
+    let relative = real.strip_prefix(rules.root()).unwrap_or(&real);
+    let classification = if is_dir { None } else { classifier.classify_file(&real, relative) };
+    let hidden_by_class = classification.as_ref().is_some_and(|c| classifier.hides_in_tree(c));
+
+    Ok(IgnoreExplanation {
+        path,
+        root: rules.root().to_string_lossy().to_string(),
+        ignored: rule.as_ref().is_some_and(|rule| !rule.negated) || hidden_by_class,
+        rule,
+        classification,
+        hidden_by_class,
+    })
+}
+
+/// Error listing every glob gitignore syntax rejects
+fn validate_globs(globs: &[String]) -> Result<(), String> {
+    let mut builder = GitignoreBuilder::new("/");
+    let invalid: Vec<String> = globs
+        .iter()
+        .filter_map(|glob| builder.add_line(None, glob).err().map(|e| format!("{:?}: {}", glob, e)))
+        .collect();
+    if invalid.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Invalid ignore globs: {}", invalid.join("; ")))
+    }
+}
+
+/// Replace the default ignore globs; `None` restores the built-in list.
+/// Returns the globs now in effect.
+#[tauri::command]
+pub async fn set_ignore_globs(app: AppHandle, globs: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    if let Some(globs) = &globs {
+        validate_globs(globs)?;
+    }
+    app_config::modify(&app, |config| config.ignore_globs = globs)?;
+
+    let globs = default_globs(&app);
+    println!("⚙️  Ignore globs updated: {:?}", globs);
+    Ok(globs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::project_dir;
+    use tempfile::TempDir;
+
+    fn rules(files: &[(&str, &str)]) -> (TempDir, IgnoreRules) {
+        let dir = project_dir(files);
+        let defaults: Vec<String> = DEFAULT_IGNORE_GLOBS.iter().map(|g| g.to_string()).collect();
+        let rules = IgnoreRules::new(dir.path(), &defaults);
+        (dir, rules)
+    }
+
+    #[test]
+    fn names_containing_ignored_words_are_kept() {
+        let (_dir, rules) = rules(&[]);
+        for file in ["layout.php", "checkout.php", "about.html", "rebuild.sh", "src/distance.php"] {
+            assert!(!rules.is_ignored(Path::new(file), false), "{} should be visible", file);
+        }
+        assert!(rules.is_ignored(Path::new("node_modules"), true));
+        assert!(rules.is_ignored(Path::new("vendor/autoload.php"), false));
+        assert!(rules.is_ignored(Path::new(".env"), false));
+    }
+
+    #[test]
+    fn negation_reincludes_hidden_files() {
+        let (_dir, rules) = rules(&[]);
+        let rule = rules.check(Path::new(".htaccess"), false).unwrap();
+        assert!(rule.negated);
+        assert_eq!(rule.source, IgnoreSource::Default);
+        assert!(!rules.is_ignored(Path::new(".htaccess"), false));
+    }
+
+    #[test]
+    fn later_sources_and_deeper_gitignores_win() {
+        let (_dir, rules) = rules(&[
+            (".gitignore", "*.log\ncache/\n"),
+            ("logs/.gitignore", "!keep.log\n"),
+            (".git/info/exclude", "local.php\n"),
+            (".scout94ignore", "!cache/\nfixtures/\n"),
+        ]);
+
+        assert!(rules.is_ignored(Path::new("logs/error.log"), false));
+        assert!(!rules.is_ignored(Path::new("logs/keep.log"), false));
+        assert_eq!(rules.check(Path::new("local.php"), false).unwrap().source, IgnoreSource::GitExclude);
+        assert!(!rules.is_ignored(Path::new("cache"), true));
+        let rule = rules.check(Path::new("tests/fixtures/a.php"), false).unwrap();
+        assert_eq!(rule.source, IgnoreSource::Scout94ignore);
+        assert_eq!(rule.pattern, "fixtures/");
+        assert!(rule.file.unwrap().ends_with(SCOUT94_IGNORE_FILE));
+    }
+
+    #[test]
+    fn invalid_globs_are_rejected() {
+        let globs = |list: &[&str]| list.iter().map(|g| g.to_string()).collect::<Vec<String>>();
+        assert!(validate_globs(&globs(&["node_modules/", "*.log", "!keep.log", "/build"])).is_ok());
+        let error = validate_globs(&globs(&["vendor/", "[z-a].php", "logs/**/{a,b"])).unwrap_err();
+        assert!(error.contains("\"[z-a].php\"") && error.contains("\"logs/**/{a,b\""), "{}", error);
+        assert!(!error.contains("vendor"));
+    }
+}
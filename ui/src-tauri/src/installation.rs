@@ -0,0 +1,153 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::app_config;
+
+/// Environment variable that points at a Scout94 checkout
+pub const SCOUT94_HOME_ENV: &str = "SCOUT94_HOME";
+
+/// Scripts that must be present for a directory to count as a Scout94 installation
+const REQUIRED_SCRIPTS: &[&str] = &[
+    "run_all_tests.php",
+    "test_routing.php",
+    "run_with_audit.php",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallSource {
+    Environment,
+    AppConfig,
+    Resources,
+    Executable,
+}
+
+impl fmt::Display for InstallSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            InstallSource::Environment => "$SCOUT94_HOME",
+            InstallSource::AppConfig => "app config",
+            InstallSource::Resources => "bundled resources",
+            InstallSource::Executable => "executable ancestor",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Scout94Installation {
+    pub path: String,
+    pub source: InstallSource,
+}
+
+impl Scout94Installation {
+    pub fn script_path(&self, script: &str) -> PathBuf {
+        Path::new(&self.path).join(script)
+    }
+}
+
+/// Find the Scout94 installation.
+///
+/// Candidates are checked in order: `$SCOUT94_HOME`, the path saved in the
+/// app config, the bundled resource dir and the ancestors of the running
+/// binary. The error lists every candidate and why it was rejected.
+pub fn resolve(app: &AppHandle) -> Result<Scout94Installation, String> {
+    let mut tried = Vec::new();
+
+    for (source, candidate) in candidates(app, &mut tried) {
+        match missing_scripts(&candidate) {
+            None => {
+                return Ok(Scout94Installation {
+                    path: candidate.to_string_lossy().to_string(),
+                    source,
+                })
+            }
+            Some(reason) => tried.push(format!("{} {:?}: {}", source, candidate, reason)),
+        }
+    }
+
+    Err(format!(
+        "❌ Scout94 installation not found. Tried:\n  • {}\n\nSet {} or choose the Scout94 folder in settings.",
+        tried.join("\n  • "),
+        SCOUT94_HOME_ENV
+    ))
+}
+
+/// Every directory worth checking, in priority order.
+/// Sources that yield nothing are noted in `tried` so the diagnostic stays complete.
+fn candidates(app: &AppHandle, tried: &mut Vec<String>) -> Vec<(InstallSource, PathBuf)> {
+    let mut found = Vec::new();
+
+    match std::env::var(SCOUT94_HOME_ENV) {
+        Ok(value) if !value.trim().is_empty() => found.push((InstallSource::Environment, PathBuf::from(value.trim()))),
+        _ => tried.push(format!("{}: not set", InstallSource::Environment)),
+    }
+
+    match app_config::load(app).scout94_path {
+        Some(path) => found.push((InstallSource::AppConfig, PathBuf::from(path))),
+        None => tried.push(format!("{}: no scout94_path saved", InstallSource::AppConfig)),
+    }
+
+    match app.path().resource_dir() {
+        Ok(dir) => {
+            found.push((InstallSource::Resources, dir.join("scout94")));
+            found.push((InstallSource::Resources, dir));
+        }
+        Err(e) => tried.push(format!("{}: unavailable ({})", InstallSource::Resources, e)),
+    }
+
+    match std::env::current_exe() {
+        Ok(exe) => {
+            for ancestor in exe.ancestors().skip(1) {
+                found.push((InstallSource::Executable, ancestor.to_path_buf()));
+            }
+        }
+        Err(e) => tried.push(format!("{}: unavailable ({})", InstallSource::Executable, e)),
+    }
+
+    found
+}
+
+/// Why `dir` is not a Scout94 installation, or `None` if it is one
+fn missing_scripts(dir: &Path) -> Option<String> {
+    if !dir.is_dir() {
+        return Some("not a directory".to_string());
+    }
+
+    let missing: Vec<&str> = REQUIRED_SCRIPTS
+        .iter()
+        .copied()
+        .filter(|script| !dir.join(script).is_file())
+        .collect();
+
+    if missing.is_empty() {
+        None
+    } else {
+        Some(format!("missing {}", missing.join(", ")))
+    }
+}
+
+/// Report which Scout94 installation the app will use
+#[tauri::command]
+pub async fn locate_scout94(app: AppHandle) -> Result<Scout94Installation, String> {
+    resolve(&app)
+}
+
+/// Validate a Scout94 checkout and remember it in the app config
+#[tauri::command]
+pub async fn set_scout94_path(app: AppHandle, path: String) -> Result<Scout94Installation, String> {
+    let dir = PathBuf::from(&path);
+    if let Some(reason) = missing_scripts(&dir) {
+        return Err(format!("❌ {} is not a Scout94 installation: {}", path, reason));
+    }
+
+    app_config::modify(&app, |config| config.scout94_path = Some(path.clone()))?;
+
+    println!("📌 Scout94 installation set to {}", path);
+    Ok(Scout94Installation {
+        path,
+        source: InstallSource::AppConfig,
+    })
+}
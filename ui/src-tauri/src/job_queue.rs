@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{LazyLock, Mutex};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::oneshot;
+
+use crate::app_config;
+use crate::limits::RunLimits;
+use crate::test_runner::{self, now_millis, RunCompleteEvent, RunMeta, RunState};
+
+/// Emitted whenever jobs are queued, started, finished or cancelled
+pub const QUEUE_EVENT: &str = "test-queue-changed";
+
+/// Matches the `general.parallelProcesses` default in the settings schema
+const DEFAULT_MAX_PARALLEL_RUNS: usize = 4;
+
+static QUEUE: LazyLock<Mutex<JobQueue>> = LazyLock::new(|| Mutex::new(JobQueue::default()));
+
+struct Job {
+    run_id: String,
+    meta: RunMeta,
+    priority: i32,
+    seq: u64,
+    queued_at: u64,
+    /// Jobs with the same key never run at the same time
+    project_key: String,
+    command: Command,
+    limits: RunLimits,
+    completion: oneshot::Sender<RunCompleteEvent>,
+}
+
+#[derive(Default)]
+struct JobQueue {
+    /// Waiting jobs, highest priority first and FIFO within a priority
+    pending: Vec<Job>,
+    /// Running run ids and the project each one holds
+    running: HashMap<String, String>,
+    max_parallel_runs: Option<usize>,
+    next_seq: u64,
+}
+
+impl JobQueue {
+    /// Add a job behind waiting jobs of the same or higher priority
+    fn push(&mut self, mut job: Job) {
+        job.seq = self.next_seq;
+        self.next_seq += 1;
+        self.pending.push(job);
+        self.pending.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.seq.cmp(&b.seq)));
+    }
+
+    /// Move every waiting job that fits in a free slot and whose project is idle to `running`
+    fn take_startable(&mut self) -> Vec<Job> {
+        let limit = self.max_parallel_runs.unwrap_or(DEFAULT_MAX_PARALLEL_RUNS).max(1);
+        let mut started = Vec::new();
+        let mut i = 0;
+        while self.running.len() < limit && i < self.pending.len() {
+            let busy = self.running.values().any(|key| *key == self.pending[i].project_key);
+            if busy {
+                i += 1;
+                continue;
+            }
+            let job = self.pending.remove(i);
+            self.running.insert(job.run_id.clone(), job.project_key.clone());
+            started.push(job);
+        }
+        started
+    }
+}
+
+/// A job accepted by the queue; `completion` resolves with the `test-run-complete` payload
+pub struct QueuedRun {
+    pub run_id: String,
+    pub completion: oneshot::Receiver<RunCompleteEvent>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct QueueEntry {
+    pub run_id: String,
+    pub meta: RunMeta,
+    pub priority: i32,
+    /// 1-based position among waiting jobs
+    pub position: usize,
+    pub queued_at: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct QueueSnapshot {
+    pub max_parallel_runs: usize,
+    pub running: Vec<String>,
+    pub queued: Vec<QueueEntry>,
+}
+
+fn project_key(meta: &RunMeta) -> String {
+    match &meta.host {
+        Some(host) => format!("{}:{}", host, meta.project_path),
+        None => std::fs::canonicalize(&meta.project_path)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| meta.project_path.clone()),
+    }
+}
+
+/// Queue `command` as a test run and start it once a slot and its project are free
+pub fn enqueue(app: &AppHandle, command: Command, limits: RunLimits, meta: RunMeta, priority: i32) -> QueuedRun {
+    let project_key = project_key(&meta);
+    let run_id = test_runner::reserve_run(meta.clone());
+    let (completion_tx, completion_rx) = oneshot::channel();
+
+    if let Ok(mut queue) = QUEUE.lock() {
+        if queue.max_parallel_runs.is_none() {
+            queue.max_parallel_runs = app_config::load(app).max_parallel_runs;
+        }
+
+        queue.push(Job {
+            run_id: run_id.clone(),
+            meta,
+            priority,
+            seq: 0,
+            queued_at: now_millis(),
+            project_key,
+            command,
+            limits,
+            completion: completion_tx,
+        });
+    }
+
+    println!("📥 Queued run {} (priority {})", run_id, priority);
+    dispatch(app);
+
+    QueuedRun {
+        run_id,
+        completion: completion_rx,
+    }
+}
+
+/// Start every waiting job that fits in a free slot and whose project is idle
+fn dispatch(app: &AppHandle) {
+    let to_start: Vec<Job> = match QUEUE.lock() {
+        Ok(mut queue) => queue.take_startable(),
+        Err(_) => return,
+    };
+
+    for job in to_start {
+        let Job { run_id, command, limits, completion, .. } = job;
+        let finished_app = app.clone();
+        let finished_id = run_id.clone();
+
+        let started = test_runner::start_run(app.clone(), &run_id, command, limits, move |event| {
+            let _ = completion.send(event);
+            job_finished(&finished_app, &finished_id);
+        });
+        if let Err(e) = started {
+            println!("❌ Run {} could not start: {}", run_id, e);
+        }
+    }
+
+    emit_snapshot(app);
+}
+
+fn job_finished(app: &AppHandle, run_id: &str) {
+    if let Ok(mut queue) = QUEUE.lock() {
+        queue.running.remove(run_id);
+    }
+    dispatch(app);
+}
+
+/// Remove a job that has not started yet. Returns false if it is not waiting.
+pub fn cancel_queued(app: &AppHandle, run_id: &str) -> bool {
+    let job = match QUEUE.lock() {
+        Ok(mut queue) => match queue.pending.iter().position(|j| j.run_id == run_id) {
+            Some(index) => queue.pending.remove(index),
+            None => return false,
+        },
+        Err(_) => return false,
+    };
+
+    println!("🗑️  Removed run {} from the queue", run_id);
+    let event = test_runner::finish_unstarted(app, run_id, RunState::Cancelled, "Run cancelled before it started".to_string());
+    let _ = job.completion.send(event);
+
+    emit_snapshot(app);
+    true
+}
+
+/// Drop every waiting job (used on window close)
+pub fn clear() {
+    if let Ok(mut queue) = QUEUE.lock() {
+        queue.pending.clear();
+    }
+}
+
+fn snapshot() -> Result<QueueSnapshot, String> {
+    let queue = QUEUE.lock().map_err(|e| format!("Job queue unavailable: {}", e))?;
+
+    let mut running: Vec<String> = queue.running.keys().cloned().collect();
+    running.sort();
+
+    Ok(QueueSnapshot {
+        max_parallel_runs: queue.max_parallel_runs.unwrap_or(DEFAULT_MAX_PARALLEL_RUNS),
+        running,
+        queued: queue
+            .pending
+            .iter()
+            .enumerate()
+            .map(|(i, job)| QueueEntry {
+                run_id: job.run_id.clone(),
+                meta: job.meta.clone(),
+                priority: job.priority,
+                position: i + 1,
+                queued_at: job.queued_at,
+            })
+            .collect(),
+    })
+}
+
+fn emit_snapshot(app: &AppHandle) {
+    if let Ok(snapshot) = snapshot() {
+        let _ = app.emit(QUEUE_EVENT, snapshot);
+    }
+}
+
+/// Running and waiting jobs, with the position of each waiting job
+#[tauri::command]
+pub async fn get_queue() -> Result<QueueSnapshot, String> {
+    snapshot()
+}
+
+/// Change how many test runs may execute at once and persist the setting
+#[tauri::command]
+pub async fn set_max_parallel_runs(app: AppHandle, max_parallel_runs: usize) -> Result<QueueSnapshot, String> {
+    let max_parallel_runs = max_parallel_runs.max(1);
+
+    app_config::modify(&app, |config| config.max_parallel_runs = Some(max_parallel_runs))?;
+
+    if let Ok(mut queue) = QUEUE.lock() {
+        queue.max_parallel_runs = Some(max_parallel_runs);
+    }
+    println!("⚙️  Max parallel test runs set to {}", max_parallel_runs);
+
+    dispatch(&app);
+    snapshot()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(run_id: &str, project: &str, priority: i32) -> Job {
+        Job {
+            run_id: run_id.to_string(),
+            meta: RunMeta { test_type: "routing".to_string(), project_path: project.to_string(), host: None },
+            priority,
+            seq: 0,
+            queued_at: 0,
+            project_key: project.to_string(),
+            command: Command::new("true"),
+            limits: RunLimits::default(),
+            completion: oneshot::channel().0,
+        }
+    }
+
+    fn ids(jobs: &[Job]) -> Vec<&str> {
+        jobs.iter().map(|j| j.run_id.as_str()).collect()
+    }
+
+    #[test]
+    fn starts_higher_priority_first_and_fifo_within_a_priority() {
+        let mut queue = JobQueue { max_parallel_runs: Some(1), ..Default::default() };
+        queue.push(job("low", "/a", 0));
+        queue.push(job("high", "/b", 5));
+        queue.push(job("high-later", "/c", 5));
+
+        assert_eq!(ids(&queue.take_startable()), vec!["high"]);
+        queue.running.clear();
+        assert_eq!(ids(&queue.take_startable()), vec!["high-later"]);
+        queue.running.clear();
+        assert_eq!(ids(&queue.take_startable()), vec!["low"]);
+    }
+
+    #[test]
+    fn never_runs_more_than_the_limit() {
+        let mut queue = JobQueue { max_parallel_runs: Some(2), ..Default::default() };
+        for (i, project) in ["/a", "/b", "/c"].iter().enumerate() {
+            queue.push(job(&format!("run-{}", i), project, 0));
+        }
+
+        assert_eq!(ids(&queue.take_startable()), vec!["run-0", "run-1"]);
+        assert!(queue.take_startable().is_empty());
+        queue.running.remove("run-0");
+        assert_eq!(ids(&queue.take_startable()), vec!["run-2"]);
+
+        // A limit of zero still lets one run through
+        let mut queue = JobQueue { max_parallel_runs: Some(0), ..Default::default() };
+        queue.push(job("only", "/a", 0));
+        assert_eq!(ids(&queue.take_startable()), vec!["only"]);
+    }
+
+    #[test]
+    fn runs_one_job_per_project_at_a_time() {
+        let mut queue = JobQueue::default();
+        queue.push(job("a-1", "/a", 10));
+        queue.push(job("a-2", "/a", 10));
+        queue.push(job("b-1", "/b", 0));
+
+        // The second run of /a waits although it outranks /b
+        assert_eq!(ids(&queue.take_startable()), vec!["a-1", "b-1"]);
+        assert_eq!(ids(&queue.pending), vec!["a-2"]);
+        queue.running.remove("a-1");
+        assert_eq!(ids(&queue.take_startable()), vec!["a-2"]);
+    }
+}
@@ -0,0 +1,252 @@
+use std::process::{Command, ExitStatus};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::app_config::{self, AppConfig};
+
+/// Matches the `general.maxExecutionTime` default (minutes) in the settings schema
+const DEFAULT_MAX_EXECUTION_MINUTES: u64 = 120;
+/// Matches the `general.memoryLimitMB` default in the settings schema
+const DEFAULT_MEMORY_LIMIT_MB: u64 = 2048;
+const DEFAULT_MAX_OPEN_FILES: u64 = 1024;
+/// RLIMIT_NPROC counts every process of the user, so this stays generous
+const DEFAULT_MAX_PROCESSES: u64 = 4096;
+
+/// Which limit stopped a run
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceLimit {
+    Timeout,
+    Memory,
+    Cpu,
+    OpenFiles,
+    Processes,
+}
+
+/// Limits applied to one spawned test process
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RunLimits {
+    /// Wall-clock limit, enforced by the runner on every platform
+    pub timeout_secs: Option<u64>,
+    /// Linux only: heap and private writable mappings (RLIMIT_DATA). Unlike
+    /// RLIMIT_AS this ignores the large reservations Chromium and V8 make.
+    pub memory_mb: Option<u64>,
+    /// Linux only: CPU time (RLIMIT_CPU)
+    pub cpu_secs: Option<u64>,
+    /// Linux only: open file descriptors (RLIMIT_NOFILE)
+    pub open_files: Option<u64>,
+    /// Linux only: processes of the user (RLIMIT_NPROC)
+    pub max_processes: Option<u64>,
+}
+
+/// Limit settings the UI can change, persisted in the app config
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunLimitSettings {
+    pub max_execution_minutes: Option<u64>,
+    pub memory_limit_mb: Option<u64>,
+    pub max_open_files: Option<u64>,
+    pub max_processes: Option<u64>,
+}
+
+impl RunLimits {
+    /// Limits for a local run: the suite's own timeout, capped by `maxExecutionTime`
+    pub fn local(config: &AppConfig, suite_timeout_secs: u64) -> RunLimits {
+        let max_secs = config.max_execution_minutes.unwrap_or(DEFAULT_MAX_EXECUTION_MINUTES) * 60;
+        let timeout = suite_timeout_secs.min(max_secs).max(1);
+
+        RunLimits {
+            timeout_secs: Some(timeout),
+            memory_mb: Some(config.memory_limit_mb.unwrap_or(DEFAULT_MEMORY_LIMIT_MB)),
+            cpu_secs: Some(timeout),
+            open_files: Some(config.max_open_files.unwrap_or(DEFAULT_MAX_OPEN_FILES)),
+            max_processes: Some(config.max_processes.unwrap_or(DEFAULT_MAX_PROCESSES)),
+        }
+    }
+
+    /// Limits for an SSH run: only the timeout, since rlimits would bind the local `ssh`
+    pub fn remote(config: &AppConfig, suite_timeout_secs: u64) -> RunLimits {
+        RunLimits {
+            timeout_secs: RunLimits::local(config, suite_timeout_secs).timeout_secs,
+            ..Default::default()
+        }
+    }
+
+    /// Install the rlimits on `command` so they apply to the child only
+    #[cfg(target_os = "linux")]
+    pub fn apply(&self, command: &mut Command) {
+        use std::os::unix::process::CommandExt;
+
+        // CPU gets a few seconds between SIGXCPU (soft) and SIGKILL (hard)
+        let limits = [
+            (libc::RLIMIT_DATA, self.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024)), 0),
+            (libc::RLIMIT_CPU, self.cpu_secs, 5),
+            (libc::RLIMIT_NOFILE, self.open_files, 0),
+            (libc::RLIMIT_NPROC, self.max_processes, 0),
+        ];
+
+        // SAFETY: the closure only calls getrlimit/setrlimit, which are
+        // async-signal-safe, and does not allocate.
+        unsafe {
+            command.pre_exec(move || {
+                for (resource, value, slack) in limits {
+                    let Some(value) = value else { continue };
+
+                    let mut current = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+                    if libc::getrlimit(resource, &mut current) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+
+                    // Never ask for more than the inherited hard limit (EPERM otherwise)
+                    let hard = (value.saturating_add(slack) as libc::rlim_t).min(current.rlim_max);
+                    let soft = (value as libc::rlim_t).min(hard);
+                    let limit = libc::rlimit { rlim_cur: soft, rlim_max: hard };
+                    if libc::setrlimit(resource, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn apply(&self, _command: &mut Command) {}
+
+    /// Human-readable reason for the TestResult error
+    pub fn describe(&self, limit: ResourceLimit) -> String {
+        let value = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_else(|| "?".to_string());
+        match limit {
+            ResourceLimit::Timeout => format!("⏱️ Run stopped: exceeded the {}s time limit", value(self.timeout_secs)),
+            ResourceLimit::Memory => format!("💾 Run stopped: exceeded the {} MB memory limit", value(self.memory_mb)),
+            ResourceLimit::Cpu => format!("🔥 Run stopped: exceeded the {}s CPU time limit", value(self.cpu_secs)),
+            ResourceLimit::OpenFiles => format!("📂 Run stopped: exceeded the limit of {} open files", value(self.open_files)),
+            ResourceLimit::Processes => format!("🧬 Run stopped: exceeded the limit of {} processes", value(self.max_processes)),
+        }
+    }
+}
+
+/// Work out whether a failed run was stopped by one of its rlimits
+pub fn detect(limits: &RunLimits, status: Option<ExitStatus>, output: &str, errors: &str) -> Option<ResourceLimit> {
+    if status.is_some_and(|s| s.success()) {
+        return None;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if limits.cpu_secs.is_some() && status.and_then(|s| s.signal()) == Some(libc::SIGXCPU) {
+            return Some(ResourceLimit::Cpu);
+        }
+    }
+
+    // Only messages that name the failing limit: PHP's allocator when the OS refuses
+    // memory (not its own memory_limit), EMFILE (not ENFILE) and a shell's fork EAGAIN
+    let text = format!("{}\n{}", output, errors).to_lowercase();
+    let mentions = |markers: &[&str]| markers.iter().any(|m| text.contains(m));
+
+    if limits.memory_mb.is_some() && mentions(&["out of memory (allocated", "mmap() failed: [12]"]) {
+        Some(ResourceLimit::Memory)
+    } else if limits.open_files.is_some() && text.lines().any(|l| l.contains("too many open files") && !l.contains("in system")) {
+        Some(ResourceLimit::OpenFiles)
+    } else if limits.max_processes.is_some() && mentions(&["fork: retry: resource temporarily unavailable", "fork: resource temporarily unavailable"]) {
+        Some(ResourceLimit::Processes)
+    } else {
+        None
+    }
+}
+
+/// Persist limit settings (mapped from `maxExecutionTime` and `memoryLimitMB`)
+#[tauri::command]
+pub async fn set_run_limits(app: AppHandle, settings: RunLimitSettings) -> Result<RunLimitSettings, String> {
+    let updated = app_config::modify(&app, |config| {
+        config.max_execution_minutes = settings.max_execution_minutes.or(config.max_execution_minutes);
+        config.memory_limit_mb = settings.memory_limit_mb.or(config.memory_limit_mb);
+        config.max_open_files = settings.max_open_files.or(config.max_open_files);
+        config.max_processes = settings.max_processes.or(config.max_processes);
+        RunLimitSettings {
+            max_execution_minutes: config.max_execution_minutes,
+            memory_limit_mb: config.memory_limit_mb,
+            max_open_files: config.max_open_files,
+            max_processes: config.max_processes,
+        }
+    })?;
+
+    println!("⚙️  Run limits updated: {:?}", settings);
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed() -> Option<ExitStatus> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            Some(ExitStatus::from_raw(1 << 8))
+        }
+        #[cfg(not(unix))]
+        None
+    }
+
+    #[test]
+    fn caps_the_suite_timeout_by_the_configured_maximum() {
+        let config = AppConfig { max_execution_minutes: Some(10), ..Default::default() };
+        let local = RunLimits::local(&config, 3_600);
+        assert_eq!(local.timeout_secs, Some(600));
+        assert_eq!(local.cpu_secs, Some(600));
+        assert_eq!(local.memory_mb, Some(DEFAULT_MEMORY_LIMIT_MB));
+        assert_eq!(RunLimits::local(&config, 0).timeout_secs, Some(1));
+
+        let remote = RunLimits::remote(&config, 300);
+        assert_eq!(remote.timeout_secs, Some(300));
+        assert_eq!((remote.memory_mb, remote.cpu_secs, remote.open_files, remote.max_processes), (None, None, None, None));
+    }
+
+    #[test]
+    fn ignores_messages_that_do_not_name_a_run_limit() {
+        let limits = RunLimits::local(&AppConfig::default(), 60);
+        let unrelated = [
+            // PHP's own memory_limit, not the rlimit
+            "PHP Fatal error:  Allowed memory size of 134217728 bytes exhausted (tried to allocate 20480 bytes)",
+            // EAGAIN from a non-blocking socket
+            "curl: (56) Recv failure: Resource temporarily unavailable",
+            "Warning: fopen(): Too many open files in system",
+            "❌ Database connection failed: out of memory",
+        ];
+        for output in unrelated {
+            assert_eq!(detect(&limits, failed(), output, ""), None, "{}", output);
+        }
+    }
+
+    #[test]
+    fn recognises_messages_that_name_a_run_limit() {
+        let limits = RunLimits::local(&AppConfig::default(), 60);
+        let cases = [
+            ("PHP Fatal error:  Out of memory (allocated 2097152) (tried to allocate 4096 bytes)", ResourceLimit::Memory),
+            ("Fatal error: mmap() failed: [12] Cannot allocate memory", ResourceLimit::Memory),
+            ("PHP Warning:  fopen(report.json): Failed to open stream: Too many open files", ResourceLimit::OpenFiles),
+            ("sh: fork: retry: Resource temporarily unavailable", ResourceLimit::Processes),
+        ];
+        for (errors, expected) in cases {
+            assert_eq!(detect(&limits, failed(), "", errors), Some(expected), "{}", errors);
+        }
+
+        // Not reported for limits the run did not have, nor for runs that succeeded
+        assert_eq!(detect(&RunLimits::default(), failed(), "", cases[0].0), None);
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            assert_eq!(detect(&limits, Some(ExitStatus::from_raw(0)), "", cases[0].0), None);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reports_cpu_from_the_wait_status() {
+        use std::os::unix::process::ExitStatusExt;
+        let limits = RunLimits::local(&AppConfig::default(), 60);
+        assert_eq!(detect(&limits, Some(ExitStatus::from_raw(libc::SIGXCPU)), "", ""), Some(ResourceLimit::Cpu));
+        assert_eq!(detect(&limits, Some(ExitStatus::from_raw(libc::SIGKILL)), "", ""), None);
+    }
+}
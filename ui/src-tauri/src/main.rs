@@ -1,8 +1,33 @@
 // Prevents additional console window on Windows
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod app_config;
+mod classifier;
+mod command_policy;
 mod commands;
+mod compare;
+mod export;
+mod file_reader;
+mod file_versions;
+mod history;
+mod ignore_rules;
+mod installation;
+mod job_queue;
+mod limits;
+mod patch;
 mod remote_scanner;
+mod report;
+mod scanner;
+mod search;
+mod stats;
+mod symbols;
+mod tech_stack;
+mod test_runner;
+mod test_suites;
+#[cfg(test)]
+mod test_support;
+mod watcher;
+mod workspace;
 
 use std::process::{Command, Child};
 use std::sync::Mutex;
@@ -28,6 +53,7 @@ fn main() {
     start_websocket_server();
 
     tauri::Builder::default()
+        .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
             commands::run_scout94_test,
             commands::select_project_folder,
@@ -38,9 +64,51 @@ fn main() {
             commands::execute_command,
             commands::list_directory,
             commands::read_directory_tree,
+            commands::list_children,
             remote_scanner::deploy_scanner_remote,
             remote_scanner::run_scout94_remote,
-            remote_scanner::check_remote_access
+            remote_scanner::check_remote_access,
+            test_runner::cancel_run,
+            test_runner::list_runs,
+            test_runner::get_run_status,
+            installation::locate_scout94,
+            installation::set_scout94_path,
+            report::parse_test_output,
+            test_suites::list_test_suites,
+            job_queue::get_queue,
+            job_queue::set_max_parallel_runs,
+            limits::set_run_limits,
+            history::list_history,
+            history::get_run,
+            history::delete_runs,
+            compare::compare_runs,
+            export::export_run,
+            workspace::register_project_root,
+            workspace::unregister_project_root,
+            workspace::list_project_roots,
+            command_policy::list_pending_commands,
+            command_policy::approve_command,
+            command_policy::reject_command,
+            command_policy::get_command_policy,
+            command_policy::set_command_policy,
+            file_versions::list_file_versions,
+            file_versions::restore_file_version,
+            patch::apply_patch,
+            file_reader::read_file_range,
+            watcher::watch_directory,
+            watcher::unwatch_directory,
+            ignore_rules::explain_ignore,
+            ignore_rules::set_ignore_globs,
+            scanner::scan_project,
+            search::search_project,
+            search::cancel_search,
+            tech_stack::detect_tech_stack,
+            classifier::classify_file,
+            classifier::get_classifier_settings,
+            classifier::set_classifier_settings,
+            stats::project_stats,
+            symbols::file_outline,
+            symbols::find_symbol
         ])
         .setup(|_app| {
             println!("✅ Scout94 Mission Control started!");
@@ -54,6 +122,8 @@ fn main() {
                 stop_websocket_server();
                 // Clean up PID file
                 cleanup_pid_file();
+                // Stop test runs that are still in flight
+                test_runner::kill_all_runs();
                 // Kill any orphaned processes
                 kill_orphaned_processes();
                 println!("✅ Graceful shutdown complete");
@@ -0,0 +1,712 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::file_versions;
+use crate::workspace::{Access, Workspace};
+
+/// Context lines that may be ignored at each end of a hunk (GNU patch default)
+const DEFAULT_FUZZ: usize = 2;
+/// Expected lines included in a conflict report
+const CONFLICT_PREVIEW_LINES: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PatchOptions {
+    /// Leading/trailing context lines allowed to mismatch, `DEFAULT_FUZZ` if absent
+    pub fuzz: Option<usize>,
+    /// Only compute the result; nothing is written
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileAction {
+    Create,
+    Modify,
+    Delete,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HunkResult {
+    /// 0-based index of the hunk within its file
+    pub index: usize,
+    /// Lines between where the hunk said it applies and where it did
+    pub offset: isize,
+    /// Context lines ignored to make the hunk fit
+    pub fuzz: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FilePatchResult {
+    pub path: String,
+    pub action: FileAction,
+    pub hunks: Vec<HunkResult>,
+    /// Resulting content, only returned for dry runs
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PatchConflict {
+    pub path: String,
+    /// `None` for problems with the file itself (exists already, missing, ...)
+    pub hunk: Option<usize>,
+    /// 1-based line the hunk expected to start at in the original file
+    pub line: Option<usize>,
+    pub reason: String,
+    /// First lines the hunk expected to find
+    pub expected: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PatchResult {
+    /// True when every file was (or, for dry runs, would be) patched
+    pub applied: bool,
+    pub dry_run: bool,
+    pub files: Vec<FilePatchResult>,
+    pub conflicts: Vec<PatchConflict>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    Context,
+    Removed,
+    Added,
+}
+
+#[derive(Debug, Clone)]
+struct Hunk {
+    old_start: usize,
+    lines: Vec<(LineKind, String)>,
+    /// "\ No newline at end of file" after the last old / new line
+    old_no_newline: bool,
+    new_no_newline: bool,
+}
+
+#[derive(Debug, Clone)]
+struct FilePatch {
+    old_path: Option<String>,
+    new_path: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+impl Hunk {
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines.iter().filter(|(k, _)| *k != LineKind::Added).map(|(_, l)| l.as_str()).collect()
+    }
+
+    fn leading_context(&self) -> usize {
+        self.lines.iter().take_while(|(k, _)| *k == LineKind::Context).count()
+    }
+
+    fn trailing_context(&self) -> usize {
+        self.lines.iter().rev().take_while(|(k, _)| *k == LineKind::Context).count()
+    }
+}
+
+/// "@@ -12,7 +12,8 @@ optional section" → (old_start, old_len, new_start, new_len)
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize, usize)> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, _) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+
+    let range = |r: &str| -> Option<(usize, usize)> {
+        match r.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((r.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_len) = range(old)?;
+    let (new_start, new_len) = range(new)?;
+    Some((old_start, old_len, new_start, new_len))
+}
+
+/// Path from a ---/+++ header, without timestamp; `None` for /dev/null
+fn header_path(text: &str) -> Option<String> {
+    let path = text.split('\t').next().unwrap_or_default().trim();
+    let path = path.trim_matches('"');
+    if path == "/dev/null" || path.is_empty() {
+        None
+    } else {
+        Some(path.to_string())
+    }
+}
+
+fn parse_patch(patch: &str) -> Result<Vec<FilePatch>, String> {
+    let lines: Vec<&str> = patch.lines().map(|l| l.strip_suffix('\r').unwrap_or(l)).collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+
+        if line.starts_with("GIT binary patch") || (line.starts_with("Binary files ") && line.ends_with(" differ")) {
+            return Err(format!("Binary patches are not supported (line {})", i + 1));
+        }
+
+        if line.starts_with("--- ") && lines.get(i + 1).is_some_and(|next| next.starts_with("+++ ")) {
+            let mut old_path = header_path(&line[4..]);
+            let mut new_path = header_path(&lines[i + 1][4..]);
+
+            // git-style a/ and b/ prefixes
+            let git_style = old_path.as_deref().is_none_or(|p| p.starts_with("a/"))
+                && new_path.as_deref().is_none_or(|p| p.starts_with("b/"));
+            if git_style {
+                old_path = old_path.map(|p| p[2..].to_string());
+                new_path = new_path.map(|p| p[2..].to_string());
+            }
+
+            if old_path.is_none() && new_path.is_none() {
+                return Err(format!("Both sides of the file header at line {} are /dev/null", i + 1));
+            }
+            files.push(FilePatch { old_path, new_path, hunks: Vec::new() });
+            i += 2;
+            continue;
+        }
+
+        if line.starts_with("@@ ") {
+            let (old_start, old_len, _, new_len) =
+                parse_hunk_header(line).ok_or_else(|| format!("Malformed hunk header at line {}: {}", i + 1, line))?;
+            let file = files
+                .last_mut()
+                .ok_or_else(|| format!("Hunk at line {} has no ---/+++ file header", i + 1))?;
+
+            let mut hunk = Hunk { old_start, lines: Vec::new(), old_no_newline: false, new_no_newline: false };
+            let (mut old_seen, mut new_seen) = (0, 0);
+            i += 1;
+
+            while old_seen < old_len || new_seen < new_len {
+                let Some(body) = lines.get(i) else {
+                    return Err(format!("Hunk starting at line {} is truncated", hunk_line(&lines, i)));
+                };
+                let (kind, text) = match body.chars().next() {
+                    Some(' ') => (LineKind::Context, &body[1..]),
+                    Some('-') => (LineKind::Removed, &body[1..]),
+                    Some('+') => (LineKind::Added, &body[1..]),
+                    // Some editors strip the space of empty context lines
+                    None => (LineKind::Context, ""),
+                    Some('\\') => {
+                        mark_no_newline(&mut hunk);
+                        i += 1;
+                        continue;
+                    }
+                    Some(_) => return Err(format!("Unexpected line {} inside hunk: {}", i + 1, body)),
+                };
+                if kind != LineKind::Added {
+                    old_seen += 1;
+                }
+                if kind != LineKind::Removed {
+                    new_seen += 1;
+                }
+                if old_seen > old_len || new_seen > new_len {
+                    return Err(format!("Hunk ending at line {} has more lines than its header says", i + 1));
+                }
+                hunk.lines.push((kind, text.to_string()));
+                i += 1;
+            }
+
+            if lines.get(i).is_some_and(|l| l.starts_with('\\')) {
+                mark_no_newline(&mut hunk);
+                i += 1;
+            }
+
+            file.hunks.push(hunk);
+            continue;
+        }
+
+        // diff --git, index, mode lines and free text between files
+        i += 1;
+    }
+
+    if files.is_empty() {
+        return Err("Patch contains no file changes".to_string());
+    }
+    Ok(files)
+}
+
+/// Line number of the header of the hunk being read, for error messages
+fn hunk_line(lines: &[&str], i: usize) -> usize {
+    lines[..i.min(lines.len())].iter().rposition(|l| l.starts_with("@@ ")).map(|p| p + 1).unwrap_or(i)
+}
+
+fn mark_no_newline(hunk: &mut Hunk) {
+    match hunk.lines.last().map(|(k, _)| *k) {
+        Some(LineKind::Removed) => hunk.old_no_newline = true,
+        Some(LineKind::Added) => hunk.new_no_newline = true,
+        Some(LineKind::Context) => {
+            hunk.old_no_newline = true;
+            hunk.new_no_newline = true;
+        }
+        None => {}
+    }
+}
+
+/// File content split into lines, each keeping its own terminator
+struct SourceText {
+    lines: Vec<(String, &'static str)>,
+    /// Terminator for added lines: the one most lines use
+    eol: &'static str,
+}
+
+impl SourceText {
+    fn new(content: &str) -> SourceText {
+        let lines: Vec<(String, &'static str)> = content
+            .split_inclusive('\n')
+            .map(|line| match line.strip_suffix("\r\n") {
+                Some(text) => (text.to_string(), "\r\n"),
+                None => match line.strip_suffix('\n') {
+                    Some(text) => (text.to_string(), "\n"),
+                    None => (line.to_string(), ""),
+                },
+            })
+            .collect();
+        let crlf = lines.iter().filter(|(_, eol)| *eol == "\r\n").count();
+        let lf = lines.iter().filter(|(_, eol)| *eol == "\n").count();
+        SourceText { lines, eol: if crlf > lf { "\r\n" } else { "\n" } }
+    }
+
+    fn texts(&self) -> Vec<&str> {
+        self.lines.iter().map(|(text, _)| text.as_str()).collect()
+    }
+
+    fn trailing_newline(&self) -> bool {
+        self.lines.last().is_none_or(|(_, eol)| !eol.is_empty())
+    }
+
+    /// Join lines with their own terminators; only the last may lack one
+    fn join(&self, lines: &[(String, &'static str)], trailing_newline: bool) -> String {
+        let mut text = String::new();
+        for (i, (line, eol)) in lines.iter().enumerate() {
+            text.push_str(line);
+            if i + 1 < lines.len() || trailing_newline {
+                text.push_str(if eol.is_empty() { self.eol } else { eol });
+            }
+        }
+        text
+    }
+}
+
+fn matches_at(lines: &[&str], pos: usize, expected: &[&str]) -> bool {
+    pos + expected.len() <= lines.len() && lines[pos..pos + expected.len()].iter().zip(expected).all(|(a, b)| a == b)
+}
+
+/// Closest position at or after `min` where `expected` matches, searching outward from `around`
+fn find_match(lines: &[&str], expected: &[&str], around: usize, min: usize) -> Option<usize> {
+    if expected.is_empty() {
+        return Some(around.clamp(min, lines.len()));
+    }
+    let last = lines.len().checked_sub(expected.len())?;
+    if min > last {
+        return None;
+    }
+    let around = around.clamp(min, last);
+
+    for distance in 0..=last.saturating_sub(min) {
+        if let Some(pos) = around.checked_add(distance).filter(|p| *p <= last) {
+            if matches_at(lines, pos, expected) {
+                return Some(pos);
+            }
+        }
+        if let Some(pos) = around.checked_sub(distance).filter(|p| *p >= min) {
+            if distance > 0 && matches_at(lines, pos, expected) {
+                return Some(pos);
+            }
+        }
+    }
+    None
+}
+
+struct AppliedFile {
+    content: String,
+    hunks: Vec<HunkResult>,
+}
+
+/// Apply the hunks of one file to its current content, collecting every conflict
+fn apply_hunks(original: &str, hunks: &[Hunk], fuzz: usize, path: &str) -> Result<AppliedFile, Vec<PatchConflict>> {
+    let source = SourceText::new(original);
+    let texts = source.texts();
+    let mut output: Vec<(String, &'static str)> = Vec::new();
+    let mut cursor = 0;
+    let mut offset: isize = 0;
+    let mut trailing_newline = source.trailing_newline();
+    let mut results = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let old = hunk.old_lines();
+        // "-0,0" hunks insert before the first line
+        let expected_pos = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+
+        let mut found = None;
+        for f in 0..=fuzz {
+            let lead = f.min(hunk.leading_context());
+            let trail = f.min(hunk.trailing_context());
+            if lead + trail > old.len() || (f > 0 && lead == 0 && trail == 0) {
+                break;
+            }
+            let old_part = &old[lead..old.len() - trail];
+            // Fuzzing away every context line would let the hunk apply anywhere
+            if f > 0 && old_part.is_empty() {
+                break;
+            }
+            if let Some(pos) = find_match(&texts, old_part, expected_pos + lead, cursor) {
+                found = Some((pos, lead, trail, f));
+                break;
+            }
+        }
+
+        let Some((pos, lead, trail, used_fuzz)) = found else {
+            conflicts.push(PatchConflict {
+                path: path.to_string(),
+                hunk: Some(index),
+                line: Some(hunk.old_start),
+                reason: if fuzz > 0 {
+                    format!("Context does not match, even with fuzz {}", fuzz)
+                } else {
+                    "Context does not match".to_string()
+                },
+                expected: old.iter().take(CONFLICT_PREVIEW_LINES).map(|l| l.to_string()).collect(),
+            });
+            continue;
+        };
+
+        output.extend(source.lines[cursor..pos].iter().cloned());
+        // Context lines keep the file's own line endings, added lines get the usual one
+        cursor = pos;
+        for (kind, text) in &hunk.lines[lead..hunk.lines.len() - trail] {
+            match kind {
+                LineKind::Context => {
+                    output.push(source.lines[cursor].clone());
+                    cursor += 1;
+                }
+                LineKind::Removed => cursor += 1,
+                LineKind::Added => output.push((text.clone(), source.eol)),
+            }
+        }
+        offset = pos as isize - lead as isize - hunk.old_start.saturating_sub(1) as isize;
+
+        // Only hunks that reach the end of the file decide its final newline
+        if cursor == source.lines.len() && trail == 0 {
+            if hunk.new_no_newline {
+                trailing_newline = false;
+            } else if hunk.old_no_newline || original.is_empty() {
+                trailing_newline = true;
+            }
+        }
+
+        results.push(HunkResult { index, offset, fuzz: used_fuzz });
+    }
+
+    if !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+
+    output.extend(source.lines[cursor..].iter().cloned());
+    Ok(AppliedFile {
+        content: source.join(&output, trailing_newline),
+        hunks: results,
+    })
+}
+
+fn file_conflict(path: &str, reason: String) -> PatchConflict {
+    PatchConflict { path: path.to_string(), hunk: None, line: None, reason, expected: Vec::new() }
+}
+
+/// Final content of every file in the patch, or all conflicts found
+struct PlannedFile {
+    path: PathBuf,
+    action: FileAction,
+    original: Option<String>,
+    content: String,
+    hunks: Vec<HunkResult>,
+}
+
+fn plan(workspace: &Workspace, base_dir: &Path, files: &[FilePatch], fuzz: usize) -> Result<(Vec<PlannedFile>, Vec<PatchConflict>), String> {
+    let mut planned: Vec<PlannedFile> = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for file in files {
+        let action = match (&file.old_path, &file.new_path) {
+            (None, Some(_)) => FileAction::Create,
+            (Some(_), None) => FileAction::Delete,
+            _ => FileAction::Modify,
+        };
+        if let (Some(old), Some(new)) = (&file.old_path, &file.new_path) {
+            if old != new {
+                conflicts.push(file_conflict(new, format!("Renames are not supported ({} → {})", old, new)));
+                continue;
+            }
+        }
+
+        let relative = file.new_path.as_deref().or(file.old_path.as_deref()).unwrap_or_default();
+        let requested = base_dir.join(relative);
+        let path = workspace.resolve(&requested.to_string_lossy(), Access::Write)?;
+        let display = requested.to_string_lossy().to_string();
+
+        if planned.iter().any(|p| p.path == path) {
+            conflicts.push(file_conflict(&display, "File appears more than once in the patch".to_string()));
+            continue;
+        }
+
+        let original = match fs::read(&path) {
+            Ok(bytes) => Some(
+                String::from_utf8(bytes).map_err(|_| format!("{} is not UTF-8 text and cannot be patched", display))?,
+            ),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("Failed to read {}: {}", display, e)),
+        };
+
+        match (action, &original) {
+            (FileAction::Create, Some(_)) => {
+                conflicts.push(file_conflict(&display, "File to create already exists".to_string()));
+                continue;
+            }
+            (FileAction::Modify | FileAction::Delete, None) => {
+                conflicts.push(file_conflict(&display, "File to patch does not exist".to_string()));
+                continue;
+            }
+            _ => {}
+        }
+
+        match apply_hunks(original.as_deref().unwrap_or_default(), &file.hunks, fuzz, &display) {
+            Ok(applied) => {
+                if action == FileAction::Delete && !applied.content.is_empty() {
+                    conflicts.push(file_conflict(&display, "File to delete has content the patch does not remove".to_string()));
+                    continue;
+                }
+                planned.push(PlannedFile { path, action, original, content: applied.content, hunks: applied.hunks });
+            }
+            Err(mut hunk_conflicts) => conflicts.append(&mut hunk_conflicts),
+        }
+    }
+
+    Ok((planned, conflicts))
+}
+
+/// Write every planned file, undoing the ones already written if one fails.
+///
+/// Each write checks that the file still has the content it was planned
+/// from, so edits made since planning are reported instead of overwritten.
+fn write_all(versions: &Path, planned: &[PlannedFile]) -> Result<(), String> {
+    for (done, file) in planned.iter().enumerate() {
+        // Empty for files the patch creates: they must still not exist
+        let expected = file.original.as_deref().map(|o| file_versions::sha256_hex(o.as_bytes())).unwrap_or_default();
+        let result = match file.action {
+            FileAction::Delete => file_versions::remove_checked(versions, &file.path, Some(&expected)),
+            FileAction::Create | FileAction::Modify => {
+                file_versions::write_checked(versions, &file.path, file.content.as_bytes(), Some(&expected))
+            }
+        };
+
+        if let Err(e) = result {
+            for written in planned[..done].iter().rev() {
+                let rollback = match &written.original {
+                    Some(original) => file_versions::write_atomic(&written.path, original.as_bytes()),
+                    None => fs::remove_file(&written.path),
+                };
+                if let Err(rollback_error) = rollback {
+                    println!("⚠️ Could not roll back {:?}: {}", written.path, rollback_error);
+                }
+            }
+            return Err(format!("Patch not applied, {} could not be written: {}", file.path.display(), e));
+        }
+    }
+    Ok(())
+}
+
+/// Parse, plan and (unless dry running) write a patch, keeping backups under `versions`
+fn apply(workspace: &Workspace, versions: &Path, patch: &str, base_dir: &str, options: PatchOptions) -> Result<PatchResult, String> {
+    let fuzz = options.fuzz.unwrap_or(DEFAULT_FUZZ);
+
+    let base = workspace.resolve(base_dir, Access::Read)?;
+    let files = parse_patch(patch)?;
+    let (planned, conflicts) = plan(workspace, &base, &files, fuzz)?;
+
+    let applied = conflicts.is_empty();
+    if applied && !options.dry_run {
+        write_all(versions, &planned)?;
+        println!("🩹 Applied patch to {} file(s) in {}", planned.len(), base_dir);
+    }
+
+    Ok(PatchResult {
+        applied,
+        dry_run: options.dry_run,
+        files: planned
+            .into_iter()
+            .map(|file| FilePatchResult {
+                path: file.path.to_string_lossy().to_string(),
+                action: file.action,
+                hunks: file.hunks,
+                content: (options.dry_run && file.action != FileAction::Delete).then_some(file.content),
+            })
+            .collect(),
+        conflicts,
+    })
+}
+
+/// Apply a unified diff touching one or more files under `base_dir`.
+///
+/// Either every hunk applies or nothing is written; conflicts are reported per
+/// hunk. With `dry_run` the resulting contents are returned for preview.
+#[tauri::command]
+pub async fn apply_patch(app: AppHandle, patch: String, base_dir: String, options: Option<PatchOptions>) -> Result<PatchResult, String> {
+    let versions = file_versions::versions_root(&app)?;
+    apply(&Workspace::current(&app), &versions, &patch, &base_dir, options.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    struct Project {
+        temp: TempDir,
+        workspace: Workspace,
+    }
+
+    impl Project {
+        fn new(files: &[(&str, &str)]) -> Project {
+            let temp = TempDir::new().unwrap();
+            fs::create_dir(temp.path().join("src")).unwrap();
+            for (name, content) in files {
+                fs::write(temp.path().join("src").join(name), content).unwrap();
+            }
+            let workspace = Workspace::new(&[temp.path().join("src")], &[]);
+            Project { temp, workspace }
+        }
+
+        fn base(&self) -> String {
+            self.temp.path().join("src").canonicalize().unwrap().to_string_lossy().to_string()
+        }
+
+        fn apply(&self, patch: &str, dry_run: bool) -> PatchResult {
+            let options = PatchOptions { fuzz: None, dry_run };
+            apply(&self.workspace, &self.temp.path().join("versions"), patch, &self.base(), options).unwrap()
+        }
+
+        fn read(&self, name: &str) -> Option<String> {
+            fs::read_to_string(self.temp.path().join("src").join(name)).ok()
+        }
+    }
+
+    const TEN_LINES: &str = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+
+    #[test]
+    fn parses_git_and_plain_headers() {
+        let patch = "diff --git a/app.php b/app.php\nindex 1..2 100644\n--- a/app.php\n+++ b/app.php\n@@ -1,2 +1,2 @@ class App\n-old\n+new\n \nsome trailing text\n--- /dev/null\n+++ lib/new.php\t2024-01-01 00:00:00\n@@ -0,0 +1 @@\n+<?php\n";
+        let files = parse_patch(patch).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!((files[0].old_path.as_deref(), files[0].new_path.as_deref()), (Some("app.php"), Some("app.php")));
+        let kinds: Vec<LineKind> = files[0].hunks[0].lines.iter().map(|(k, _)| *k).collect();
+        assert_eq!(kinds, vec![LineKind::Removed, LineKind::Added, LineKind::Context]);
+        assert_eq!((files[1].old_path.as_deref(), files[1].new_path.as_deref()), (None, Some("lib/new.php")));
+
+        assert!(parse_patch("just text\n").is_err());
+        assert!(parse_patch("--- a/x\n+++ b/x\n@@ -1,3 +1,3 @@\n-a\n+b\n").unwrap_err().contains("truncated"));
+        assert!(parse_patch("--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n-b\n").is_err());
+        assert!(parse_patch("Binary files a/x.png and b/x.png differ\n").unwrap_err().contains("Binary"));
+    }
+
+    #[test]
+    fn hunks_apply_with_offset_and_fuzz() {
+        let original = format!("a\nb\n{}", TEN_LINES);
+        let moved = parse_patch("--- a/f\n+++ b/f\n@@ -4,3 +4,3 @@\n 4\n-5\n+five\n 6\n").unwrap();
+        let applied = apply_hunks(&original, &moved[0].hunks, 0, "f").unwrap();
+        assert_eq!(applied.content, format!("a\nb\n{}", TEN_LINES.replace("5\n", "five\n")));
+        assert_eq!((applied.hunks[0].offset, applied.hunks[0].fuzz), (2, 0));
+
+        // The outer context lines no longer match, the inner ones do
+        let fuzzy = parse_patch("--- a/f\n+++ b/f\n@@ -3,5 +3,5 @@\n x\n 4\n-5\n+five\n 6\n y\n").unwrap();
+        assert!(apply_hunks(TEN_LINES, &fuzzy[0].hunks, 0, "f").is_err());
+        let applied = apply_hunks(TEN_LINES, &fuzzy[0].hunks, 1, "f").unwrap();
+        assert_eq!(applied.content, TEN_LINES.replace("5\n", "five\n"));
+        assert_eq!(applied.hunks[0].fuzz, 1);
+    }
+
+    #[test]
+    fn missing_final_newline_is_kept_added_and_removed() {
+        let add_line = parse_patch("--- a/f\n+++ b/f\n@@ -1,2 +1,3 @@\n a\n-b\n\\ No newline at end of file\n+b\n+c\n\\ No newline at end of file\n").unwrap();
+        assert_eq!(apply_hunks("a\nb", &add_line[0].hunks, 0, "f").unwrap().content, "a\nb\nc");
+
+        let add_newline = parse_patch("--- a/f\n+++ b/f\n@@ -1 +1 @@\n-a\n\\ No newline at end of file\n+a\n").unwrap();
+        assert_eq!(apply_hunks("a", &add_newline[0].hunks, 0, "f").unwrap().content, "a\n");
+
+        let drop_newline = parse_patch("--- a/f\n+++ b/f\n@@ -1 +1 @@\n-a\n+a\n\\ No newline at end of file\n").unwrap();
+        assert_eq!(apply_hunks("a\n", &drop_newline[0].hunks, 0, "f").unwrap().content, "a");
+
+        // Hunks away from the end leave it alone
+        let middle = parse_patch("--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n-a\n+A\n b\n").unwrap();
+        assert_eq!(apply_hunks("a\nb\nc", &middle[0].hunks, 0, "f").unwrap().content, "A\nb\nc");
+    }
+
+    #[test]
+    fn line_endings_are_preserved_per_line() {
+        let patch = parse_patch("--- a/f\n+++ b/f\n@@ -1,3 +1,4 @@\n one\n-two\n+TWO\n+extra\n three\n").unwrap();
+        let crlf = apply_hunks("one\r\ntwo\r\nthree\r\n", &patch[0].hunks, 0, "f").unwrap();
+        assert_eq!(crlf.content, "one\r\nTWO\r\nextra\r\nthree\r\n");
+
+        // Mixed files keep each context line's own ending; added lines use the majority
+        let mixed = apply_hunks("one\r\ntwo\nthree\n", &patch[0].hunks, 0, "f").unwrap();
+        assert_eq!(mixed.content, "one\r\nTWO\nextra\nthree\n");
+
+        let crlf_patch = parse_patch("--- a/f\r\n+++ b/f\r\n@@ -1 +1 @@\r\n-one\r\n+ONE\r\n").unwrap();
+        assert_eq!(apply_hunks("one\r\n", &crlf_patch[0].hunks, 0, "f").unwrap().content, "ONE\r\n");
+    }
+
+    #[test]
+    fn creates_and_deletes_through_dev_null() {
+        let project = Project::new(&[("old.php", "<?php\necho 1;\n")]);
+        let patch = "--- /dev/null\n+++ b/lib/new.php\n@@ -0,0 +1,2 @@\n+<?php\n+echo 2;\n--- a/old.php\n+++ /dev/null\n@@ -1,2 +0,0 @@\n-<?php\n-echo 1;\n";
+
+        let result = project.apply(patch, false);
+        assert!(result.applied, "{:?}", result.conflicts);
+        assert_eq!(result.files.iter().map(|f| f.action).collect::<Vec<_>>(), vec![FileAction::Create, FileAction::Delete]);
+        assert_eq!(project.read("lib/new.php").as_deref(), Some("<?php\necho 2;\n"));
+        assert_eq!(project.read("old.php"), None);
+
+        // Creating it again conflicts with the file now there
+        let again = project.apply("--- /dev/null\n+++ b/lib/new.php\n@@ -0,0 +1 @@\n+x\n", false);
+        assert!(!again.applied);
+        assert_eq!(again.conflicts[0].reason, "File to create already exists");
+    }
+
+    #[test]
+    fn one_conflicting_hunk_leaves_every_file_untouched() {
+        let project = Project::new(&[("a.txt", TEN_LINES), ("b.txt", "x\ny\n")]);
+        let patch = "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n-1\n+one\n 2\n--- a/b.txt\n+++ b/b.txt\n@@ -1,2 +1,2 @@\n-nope\n+x\n y\n";
+
+        let result = project.apply(patch, false);
+        assert!(!result.applied);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!((result.conflicts[0].hunk, result.conflicts[0].line), (Some(0), Some(1)));
+        assert_eq!(project.read("a.txt").as_deref(), Some(TEN_LINES));
+        assert_eq!(project.read("b.txt").as_deref(), Some("x\ny\n"));
+    }
+
+    #[test]
+    fn dry_run_returns_content_without_writing() {
+        let project = Project::new(&[("a.txt", "1\n2\n")]);
+        let result = project.apply("--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n-1\n+one\n 2\n", true);
+        assert!(result.applied && result.dry_run);
+        assert_eq!(result.files[0].content.as_deref(), Some("one\n2\n"));
+        assert_eq!(project.read("a.txt").as_deref(), Some("1\n2\n"));
+    }
+
+    #[test]
+    fn files_changed_after_planning_roll_the_patch_back() {
+        let project = Project::new(&[("a.txt", "1\n2\n"), ("b.txt", "x\ny\n")]);
+        let patch = "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n-1\n+one\n 2\n--- a/b.txt\n+++ b/b.txt\n@@ -1,2 +1,2 @@\n-x\n+ex\n y\n";
+        let base = PathBuf::from(project.base());
+        let (planned, conflicts) = plan(&project.workspace, &base, &parse_patch(patch).unwrap(), 0).unwrap();
+        assert!(conflicts.is_empty());
+
+        // Someone edits b.txt between the preview and the write
+        fs::write(base.join("b.txt"), "x\ny\nz\n").unwrap();
+        let error = write_all(&project.temp.path().join("versions"), &planned).unwrap_err();
+        assert!(error.contains("changed on disk"), "{}", error);
+        assert_eq!(project.read("a.txt").as_deref(), Some("1\n2\n"));
+        assert_eq!(project.read("b.txt").as_deref(), Some("x\ny\nz\n"));
+    }
+}
@@ -1,11 +1,22 @@
 use std::process::Command;
 use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::app_config;
+use crate::installation;
+use crate::limits::RunLimits;
+use crate::report::TestReport;
+use crate::job_queue;
+use crate::test_runner::RunMeta;
+use crate::test_suites::{self, RunMode};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RemoteScanResult {
     pub success: bool,
     pub output: String,
     pub error: Option<String>,
+    /// Parsed Scout94 results for test runs
+    pub report: Option<TestReport>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,14 +28,18 @@ pub struct RemoteConfig {
     pub remote_path: String,
 }
 
+/// Quote a value for the remote POSIX shell that runs an SSH command string
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
 /// Deploy Scout94 scanner to remote machine via SSH
 #[tauri::command]
-pub async fn deploy_scanner_remote(config: RemoteConfig) -> Result<RemoteScanResult, String> {
+pub async fn deploy_scanner_remote(app: AppHandle, config: RemoteConfig) -> Result<RemoteScanResult, String> {
     println!("🚀 Deploying Scout94 scanner to remote: {}@{}", config.user, config.host);
     
     // Get Scout94 directory
-    let home_dir = std::env::var("HOME").unwrap_or_else(|_| "/Users/mac".to_string());
-    let scout94_dir = format!("{}/CascadeProjects/scout94", home_dir);
+    let scout94_dir = installation::resolve(&app)?.path;
     
     let port = config.port.unwrap_or(22);
     let remote_host = format!("{}@{}", config.user, config.host);
@@ -44,7 +59,7 @@ pub async fn deploy_scanner_remote(config: RemoteConfig) -> Result<RemoteScanRes
     let mut mkdir_args = ssh_base_args.clone();
     mkdir_args.extend(vec![
         remote_host.clone(),
-        format!("mkdir -p {}/scout94", config.remote_path)
+        format!("mkdir -p {}", shell_quote(&format!("{}/scout94", config.remote_path)))
     ]);
     
     let mkdir_output = Command::new("ssh")
@@ -92,28 +107,23 @@ pub async fn deploy_scanner_remote(config: RemoteConfig) -> Result<RemoteScanRes
         success: true,
         output: format!("Scout94 scanner deployed to {}:{}/scout94/", config.host, config.remote_path),
         error: None,
+        report: None,
     })
 }
 
 /// Run Scout94 tests on remote machine
+///
+/// The SSH session goes through the job queue and run registry, so it can be
+/// cancelled with `cancel_run` while this command is still waiting for the result.
 #[tauri::command]
-pub async fn run_scout94_remote(config: RemoteConfig, test_type: String, target_project_path: String) -> Result<RemoteScanResult, String> {
+pub async fn run_scout94_remote(app: AppHandle, config: RemoteConfig, test_type: String, target_project_path: String, priority: Option<i32>) -> Result<RemoteScanResult, String> {
     println!("🔍 Running Scout94 test remotely: {} on {}", test_type, config.host);
     
     let port = config.port.unwrap_or(22);
     let remote_host = format!("{}@{}", config.user, config.host);
     
-    // Map test types to Scout94's test scripts
-    let test_script = match test_type.as_str() {
-        "routing" => "test_routing.php",
-        "visitor" => "test_user_journey_visitor.php",
-        "user" => "test_user_journey_user.php",
-        "admin" => "test_user_journey_admin.php",
-        "database" => "test_install_db.php",
-        "audit" => "run_with_audit.php",
-        "all" => "run_all_tests.php",
-        _ => "run_all_tests.php",
-    };
+    let suite = test_suites::resolve(&app, &test_type, RunMode::Remote)?;
+    let test_script = suite.script.as_str();
     
     let remote_test_path = format!("{}/scout94/{}", config.remote_path, test_script);
     
@@ -130,32 +140,42 @@ pub async fn run_scout94_remote(config: RemoteConfig, test_type: String, target_
     // Execute test script remotely
     ssh_args.extend(vec![
         remote_host,
-        format!("cd {} && php {}", target_project_path, remote_test_path)
+        format!("cd {} && php {}", shell_quote(&target_project_path), shell_quote(&remote_test_path))
     ]);
     
     println!("📋 Executing: ssh {} (remote test)", test_script);
     
-    let output = Command::new("ssh")
-        .args(&ssh_args)
-        .output()
-        .map_err(|e| format!("Failed to execute remote test: {}", e))?;
+    let mut command = Command::new("ssh");
+    command.args(&ssh_args);
     
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let limits = RunLimits::remote(&app_config::load(&app), suite.default_timeout_secs);
     
-    if output.status.success() {
+    let meta = RunMeta {
+        test_type,
+        project_path: target_project_path,
+        host: Some(config.host.clone()),
+    };
+    
+    let run = job_queue::enqueue(&app, command, limits, meta, priority.unwrap_or(0));
+    let completed = run.completion
+        .await
+        .map_err(|_| format!("Remote test run {} ended without a result", run.run_id))?;
+    
+    if completed.result.success {
         println!("✅ Remote test completed successfully");
         Ok(RemoteScanResult {
             success: true,
-            output: stdout,
+            output: completed.result.output,
             error: None,
+            report: completed.result.report,
         })
     } else {
         println!("❌ Remote test failed");
         Ok(RemoteScanResult {
             success: false,
-            output: stdout,
-            error: Some(stderr),
+            output: completed.result.output,
+            error: completed.result.error,
+            report: completed.result.report,
         })
     }
 }
@@ -189,3 +209,15 @@ pub async fn check_remote_access(config: RemoteConfig) -> Result<bool, String> {
     
     Ok(output.status.success())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_values_for_the_remote_shell() {
+        assert_eq!(shell_quote("/srv/Viz Venture Group"), "'/srv/Viz Venture Group'");
+        assert_eq!(shell_quote("it's; rm -rf ~"), "'it'\\''s; rm -rf ~'");
+        assert_eq!(shell_quote(""), "''");
+    }
+}
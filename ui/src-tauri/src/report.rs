@@ -0,0 +1,578 @@
+use serde::{Deserialize, Serialize};
+
+const IMAGE_EXTENSIONS: &[&str] = &[".png", ".jpg", ".jpeg", ".webp", ".gif"];
+/// Suite used for checks that appear before any suite header
+const DEFAULT_SUITE: &str = "Scout94";
+
+/// Outcome of a single check, suite or run
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    Skipped,
+    Warning,
+}
+
+impl CheckStatus {
+    /// Combine several outcomes: any failure wins, then warnings, then passes
+    fn aggregate(statuses: impl IntoIterator<Item = CheckStatus>) -> Option<CheckStatus> {
+        let mut result = None;
+        for status in statuses {
+            let rank = |s: Option<CheckStatus>| match s {
+                None => 0,
+                Some(CheckStatus::Skipped) => 1,
+                Some(CheckStatus::Passed) => 2,
+                Some(CheckStatus::Warning) => 3,
+                Some(CheckStatus::Failed) => 4,
+            };
+            if rank(Some(status)) > rank(result) {
+                result = Some(status);
+            }
+        }
+        result
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ReportCounts {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub warnings: usize,
+}
+
+impl ReportCounts {
+    fn add(&mut self, status: CheckStatus) {
+        self.total += 1;
+        match status {
+            CheckStatus::Passed => self.passed += 1,
+            CheckStatus::Failed => self.failed += 1,
+            CheckStatus::Skipped => self.skipped += 1,
+            CheckStatus::Warning => self.warnings += 1,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TestCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Result lines and their indented details, without status emoji
+    pub messages: Vec<String>,
+    /// 1-based line in the raw output where the check starts
+    pub line: usize,
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TestSuite {
+    pub name: String,
+    pub status: CheckStatus,
+    pub checks: Vec<TestCheck>,
+    pub counts: ReportCounts,
+    pub duration_ms: Option<u64>,
+}
+
+/// Structured view of Scout94 test output
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TestReport {
+    pub status: CheckStatus,
+    pub suites: Vec<TestSuite>,
+    pub counts: ReportCounts,
+    pub duration_ms: Option<u64>,
+    /// Screenshot files referenced anywhere in the output
+    pub screenshots: Vec<String>,
+}
+
+impl TestReport {
+    /// Parse output without timing information
+    pub fn parse(output: &str) -> TestReport {
+        parse_lines(output.lines().map(|line| (None, line)))
+    }
+
+    /// Parse output lines paired with the time (ms) they were received
+    pub fn parse_timed<'a>(lines: impl IntoIterator<Item = (u64, &'a str)>) -> TestReport {
+        parse_lines(lines.into_iter().map(|(ts, line)| (Some(ts), line)))
+    }
+}
+
+#[derive(Default)]
+struct CheckDraft {
+    name: String,
+    statuses: Vec<CheckStatus>,
+    messages: Vec<String>,
+    line: usize,
+    first_ts: Option<u64>,
+    last_ts: Option<u64>,
+    reported_duration: Option<u64>,
+}
+
+#[derive(Default)]
+struct SuiteDraft {
+    name: String,
+    checks: Vec<CheckDraft>,
+    summary_status: Option<CheckStatus>,
+    first_ts: Option<u64>,
+    last_ts: Option<u64>,
+}
+
+fn parse_lines<'a>(lines: impl IntoIterator<Item = (Option<u64>, &'a str)>) -> TestReport {
+    let mut suites: Vec<SuiteDraft> = Vec::new();
+    let mut screenshots: Vec<String> = Vec::new();
+    // Whether a "Test N:" / "Step N:" block is collecting result lines
+    let mut step_open = false;
+    // Whether the last check of the current suite still accepts detail lines
+    let mut check_open = false;
+    // Inside a "📊 ... SUMMARY" block, whose verdict lines repeat the checks above
+    let mut in_summary = false;
+    let mut first_ts = None;
+    let mut last_ts = None;
+
+    for (index, (ts, raw)) in lines.into_iter().enumerate() {
+        let line_no = index + 1;
+        let trimmed = raw.trim();
+
+        if ts.is_some() {
+            first_ts = first_ts.or(ts);
+            last_ts = ts;
+        }
+
+        for shot in screenshot_paths(trimmed) {
+            if !screenshots.contains(&shot) {
+                screenshots.push(shot);
+            }
+        }
+
+        if trimmed.is_empty() {
+            step_open = false;
+            check_open = false;
+            continue;
+        }
+
+        if let Some(suite) = suites.last_mut() {
+            suite.last_ts = ts.or(suite.last_ts);
+            if check_open {
+                if let Some(check) = suite.checks.last_mut() {
+                    check.last_ts = ts.or(check.last_ts);
+                }
+            }
+        }
+
+        if is_separator(trimmed) {
+            continue;
+        }
+
+        // ║  SCOUT94 FINAL SUMMARY  ║ and │  PHASE 2: ...  │ start new sections
+        if let Some(title) = boxed_title(trimmed) {
+            in_summary = is_summary_title(&title);
+            suites.push(SuiteDraft { name: title, first_ts: ts, last_ts: ts, ..Default::default() });
+            step_open = false;
+            check_open = false;
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_prefix("Running:") {
+            suites.push(SuiteDraft { name: name.trim().to_string(), first_ts: ts, last_ts: ts, ..Default::default() });
+            step_open = false;
+            check_open = false;
+            in_summary = false;
+            continue;
+        }
+
+        // "Routing Validation    ✅ PASSED" in the final summary
+        if let Some((name, status)) = summary_line(trimmed) {
+            if let Some(suite) = suites.iter_mut().find(|s| s.name.eq_ignore_ascii_case(&name)) {
+                suite.summary_status = Some(status);
+                continue;
+            }
+        }
+
+        // "🔍 Scout94 - Routing Validation Test" names the suite of a standalone script
+        if let Some(title) = script_title(trimmed) {
+            let needs_suite = suites.last().is_none_or(|s| !s.checks.is_empty());
+            if needs_suite {
+                suites.push(SuiteDraft { name: title, first_ts: ts, last_ts: ts, ..Default::default() });
+            }
+            step_open = false;
+            check_open = false;
+            in_summary = false;
+            continue;
+        }
+
+        // "📊 ROUTING VALIDATION SUMMARY" is followed by counts and a verdict
+        if trimmed.starts_with('📊') && is_summary_title(trimmed) {
+            in_summary = true;
+            step_open = false;
+            check_open = false;
+            continue;
+        }
+        if in_summary {
+            continue;
+        }
+
+        if suites.is_empty() {
+            suites.push(SuiteDraft { name: DEFAULT_SUITE.to_string(), first_ts: ts, last_ts: ts, ..Default::default() });
+        }
+        let suite = suites.last_mut().expect("suite was just ensured");
+
+        if let Some(name) = step_header(trimmed) {
+            suite.checks.push(CheckDraft { name, line: line_no, first_ts: ts, last_ts: ts, ..Default::default() });
+            step_open = true;
+            check_open = true;
+            continue;
+        }
+
+        if let Some(ms) = reported_duration(trimmed) {
+            if let Some(check) = suite.checks.last_mut() {
+                check.reported_duration = Some(ms);
+            }
+            continue;
+        }
+
+        if let Some((status, message)) = result_line(trimmed) {
+            // Result lines outside a step are checks of their own
+            if !step_open {
+                suite.checks.push(CheckDraft { name: message.clone(), line: line_no, first_ts: ts, last_ts: ts, ..Default::default() });
+                check_open = true;
+            }
+            let check = suite.checks.last_mut().expect("check was just ensured");
+            check.statuses.push(status);
+            check.messages.push(message);
+            continue;
+        }
+
+        // Indented details following a result ("   This will cause 404 on /admin/login")
+        if check_open && raw.starts_with(char::is_whitespace) {
+            if let Some(check) = suite.checks.last_mut() {
+                if !check.statuses.is_empty() {
+                    check.messages.push(trimmed.to_string());
+                }
+            }
+        }
+    }
+
+    let suites: Vec<TestSuite> = suites.into_iter().filter_map(finish_suite).collect();
+
+    let mut counts = ReportCounts::default();
+    for check in suites.iter().flat_map(|s| &s.checks) {
+        counts.add(check.status);
+    }
+
+    TestReport {
+        status: CheckStatus::aggregate(suites.iter().map(|s| s.status)).unwrap_or(CheckStatus::Skipped),
+        suites,
+        counts,
+        duration_ms: elapsed(first_ts, last_ts),
+        screenshots,
+    }
+}
+
+fn finish_suite(draft: SuiteDraft) -> Option<TestSuite> {
+    if draft.checks.is_empty() && draft.summary_status.is_none() {
+        return None;
+    }
+
+    let mut checks: Vec<TestCheck> = Vec::new();
+    for check in draft.checks {
+        // Keep names unique within a suite so runs can be compared check by check
+        let mut name = check.name.clone();
+        let mut n = 2;
+        while checks.iter().any(|c| c.name == name) {
+            name = format!("{} ({})", check.name, n);
+            n += 1;
+        }
+
+        // The runner marks a suite skipped when its script is missing; its notice is no warning
+        let status = match draft.summary_status {
+            Some(CheckStatus::Skipped) => CheckStatus::Skipped,
+            _ => CheckStatus::aggregate(check.statuses).unwrap_or(CheckStatus::Skipped),
+        };
+        checks.push(TestCheck {
+            name,
+            status,
+            messages: check.messages,
+            line: check.line,
+            duration_ms: check.reported_duration.or_else(|| elapsed(check.first_ts, check.last_ts)),
+        });
+    }
+
+    let mut counts = ReportCounts::default();
+    for check in &checks {
+        counts.add(check.status);
+    }
+
+    let status = draft
+        .summary_status
+        .or_else(|| CheckStatus::aggregate(checks.iter().map(|c| c.status)))
+        .unwrap_or(CheckStatus::Skipped);
+
+    Some(TestSuite {
+        name: draft.name,
+        status,
+        checks,
+        counts,
+        duration_ms: elapsed(draft.first_ts, draft.last_ts),
+    })
+}
+
+fn elapsed(first: Option<u64>, last: Option<u64>) -> Option<u64> {
+    match (first, last) {
+        (Some(first), Some(last)) => Some(last.saturating_sub(first)),
+        _ => None,
+    }
+}
+
+fn is_separator(line: &str) -> bool {
+    line.chars().all(|c| "═━─-=┌┐└┘╔╗╚╝ ".contains(c))
+}
+
+fn boxed_title(line: &str) -> Option<String> {
+    if !(line.starts_with('║') || line.starts_with('│')) {
+        return None;
+    }
+    let title = line.trim_matches(|c: char| c == '║' || c == '│' || c.is_whitespace());
+    if title.is_empty() {
+        None
+    } else {
+        Some(title.to_string())
+    }
+}
+
+fn is_summary_title(title: &str) -> bool {
+    title.to_uppercase().contains("SUMMARY")
+}
+
+fn script_title(line: &str) -> Option<String> {
+    let (_, title) = line.split_once("Scout94 - ")?;
+    if line.starts_with(|c: char| c.is_alphanumeric()) {
+        return None;
+    }
+    Some(title.trim().to_string())
+}
+
+/// "Test 3: .htaccess exists..." or "Step 1: Admin logs in..."
+fn step_header(line: &str) -> Option<String> {
+    let rest = line.strip_prefix("Test ").or_else(|| line.strip_prefix("Step "))?;
+    let (number, name) = rest.split_once(':')?;
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let name = name.trim().trim_end_matches("...").trim_end();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Strip leading status emoji and return the status they stand for
+fn result_line(line: &str) -> Option<(CheckStatus, String)> {
+    let mut status = None;
+    let mut rest = line;
+
+    loop {
+        let (found, tail) = if let Some(tail) = rest.strip_prefix('✅') {
+            (CheckStatus::Passed, tail)
+        } else if let Some(tail) = rest.strip_prefix('❌') {
+            (CheckStatus::Failed, tail)
+        } else if let Some(tail) = rest.strip_prefix('⚠') {
+            (CheckStatus::Warning, tail)
+        } else {
+            break;
+        };
+        status = CheckStatus::aggregate(status.into_iter().chain([found]));
+        rest = tail.trim_start_matches('\u{fe0f}');
+    }
+
+    let message = rest.trim().to_string();
+    let status = match status? {
+        CheckStatus::Warning if message.to_lowercase().contains("skip") => CheckStatus::Skipped,
+        other => other,
+    };
+    Some((status, message))
+}
+
+/// Final summary rows print the suite name followed by an emoji and a status word
+fn summary_line(line: &str) -> Option<(String, CheckStatus)> {
+    let status = if line.ends_with("PASSED") {
+        CheckStatus::Passed
+    } else if line.ends_with("FAILED") {
+        CheckStatus::Failed
+    } else if line.ends_with("SKIPPED") {
+        CheckStatus::Skipped
+    } else {
+        return None;
+    };
+
+    let split = line.find(['✅', '❌', '⚠'])?;
+    let name = line[..split].trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some((name.to_string(), status))
+    }
+}
+
+/// "⏱️  Duration: 125ms" or "Duration: 1.5s"
+fn reported_duration(line: &str) -> Option<u64> {
+    let (_, value) = line.split_once("Duration:")?;
+    let value = value.trim();
+    let digits_end = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let number: f64 = value[..digits_end].parse().ok()?;
+    let unit = value[digits_end..].trim();
+
+    let ms = if unit.starts_with("ms") {
+        number
+    } else if unit.starts_with('s') {
+        number * 1000.0
+    } else {
+        return None;
+    };
+    Some(ms.round() as u64)
+}
+
+fn screenshot_paths(line: &str) -> Vec<String> {
+    let lower = line.to_lowercase();
+    if !IMAGE_EXTENSIONS.iter().any(|ext| lower.contains(ext)) {
+        return Vec::new();
+    }
+    let is_image = |s: &str| {
+        let lower = s.to_lowercase();
+        IMAGE_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+    };
+    let clean = |s: &str| s.trim_matches(|c: char| c == '"' || c == '\'' || c == '`' || c == '(' || c == ')' || c == ',').to_string();
+
+    // "Screenshot saved: /path/with spaces/home.png" keeps its spaces
+    if let Some((_, tail)) = line.rsplit_once(": ") {
+        let tail = clean(tail.trim());
+        if is_image(&tail) && tail.contains('/') {
+            return vec![tail];
+        }
+    }
+
+    line.split_whitespace()
+        .map(clean)
+        .filter(|token| is_image(token))
+        .collect()
+}
+
+/// Parse raw Scout94 output the UI already holds (e.g. from an older run)
+#[tauri::command]
+pub async fn parse_test_output(output: String) -> Result<TestReport, String> {
+    Ok(TestReport::parse(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSING: &str = include_str!("../tests/fixtures/report/passing.txt");
+    const FAILING: &str = include_str!("../tests/fixtures/report/failing.txt");
+    const MIXED: &str = include_str!("../tests/fixtures/report/mixed.txt");
+    const TRUNCATED: &str = include_str!("../tests/fixtures/report/truncated.txt");
+
+    fn counts(total: usize, passed: usize, failed: usize, skipped: usize, warnings: usize) -> ReportCounts {
+        ReportCounts { total, passed, failed, skipped, warnings }
+    }
+
+    #[test]
+    fn parses_a_passing_script() {
+        let report = TestReport::parse(PASSING);
+        assert_eq!(report.status, CheckStatus::Passed);
+        assert_eq!(report.suites.len(), 1);
+
+        let suite = &report.suites[0];
+        assert_eq!(suite.name, "Routing Validation Test");
+        // The "✅✅✅ ROUTING: PERFECT!" verdict is not a check of its own
+        assert_eq!(suite.counts, counts(9, 9, 0, 0, 0));
+        assert_eq!(suite.checks[2].name, ".htaccess exists");
+        assert_eq!(suite.checks[2].messages, vec![".htaccess found"]);
+        assert_eq!(suite.checks[2].line, 12);
+    }
+
+    #[test]
+    fn parses_a_failing_script_with_details() {
+        let report = TestReport::parse(FAILING);
+        assert_eq!(report.status, CheckStatus::Failed);
+        assert_eq!(report.counts, counts(9, 2, 4, 2, 1));
+
+        let checks = &report.suites[0].checks;
+        let dev_version = &checks[1];
+        assert_eq!(dev_version.status, CheckStatus::Failed);
+        assert_eq!(
+            dev_version.messages,
+            vec![
+                "DEV VERSION DETECTED!",
+                "Loads /src/main.jsx instead of /dist/assets/index.js",
+                "This will cause 404 errors in production",
+            ]
+        );
+        assert_eq!(checks[3].status, CheckStatus::Skipped);
+        assert_eq!(checks[5].status, CheckStatus::Warning);
+        assert_eq!(checks[5].messages, vec!["admin.php may serve wrong path", "Check readfile() calls"]);
+    }
+
+    #[test]
+    fn parses_a_full_run_with_mixed_results() {
+        let report = TestReport::parse(MIXED);
+        assert_eq!(report.status, CheckStatus::Failed);
+
+        let names: Vec<&str> = report.suites.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Routing Validation",
+                "Database Injection Test",
+                "User Journey: Visitor",
+                "User Journey: Registered User",
+                "User Journey: Admin",
+            ]
+        );
+        let statuses: Vec<CheckStatus> = report.suites.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                CheckStatus::Passed,
+                CheckStatus::Skipped,
+                CheckStatus::Failed,
+                CheckStatus::Skipped,
+                CheckStatus::Skipped,
+            ]
+        );
+
+        // A missing script is reported as skipped, not as a warning
+        assert_eq!(report.suites[1].counts, counts(1, 0, 0, 1, 0));
+        let visitor = &report.suites[2];
+        assert_eq!(visitor.counts, counts(5, 3, 1, 0, 1));
+        // Indented result lines belong to their step; "URL:" lines before them are not details
+        assert_eq!(visitor.checks[0].name, "Visitor lands on homepage");
+        assert_eq!(visitor.checks[0].messages, vec!["Homepage accessible", "React root div present"]);
+        assert_eq!(report.counts, counts(17, 12, 1, 3, 1));
+    }
+
+    #[test]
+    fn parses_output_cut_off_mid_step() {
+        let report = TestReport::parse(TRUNCATED);
+        assert_eq!(report.status, CheckStatus::Failed);
+        assert_eq!(report.suites.len(), 1);
+
+        let checks = &report.suites[0].checks;
+        assert_eq!(checks.len(), 3);
+        // The step that never printed a result is skipped, not passed
+        assert_eq!(checks[2].name, ".htaccess exists");
+        assert_eq!(checks[2].status, CheckStatus::Skipped);
+        assert!(checks[2].messages.is_empty());
+    }
+
+    #[test]
+    fn times_checks_from_line_timestamps() {
+        let lines = PASSING.lines().enumerate().map(|(i, line)| (1_000 + i as u64 * 10, line));
+        let report = TestReport::parse_timed(lines);
+        assert_eq!(report.duration_ms, Some(390));
+        assert_eq!(report.suites[0].checks[0].duration_ms, Some(10));
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::Instant;
+use rayon::prelude::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use crate::classifier::{Classification, Classifier};
+use crate::commands::detect_language_with_content;
+use crate::file_reader::{self, SNIFF_BYTES};
+use crate::file_versions::to_hex;
+use crate::ignore_rules::IgnoreRules;
+use crate::workspace::{Access, FsError, Workspace};
+
+/// Upper bound on scanner threads; smaller machines get one per core
+const MAX_SCAN_THREADS: usize = 8;
+/// Files listed in one scan before it stops
+const MAX_SCAN_FILES: usize = 100_000;
+/// Cached file scans kept across projects; past this only the latest scan's directory stays
+pub const MAX_CACHED_FILES: usize = 2 * MAX_SCAN_FILES;
+/// Shared by the scanner, search and analyzers so they never oversubscribe the CPU
+pub static SCAN_POOL: LazyLock<rayon::ThreadPool> = LazyLock::new(|| {
+    let threads = thread::available_parallelism().map_or(4, |n| n.get()).min(MAX_SCAN_THREADS);
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("scout94-scan-{}", i))
+        .build()
+        .expect("failed to start the scan thread pool")
+});
+
+// Last scan of each file, keyed by its resolved path, so scans of a project
+// and of its subdirectories share results
+static SCAN_CACHE: LazyLock<Mutex<HashMap<PathBuf, FileScan>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FileScan {
+    pub path: String,
+    /// Path below the project root, with `/` separators
+    pub relative: String,
+    pub size: u64,
+    /// Milliseconds since the Unix epoch
+    pub modified_at: Option<u64>,
+    /// `None` for binary files
+    pub lines: Option<u64>,
+    /// SHA-256 of the content, hex encoded
+    pub hash: String,
+    pub executable: bool,
+    pub binary: bool,
+    pub language: Option<String>,
+    /// Set for minified, generated, vendored, lockfile and binary files
+    pub classification: Option<Classification>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectScan {
+    pub root: String,
+    /// Sorted by relative path
+    pub files: Vec<FileScan>,
+    pub total_bytes: u64,
+    /// Files whose size and mtime were unchanged, taken from the cache
+    pub reused: usize,
+    /// More than `MAX_SCAN_FILES` files; the rest were not scanned
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+/// A file found by `collect_files`
+pub struct Candidate {
+    pub path: PathBuf,
+    /// Relative to the root of the ignore rules
+    pub relative: PathBuf,
+    pub metadata: Metadata,
+}
+
+pub fn modified_millis(metadata: &Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &Metadata) -> bool {
+    false
+}
+
+/// Every file below `dir` that the ignore rules let through; symlinks are skipped.
+/// Returns true when it stopped at `MAX_SCAN_FILES`.
+pub fn collect_files(dir: &Path, relative: &Path, rules: &IgnoreRules, files: &mut Vec<Candidate>) -> bool {
+    collect_at_most(dir, relative, rules, MAX_SCAN_FILES, &AtomicBool::new(false), files)
+}
+
+/// Like `collect_files`, but also stops as soon as `cancelled` is set
+pub fn collect_files_until_cancelled(dir: &Path, relative: &Path, rules: &IgnoreRules, cancelled: &AtomicBool, files: &mut Vec<Candidate>) -> bool {
+    collect_at_most(dir, relative, rules, MAX_SCAN_FILES, cancelled, files)
+}
+
+fn collect_at_most(dir: &Path, relative: &Path, rules: &IgnoreRules, limit: usize, cancelled: &AtomicBool, files: &mut Vec<Candidate>) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+
+    for entry in entries.flatten() {
+        if cancelled.load(Ordering::SeqCst) {
+            return true;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let entry_relative = relative.join(entry.file_name());
+        if rules.is_ignored(&entry_relative, metadata.is_dir()) {
+            continue;
+        }
+
+        if metadata.is_dir() {
+            if collect_at_most(&entry.path(), &entry_relative, rules, limit, cancelled, files) {
+                return true;
+            }
+        } else if metadata.is_file() {
+            if files.len() >= limit {
+                return true;
+            }
+            files.push(Candidate { path: entry.path(), relative: entry_relative, metadata });
+        }
+    }
+    false
+}
+
+/// Counts line breaks of any encoding, even when a UTF-16 unit spans two reads
+struct LineCounter {
+    newline: &'static [u8],
+    pending: Vec<u8>,
+    breaks: u64,
+    ends_with_break: bool,
+    empty: bool,
+}
+
+impl LineCounter {
+    fn new(newline: &'static [u8]) -> Self {
+        LineCounter { newline, pending: Vec::new(), breaks: 0, ends_with_break: false, empty: true }
+    }
+
+    fn feed(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        self.empty = false;
+
+        if self.newline.len() == 1 {
+            self.breaks += bytes.iter().filter(|b| **b == self.newline[0]).count() as u64;
+            self.ends_with_break = bytes.last() == Some(&self.newline[0]);
+            return;
+        }
+
+        self.pending.extend_from_slice(bytes);
+        let whole = self.pending.len() - self.pending.len() % self.newline.len();
+        for unit in self.pending[..whole].chunks_exact(self.newline.len()) {
+            self.ends_with_break = unit == self.newline;
+            if self.ends_with_break {
+                self.breaks += 1;
+            }
+        }
+        self.pending.drain(..whole);
+    }
+
+    /// A last line without a trailing break still counts
+    fn lines(&self) -> u64 {
+        if self.empty {
+            0
+        } else {
+            // A dangling half unit is still text after the last break
+            self.breaks + u64::from(!self.ends_with_break || !self.pending.is_empty())
+        }
+    }
+}
+
+/// Hash, count lines and keep the head of a file in one pass
+fn read_file(path: &Path) -> io::Result<(String, Option<u64>, Vec<u8>)> {
+    let mut file = File::open(path)?;
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    (&mut file).take(SNIFF_BYTES as u64).read_to_end(&mut head)?;
+
+    let encoding = file_reader::detect_encoding(&head);
+    let mut hasher = Sha256::new();
+    let mut counter = encoding.map(|encoding| LineCounter::new(encoding.newline()));
+
+    hasher.update(&head);
+    if let Some(counter) = counter.as_mut() {
+        counter.feed(&head);
+    }
+
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        if let Some(counter) = counter.as_mut() {
+            counter.feed(&buffer[..read]);
+        }
+    }
+
+    Ok((to_hex(&hasher.finalize()), counter.map(|c| c.lines()), head))
+}
+
+fn scan_file(candidate: &Candidate, classifier: &Classifier) -> io::Result<FileScan> {
+    let (hash, lines, head) = read_file(&candidate.path)?;
+    let name = candidate.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let size = candidate.metadata.len();
+
+    Ok(FileScan {
+        path: candidate.path.to_string_lossy().to_string(),
+        relative: candidate.relative.to_string_lossy().replace('\\', "/"),
+        size,
+        modified_at: modified_millis(&candidate.metadata),
+        binary: lines.is_none(),
+        classification: classifier.classify(&candidate.relative, &head, size, lines),
+        lines,
+        hash,
+        executable: is_executable(&candidate.metadata),
+        language: detect_language_with_content(&name, &head),
+    })
+}
+
+/// Scan every file below `dir` (an already resolved directory) in parallel,
+/// reusing the previous results for files whose size and mtime are unchanged
+pub fn scan(rules: &IgnoreRules, classifier: &Classifier, dir: &Path) -> ProjectScan {
+    scan_at_most(rules, classifier, dir, MAX_SCAN_FILES)
+}
+
+fn scan_at_most(rules: &IgnoreRules, classifier: &Classifier, dir: &Path, limit: usize) -> ProjectScan {
+    let started = Instant::now();
+    let relative = dir.strip_prefix(rules.root()).unwrap_or(Path::new("")).to_path_buf();
+
+    let mut candidates = Vec::new();
+    let truncated = collect_at_most(dir, &relative, rules, limit, &AtomicBool::new(false), &mut candidates);
+
+    let previous: HashMap<PathBuf, FileScan> = {
+        let mut cache = SCAN_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        candidates.iter().filter_map(|c| cache.remove(&c.path).map(|scan| (c.path.clone(), scan))).collect()
+    };
+    let (mut files, reused): (Vec<FileScan>, Vec<bool>) = SCAN_POOL.install(|| {
+        candidates
+            .par_iter()
+            .filter_map(|candidate| {
+                if let Some(cached) = previous.get(&candidate.path) {
+                    if cached.size == candidate.metadata.len() && cached.modified_at == modified_millis(&candidate.metadata) {
+                        return Some((cached.clone(), true));
+                    }
+                }
+                // Files deleted or unreadable since listing are left out
+                scan_file(candidate, classifier).ok().map(|scan| (scan, false))
+            })
+            .unzip()
+    });
+    files.sort_by(|a, b| a.relative.cmp(&b.relative));
+
+    {
+        let mut cache = SCAN_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        // Whatever is left below `dir` was deleted or is now ignored
+        cache.retain(|path, _| !path.starts_with(dir));
+        if cache.len() + files.len() > MAX_CACHED_FILES {
+            cache.clear();
+        }
+        cache.extend(files.iter().map(|f| (PathBuf::from(&f.path), f.clone())));
+    }
+
+    ProjectScan {
+        root: dir.to_string_lossy().to_string(),
+        total_bytes: files.iter().map(|f| f.size).sum(),
+        reused: reused.iter().filter(|r| **r).count(),
+        files,
+        truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Forget all cached scans, e.g. after the classifier settings changed
+pub fn clear_cache() {
+    SCAN_CACHE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Scan a project (or one of its directories) for per-file metadata: size,
+/// mtime, line count, hash, executable flag and classification.
+///
+/// Runs on a bounded thread pool; unchanged files come from the cache of the
+/// previous scan, so repeated scans only read what changed.
+#[tauri::command]
+pub async fn scan_project(app: AppHandle, project_path: String) -> Result<ProjectScan, FsError> {
+    let dir = Workspace::current(&app).resolve(&project_path, Access::Read)?;
+    if !dir.is_dir() {
+        return Err(FsError::InvalidPath { message: format!("Path is not a directory: {}", project_path), path: project_path });
+    }
+
+    let rules = IgnoreRules::for_path(&app, &dir);
+    let classifier = Classifier::for_path(&app, &dir);
+    let result = scan(&rules, &classifier, &dir);
+    println!(
+        "🔎 Scanned {} files in {} ({}ms, {} from cache)",
+        result.files.len(),
+        project_path,
+        result.duration_ms,
+        result.reused
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ClassifierSettings;
+    use crate::file_reader::TextEncoding;
+    use crate::test_support::project_dir;
+
+    fn count_lines(newline: &'static [u8], chunks: &[&[u8]]) -> u64 {
+        let mut counter = LineCounter::new(newline);
+        for chunk in chunks {
+            counter.feed(chunk);
+        }
+        counter.lines()
+    }
+
+    #[test]
+    fn counts_lines_across_reads() {
+        let lf = TextEncoding::Utf8.newline();
+        assert_eq!(count_lines(lf, &[]), 0);
+        assert_eq!(count_lines(lf, &[b"a\nb"]), 2);
+        assert_eq!(count_lines(lf, &[b"a\n", b"b\n"]), 2);
+        assert_eq!(count_lines(lf, &[b"a\n", b""]), 1);
+
+        // "a\nb\n" in UTF-16LE, with every unit cut in half
+        let utf16: Vec<u8> = "a\nb\n".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        let odd: Vec<&[u8]> = utf16.chunks(3).collect();
+        assert_eq!(count_lines(TextEncoding::Utf16Le.newline(), &odd), 2);
+        assert_eq!(count_lines(TextEncoding::Utf16Le.newline(), &[&utf16[..5]]), 2);
+        // A lone 0x0A byte inside a UTF-16 unit is not a break
+        assert_eq!(count_lines(TextEncoding::Utf16Le.newline(), &[&[0x41, 0x0A, 0x42, 0x00]]), 1);
+    }
+
+    #[test]
+    fn unchanged_files_come_from_the_cache() {
+        let dir = project_dir(&[("a.php", "<?php\n"), ("src/b.php", "<?php\necho 1;\n"), ("src/c.txt", "c\n")]);
+        let rules = IgnoreRules::new(dir.path(), &[]);
+        let classifier = Classifier::new(ClassifierSettings::default());
+
+        let first = scan(&rules, &classifier, dir.path());
+        assert_eq!((first.files.len(), first.reused), (3, 0));
+        assert_eq!(first.files.iter().map(|f| f.relative.as_str()).collect::<Vec<_>>(), ["a.php", "src/b.php", "src/c.txt"]);
+        assert_eq!(first.files[1].lines, Some(2));
+
+        // A subdirectory scan reuses what the project scan found
+        let sub = scan(&rules, &classifier, &dir.path().join("src"));
+        assert_eq!((sub.files.len(), sub.reused), (2, 2));
+        assert_eq!(sub.files[0].relative, "src/b.php");
+
+        fs::write(dir.path().join("src/b.php"), "<?php\necho 1;\necho 2;\n").unwrap();
+        fs::remove_file(dir.path().join("src/c.txt")).unwrap();
+        let again = scan(&rules, &classifier, dir.path());
+        assert_eq!((again.files.len(), again.reused), (2, 1));
+        assert_eq!(again.files[1].lines, Some(3));
+        assert!(!SCAN_CACHE.lock().unwrap().contains_key(&dir.path().join("src/c.txt")));
+    }
+
+    #[test]
+    fn scans_stop_at_the_file_limit() {
+        let dir = project_dir(&[("a.txt", "a"), ("b.txt", "b"), ("c/d.txt", "d")]);
+        let rules = IgnoreRules::new(dir.path(), &[]);
+        let classifier = Classifier::new(ClassifierSettings::default());
+
+        let limited = scan_at_most(&rules, &classifier, dir.path(), 2);
+        assert!(limited.truncated);
+        assert_eq!(limited.files.len(), 2);
+
+        let exact = scan_at_most(&rules, &classifier, dir.path(), 3);
+        assert!(!exact.truncated);
+        assert_eq!(exact.files.len(), 3);
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
+use std::time::Instant;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rayon::prelude::*;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::classifier::Classifier;
+use crate::file_reader;
+use crate::ignore_rules::IgnoreRules;
+use crate::scanner::{self, Candidate, SCAN_POOL};
+use crate::test_runner::now_millis;
+use crate::workspace::{Access, Workspace};
+
+pub const MATCH_EVENT: &str = "search-match";
+pub const COMPLETE_EVENT: &str = "search-complete";
+
+const DEFAULT_MAX_RESULTS: usize = 1_000;
+const HARD_MAX_RESULTS: usize = 10_000;
+const DEFAULT_CONTEXT_LINES: usize = 2;
+const MAX_CONTEXT_LINES: usize = 10;
+/// Larger files are skipped; they are almost never hand-written source
+const MAX_SEARCH_FILE_BYTES: u64 = 8 * 1024 * 1024;
+/// Characters of a matched line sent to the UI
+const MAX_LINE_CHARS: usize = 500;
+
+static SEARCH_COUNTER: AtomicU64 = AtomicU64::new(0);
+// Cancellation flags of running searches
+static SEARCHES: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseMode {
+    Sensitive,
+    Insensitive,
+    /// Insensitive unless the pattern contains an uppercase letter
+    #[default]
+    Smart,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SearchQuery {
+    pub pattern: String,
+    /// Treat `pattern` as a regular expression instead of literal text
+    pub regex: bool,
+    pub case: CaseMode,
+    pub whole_word: bool,
+    /// Globs relative to the project root; when set, only matching files are searched
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub max_results: Option<usize>,
+    pub context_lines: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchMatch {
+    /// 1-based
+    pub line: usize,
+    /// 1-based, in characters
+    pub column: usize,
+    /// Length of the match in characters
+    pub length: usize,
+    pub text: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// All matches found in one file
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchFileMatches {
+    pub search_id: String,
+    pub path: String,
+    pub relative: String,
+    pub matches: Vec<SearchMatch>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchSummary {
+    pub search_id: String,
+    pub files_searched: usize,
+    pub files_matched: usize,
+    pub matches: usize,
+    /// Stopped at the max-results cap
+    pub limit_reached: bool,
+    pub cancelled: bool,
+    pub duration_ms: u64,
+}
+
+fn build_regex(query: &SearchQuery) -> Result<Regex, String> {
+    if query.pattern.is_empty() {
+        return Err("Search pattern is empty".to_string());
+    }
+
+    let mut pattern = if query.regex { query.pattern.clone() } else { regex::escape(&query.pattern) };
+    if query.whole_word {
+        pattern = whole_word(&query.pattern, query.regex, pattern);
+    }
+    let insensitive = match query.case {
+        CaseMode::Sensitive => false,
+        CaseMode::Insensitive => true,
+        CaseMode::Smart => !query.pattern.chars().any(char::is_uppercase),
+    };
+
+    RegexBuilder::new(&pattern)
+        .case_insensitive(insensitive)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))
+}
+
+/// Wrap `pattern` so it can't match inside a longer word. Half boundaries only
+/// look at the outside neighbour, so `$user` and `->save` still match after a
+/// space or `)`. Literal text only gets them on ends that are word characters,
+/// since `$` or `@` can't continue a word anyway.
+fn whole_word(text: &str, regex: bool, pattern: String) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let start = regex || is_word(text.chars().next());
+    let end = regex || is_word(text.chars().next_back());
+    format!(
+        r"{}(?:{}){}",
+        if start { r"\b{start-half}" } else { "" },
+        pattern,
+        if end { r"\b{end-half}" } else { "" },
+    )
+}
+
+fn build_globs(globs: &[String]) -> Result<Option<GlobSet>, String> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        // Globs without a slash match file names anywhere, like in .gitignore
+        let glob = if glob.contains('/') { glob.clone() } else { format!("**/{}", glob) };
+        builder.add(Glob::new(&glob).map_err(|e| format!("Invalid glob {:?}: {}", glob, e))?);
+    }
+    builder.build().map(Some).map_err(|e| format!("Invalid globs: {}", e))
+}
+
+fn truncate_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Read and decode a text file; `None` for oversized files and files the
+/// classifier leaves out of analysis (binary, minified, generated, ...)
+fn read_text(candidate: &Candidate, classifier: &Classifier) -> Option<String> {
+    if candidate.metadata.len() > MAX_SEARCH_FILE_BYTES {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(candidate.metadata.len() as usize);
+    File::open(&candidate.path).ok()?.read_to_end(&mut bytes).ok()?;
+
+    let classification = classifier.classify(&candidate.relative, &bytes, candidate.metadata.len(), None);
+    if classification.is_some_and(|c| classifier.skips_in_analysis(&c)) {
+        return None;
+    }
+
+    file_reader::decode_text(&bytes).map(|(_, text)| text)
+}
+
+/// Matches in one file, stopping at the first one past `max_results`;
+/// `found` counts that dropped match too
+fn search_file(text: &str, regex: &Regex, context: usize, found: &AtomicUsize, max_results: usize) -> Vec<SearchMatch> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut matches = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        for m in regex.find_iter(line) {
+            if found.fetch_add(1, Ordering::SeqCst) >= max_results {
+                return matches;
+            }
+            matches.push(SearchMatch {
+                line: index + 1,
+                column: line[..m.start()].chars().count() + 1,
+                length: m.as_str().chars().count(),
+                text: truncate_line(line),
+                before: lines[index.saturating_sub(context)..index].iter().map(|l| truncate_line(l)).collect(),
+                after: lines[index + 1..(index + 1 + context).min(lines.len())].iter().map(|l| truncate_line(l)).collect(),
+            });
+        }
+    }
+    matches
+}
+
+struct SearchJob {
+    search_id: String,
+    regex: Regex,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    max_results: usize,
+    context: usize,
+    cancelled: Arc<AtomicBool>,
+}
+
+fn run_search(app: &AppHandle, job: &SearchJob, rules: &IgnoreRules, classifier: &Classifier, dir: &Path) -> SearchSummary {
+    let started = Instant::now();
+    let relative = dir.strip_prefix(rules.root()).unwrap_or(Path::new("")).to_path_buf();
+    let mut candidates = Vec::new();
+    scanner::collect_files_until_cancelled(dir, &relative, rules, &job.cancelled, &mut candidates);
+
+    candidates.retain(|c| {
+        job.include.as_ref().is_none_or(|set| set.is_match(&c.relative))
+            && !job.exclude.as_ref().is_some_and(|set| set.is_match(&c.relative))
+    });
+
+    let found = AtomicUsize::new(0);
+    let files_searched = AtomicUsize::new(0);
+    let files_matched = AtomicUsize::new(0);
+
+    SCAN_POOL.install(|| {
+        candidates.par_iter().for_each(|candidate| {
+            // Past the cap only once a match was actually dropped
+            if job.cancelled.load(Ordering::SeqCst) || found.load(Ordering::SeqCst) > job.max_results {
+                return;
+            }
+            let Some(text) = read_text(candidate, classifier) else {
+                return;
+            };
+            files_searched.fetch_add(1, Ordering::SeqCst);
+
+            let matches = search_file(&text, &job.regex, job.context, &found, job.max_results);
+            if matches.is_empty() {
+                return;
+            }
+            files_matched.fetch_add(1, Ordering::SeqCst);
+            let _ = app.emit(MATCH_EVENT, SearchFileMatches {
+                search_id: job.search_id.clone(),
+                path: candidate.path.to_string_lossy().to_string(),
+                relative: candidate.relative.to_string_lossy().replace('\\', "/"),
+                matches,
+            });
+        });
+    });
+
+    let total = found.load(Ordering::SeqCst);
+    SearchSummary {
+        search_id: job.search_id.clone(),
+        files_searched: files_searched.load(Ordering::SeqCst),
+        files_matched: files_matched.load(Ordering::SeqCst),
+        matches: total.min(job.max_results),
+        limit_reached: total > job.max_results,
+        cancelled: job.cancelled.load(Ordering::SeqCst),
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Search the text files of a project and return a search id immediately.
+///
+/// Matches are streamed per file as `search-match` events and a
+/// `search-complete` event carries the summary. Files are filtered by the
+/// project's ignore rules, then by the `include`/`exclude` globs; very large
+/// files and those the classifier leaves out of analysis are skipped.
+#[tauri::command]
+pub async fn search_project(app: AppHandle, project_path: String, query: SearchQuery) -> Result<String, String> {
+    let dir = Workspace::current(&app).resolve(&project_path, Access::Read)?;
+    if !dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", project_path));
+    }
+
+    let search_id = format!("search-{}-{}", now_millis(), SEARCH_COUNTER.fetch_add(1, Ordering::SeqCst));
+    let job = SearchJob {
+        search_id: search_id.clone(),
+        regex: build_regex(&query)?,
+        include: build_globs(&query.include)?,
+        exclude: build_globs(&query.exclude)?,
+        max_results: query.max_results.unwrap_or(DEFAULT_MAX_RESULTS).clamp(1, HARD_MAX_RESULTS),
+        context: query.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES).min(MAX_CONTEXT_LINES),
+        cancelled: Arc::new(AtomicBool::new(false)),
+    };
+    SEARCHES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(search_id.clone(), job.cancelled.clone());
+
+    println!("🔍 Search {} for {:?} in {}", search_id, query.pattern, project_path);
+    thread::spawn(move || {
+        let rules = IgnoreRules::for_path(&app, &dir);
+        let classifier = Classifier::for_path(&app, &dir);
+        let summary = run_search(&app, &job, &rules, &classifier, &dir);
+        SEARCHES.lock().unwrap_or_else(|e| e.into_inner()).remove(&job.search_id);
+
+        println!(
+            "✅ Search {} finished: {} matches in {} files ({}ms)",
+            summary.search_id, summary.matches, summary.files_matched, summary.duration_ms
+        );
+        let _ = app.emit(COMPLETE_EVENT, summary);
+    });
+
+    Ok(search_id)
+}
+
+/// Stop a running search; returns false if it already finished
+#[tauri::command]
+pub async fn cancel_search(search_id: String) -> Result<bool, String> {
+    let searches = SEARCHES.lock().unwrap_or_else(|e| e.into_inner());
+    match searches.get(&search_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::SeqCst);
+            println!("🛑 Cancelling search {}", search_id);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::project_dir;
+
+    fn regex(pattern: &str, configure: impl FnOnce(&mut SearchQuery)) -> Regex {
+        let mut query = SearchQuery { pattern: pattern.to_string(), ..SearchQuery::default() };
+        configure(&mut query);
+        build_regex(&query).unwrap()
+    }
+
+    #[test]
+    fn builds_patterns_from_queries() {
+        assert!(regex("foo", |_| {}).is_match("FOO"));
+        assert!(!regex("Foo", |_| {}).is_match("foo"));
+        assert!(!regex("foo", |q| q.case = CaseMode::Sensitive).is_match("FOO"));
+        assert!(regex("Foo", |q| q.case = CaseMode::Insensitive).is_match("foo"));
+
+        let word = regex("log", |q| q.whole_word = true);
+        assert!(word.is_match("log(1)"));
+        assert!(!word.is_match("login"));
+        assert!(regex("a|b", |q| { q.regex = true; q.whole_word = true; }).is_match("b c"));
+
+        let var = regex("$user", |q| q.whole_word = true);
+        assert_eq!(var.find("echo $user;").map(|m| m.as_str()), Some("$user"));
+        assert!(var.is_match("a$user"));
+        assert!(!var.is_match("$username"));
+        assert!(regex("->save", |q| q.whole_word = true).is_match("$post->save();"));
+        assert!(!regex("->save", |q| q.whole_word = true).is_match("$post->saveAll();"));
+        assert!(regex("@inject", |q| q.whole_word = true).is_match("  @inject('x')"));
+        assert!(regex(r"\$\w+", |q| { q.regex = true; q.whole_word = true; }).is_match("f($id)"));
+
+        assert!(regex("a.b(", |_| {}).is_match("x = a.b(1)"));
+        assert!(!regex("a.b(", |_| {}).is_match("axb("));
+        assert!(regex("a.b", |q| q.regex = true).is_match("axb"));
+
+        assert!(build_regex(&SearchQuery::default()).is_err());
+        assert!(build_regex(&SearchQuery { pattern: "(".to_string(), regex: true, ..SearchQuery::default() }).is_err());
+    }
+
+    #[test]
+    fn globs_without_a_slash_match_anywhere() {
+        assert!(build_globs(&[]).unwrap().is_none());
+
+        let names = build_globs(&["*.php".to_string()]).unwrap().unwrap();
+        assert!(names.is_match("a.php"));
+        assert!(names.is_match("src/deep/a.php"));
+        assert!(!names.is_match("a.js"));
+
+        let anchored = build_globs(&["src/*.js".to_string()]).unwrap().unwrap();
+        assert!(anchored.is_match("src/a.js"));
+        assert!(!anchored.is_match("lib/src/a.js"));
+
+        assert!(build_globs(&["a[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn matches_report_character_columns_and_clamped_context() {
+        let text = "héllo wörld\nsecond\nthird wörld";
+        let found = AtomicUsize::new(0);
+        let matches = search_file(text, &regex("wörld", |_| {}), 2, &found, 10);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[0].line, matches[0].column, matches[0].length), (1, 7, 5));
+        assert!(matches[0].before.is_empty());
+        assert_eq!(matches[0].after, ["second", "third wörld"]);
+        assert_eq!((matches[1].line, matches[1].column), (3, 7));
+        assert_eq!(matches[1].before, ["héllo wörld", "second"]);
+        assert!(matches[1].after.is_empty());
+        assert_eq!(found.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn matches_stop_at_the_cap() {
+        let re = regex("a", |_| {});
+        let found = AtomicUsize::new(0);
+        assert_eq!(search_file("a a\na", &re, 0, &found, 3).len(), 3);
+        // Exactly at the cap nothing was dropped yet
+        assert_eq!(found.load(Ordering::SeqCst), 3);
+
+        let found = AtomicUsize::new(0);
+        assert_eq!(search_file("a a\na a", &re, 0, &found, 3).len(), 3);
+        assert_eq!(found.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn cancelled_walks_stop() {
+        let dir = project_dir(&[("a.php", ""), ("src/b.php", "")]);
+        let rules = IgnoreRules::new(dir.path(), &[]);
+        let mut files = Vec::new();
+        assert!(scanner::collect_files_until_cancelled(dir.path(), Path::new(""), &rules, &AtomicBool::new(true), &mut files));
+        assert!(files.is_empty());
+
+        assert!(!scanner::collect_files_until_cancelled(dir.path(), Path::new(""), &rules, &AtomicBool::new(false), &mut files));
+        assert_eq!(files.len(), 2);
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+use rayon::prelude::*;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::classifier::{Classifier, FileClass};
+use crate::file_reader;
+use crate::ignore_rules::IgnoreRules;
+use crate::scanner::{self, FileScan, MAX_CACHED_FILES, SCAN_POOL};
+use crate::workspace::{Access, FsError, Workspace};
+
+/// Entries in the largest-files and deepest-directories lists
+const TOP_ENTRIES: usize = 10;
+
+// Content hash and line counts per file path, reused while the hash is unchanged
+static STATS_CACHE: LazyLock<Mutex<HashMap<String, (String, LineCounts)>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineCounts {
+    pub code: u64,
+    pub comment: u64,
+    pub blank: u64,
+}
+
+impl LineCounts {
+    fn add(&mut self, other: LineCounts) {
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: usize,
+    pub bytes: u64,
+    #[serde(flatten)]
+    pub lines: LineCounts,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LargeFile {
+    /// Relative to the project root
+    pub path: String,
+    pub size: u64,
+    pub lines: Option<u64>,
+    pub language: Option<String>,
+    pub class: Option<FileClass>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DeepDirectory {
+    /// Relative to the project root
+    pub path: String,
+    pub depth: usize,
+    /// Files directly inside it
+    pub files: usize,
+}
+
+/// Files of one classifier class and their share of the project
+#[derive(Debug, Serialize, Clone)]
+pub struct ClassShare {
+    pub class: FileClass,
+    pub files: usize,
+    pub bytes: u64,
+    /// Fraction of all bytes, 0.0 to 1.0
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectStats {
+    pub root: String,
+    pub files: usize,
+    pub total_bytes: u64,
+    /// Hand-written source only; classified files are counted in `classes`
+    pub languages: Vec<LanguageStats>,
+    pub totals: LineCounts,
+    pub largest_files: Vec<LargeFile>,
+    pub deepest_directories: Vec<DeepDirectory>,
+    pub classes: Vec<ClassShare>,
+    /// The scan stopped at its file limit
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+/// Comment syntax of a language: line comment prefixes and block delimiters
+fn comment_syntax(language: &str) -> (&'static [&'static str], &'static [(&'static str, &'static str)]) {
+    const C_BLOCK: &[(&str, &str)] = &[("/*", "*/")];
+    const HTML_BLOCK: &[(&str, &str)] = &[("<!--", "-->")];
+    match language {
+        "javascript" | "jsx" | "typescript" | "tsx" | "rust" | "c" | "cpp" | "csharp" | "go" | "java" | "swift"
+        | "kotlin" | "scss" => (&["//"], C_BLOCK),
+        "php" => (&["//", "#"], C_BLOCK),
+        "css" => (&[], C_BLOCK),
+        "python" | "ruby" | "bash" | "perl" | "yaml" | "toml" | "makefile" | "dockerfile" | "dotenv" | "gitignore"
+        | "apacheconf" | "cmake" => (&["#"], &[]),
+        "ini" => (&[";", "#"], &[]),
+        "sql" => (&["--"], C_BLOCK),
+        "html" | "xml" | "vue" | "svelte" => (&[], HTML_BLOCK),
+        "blade" => (&[], &[("{{--", "--}}"), ("<!--", "-->")]),
+        "twig" => (&[], &[("{#", "#}"), ("<!--", "-->")]),
+        _ => (&[], &[]),
+    }
+}
+
+/// Code, comment and blank lines of `text`. A line holding any code counts as
+/// code; comment markers inside strings are not recognised.
+pub fn count_lines(text: &str, language: &str) -> LineCounts {
+    let (line_comments, blocks) = comment_syntax(language);
+    let mut counts = LineCounts::default();
+    // End delimiter of the block comment we are inside
+    let mut open_block: Option<&str> = None;
+
+    for line in text.lines() {
+        let mut rest = line.trim();
+        if rest.is_empty() && open_block.is_none() {
+            counts.blank += 1;
+            continue;
+        }
+
+        let mut has_code = false;
+        let mut has_comment = false;
+        loop {
+            if let Some(end) = open_block {
+                has_comment = true;
+                match rest.find(end) {
+                    Some(index) => {
+                        rest = rest[index + end.len()..].trim_start();
+                        open_block = None;
+                    }
+                    None => break,
+                }
+            }
+            if rest.is_empty() {
+                break;
+            }
+            // PHP 8 attributes start with `#[`
+            let attribute = language == "php" && rest.starts_with("#[");
+            if !attribute && line_comments.iter().any(|prefix| rest.starts_with(prefix)) {
+                has_comment = true;
+                break;
+            }
+            match blocks.iter().find(|(start, _)| rest.starts_with(start)) {
+                Some((start, end)) => {
+                    rest = &rest[start.len()..];
+                    open_block = Some(end);
+                }
+                None => {
+                    has_code = true;
+                    break;
+                }
+            }
+        }
+
+        if has_code {
+            counts.code += 1;
+        } else if has_comment {
+            counts.comment += 1;
+        } else {
+            counts.blank += 1;
+        }
+    }
+    counts
+}
+
+fn count_file(path: &Path, language: &str) -> Option<LineCounts> {
+    let bytes = fs::read(path).ok()?;
+    let (_, text) = file_reader::decode_text(&bytes)?;
+    Some(count_lines(&text, language))
+}
+
+fn deepest_directories(files: &[FileScan]) -> Vec<DeepDirectory> {
+    let mut directories: HashMap<&str, usize> = HashMap::new();
+    for file in files {
+        if let Some((dir, _)) = file.relative.rsplit_once('/') {
+            *directories.entry(dir).or_default() += 1;
+        }
+    }
+
+    let mut deepest: Vec<DeepDirectory> = directories
+        .into_iter()
+        .map(|(path, files)| DeepDirectory { path: path.to_string(), depth: path.split('/').count(), files })
+        .collect();
+    deepest.sort_by(|a, b| b.depth.cmp(&a.depth).then_with(|| a.path.cmp(&b.path)));
+    deepest.truncate(TOP_ENTRIES);
+    deepest
+}
+
+/// Size profile of the directory `dir` from a (cached) scan of it; line
+/// counts are reused for files whose content hash did not change
+pub fn collect(rules: &IgnoreRules, classifier: &Classifier, dir: &Path) -> ProjectStats {
+    let started = Instant::now();
+    let scan = scanner::scan(rules, classifier, dir);
+
+    let is_source = |file: &FileScan| !file.binary && !file.classification.as_ref().is_some_and(|c| classifier.skips_in_analysis(c));
+    let previous: HashMap<String, (String, LineCounts)> = {
+        let mut cache = STATS_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        scan.files.iter().filter_map(|f| cache.remove(&f.path).map(|counts| (f.path.clone(), counts))).collect()
+    };
+    let counted: Vec<(&FileScan, LineCounts)> = SCAN_POOL.install(|| {
+        scan.files
+            .par_iter()
+            .filter(|file| is_source(file))
+            .filter_map(|file| {
+                let language = file.language.as_deref().unwrap_or("text");
+                match previous.get(&file.path) {
+                    Some((hash, counts)) if *hash == file.hash => Some((file, *counts)),
+                    _ => count_file(Path::new(&file.path), language).map(|counts| (file, counts)),
+                }
+            })
+            .collect()
+    });
+    {
+        let mut cache = STATS_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        cache.retain(|path, _| !Path::new(path).starts_with(dir));
+        if cache.len() + counted.len() > MAX_CACHED_FILES {
+            cache.clear();
+        }
+        cache.extend(counted.iter().map(|(file, counts)| (file.path.clone(), (file.hash.clone(), *counts))));
+    }
+
+    let mut languages: HashMap<&str, LanguageStats> = HashMap::new();
+    let mut totals = LineCounts::default();
+    for (file, counts) in &counted {
+        let language = file.language.as_deref().unwrap_or("text");
+        let stats = languages.entry(language).or_insert_with(|| LanguageStats {
+            language: language.to_string(),
+            files: 0,
+            bytes: 0,
+            lines: LineCounts::default(),
+        });
+        stats.files += 1;
+        stats.bytes += file.size;
+        stats.lines.add(*counts);
+        totals.add(*counts);
+    }
+    let mut languages: Vec<LanguageStats> = languages.into_values().collect();
+    languages.sort_by(|a, b| b.lines.code.cmp(&a.lines.code).then_with(|| a.language.cmp(&b.language)));
+
+    let mut classes: Vec<ClassShare> = Vec::new();
+    for file in &scan.files {
+        let Some(class) = file.classification.as_ref().map(|c| c.class) else {
+            continue;
+        };
+        match classes.iter_mut().find(|share| share.class == class) {
+            Some(share) => {
+                share.files += 1;
+                share.bytes += file.size;
+            }
+            None => classes.push(ClassShare { class, files: 1, bytes: file.size, share: 0.0 }),
+        }
+    }
+    for share in &mut classes {
+        share.share = if scan.total_bytes == 0 { 0.0 } else { share.bytes as f64 / scan.total_bytes as f64 };
+    }
+    classes.sort_by_key(|share| std::cmp::Reverse(share.bytes));
+
+    let mut largest: Vec<&FileScan> = scan.files.iter().collect();
+    largest.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.relative.cmp(&b.relative)));
+    let largest_files = largest
+        .into_iter()
+        .take(TOP_ENTRIES)
+        .map(|file| LargeFile {
+            path: file.relative.clone(),
+            size: file.size,
+            lines: file.lines,
+            language: file.language.clone(),
+            class: file.classification.as_ref().map(|c| c.class),
+        })
+        .collect();
+
+    ProjectStats {
+        root: scan.root.clone(),
+        files: scan.files.len(),
+        total_bytes: scan.total_bytes,
+        languages,
+        totals,
+        largest_files,
+        deepest_directories: deepest_directories(&scan.files),
+        classes,
+        truncated: scan.truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Size profile of a project for onboarding: files, code/comment/blank lines
+/// per language, the largest files, the deepest directories and how much of
+/// it is generated, vendored or otherwise not hand-written.
+///
+/// Built on the scanner's walk and thread pool; line counts are cached per
+/// file and only recomputed for files whose content changed.
+#[tauri::command]
+pub async fn project_stats(app: AppHandle, project_path: String) -> Result<ProjectStats, FsError> {
+    let dir = Workspace::current(&app).resolve(&project_path, Access::Read)?;
+    if !dir.is_dir() {
+        return Err(FsError::InvalidPath { message: format!("Path is not a directory: {}", project_path), path: project_path });
+    }
+
+    let rules = IgnoreRules::for_path(&app, &dir);
+    let classifier = Classifier::for_path(&app, &dir);
+    let stats = collect(&rules, &classifier, &dir);
+    println!(
+        "📊 Stats for {}: {} files, {} code lines in {} languages ({}ms)",
+        project_path,
+        stats.files,
+        stats.totals.code,
+        stats.languages.len(),
+        stats.duration_ms
+    );
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ClassifierSettings;
+    use crate::test_support::project_dir;
+
+    #[test]
+    fn counts_code_comment_and_blank_lines() {
+        let php = "<?php\n\n// Greets\n/**\n * Docblock\n */\nfunction hi() { /* inline */ return 1; }\n/* a */ $x = 2;\n# hash\n";
+        assert_eq!(count_lines(php, "php"), LineCounts { code: 3, comment: 5, blank: 1 });
+
+        let attributes = "#[Route('/')]\n# note\n#[\\Deprecated]\nfunction x() {}\n";
+        assert_eq!(count_lines(attributes, "php"), LineCounts { code: 3, comment: 1, blank: 0 });
+        assert_eq!(count_lines("#[derive(Debug)]\n", "python"), LineCounts { code: 0, comment: 1, blank: 0 });
+
+        let html = "<div>\n  <!-- note\n  still note -->\n</div>\n";
+        assert_eq!(count_lines(html, "html"), LineCounts { code: 2, comment: 2, blank: 0 });
+    }
+
+    #[test]
+    fn collects_classes_directories_and_reuses_counts() {
+        let dir = project_dir(&[
+            ("src/app.php", "<?php\n// hi\necho 1;\n"),
+            ("src/a/b/c/deep.php", "<?php\n"),
+            ("src/a/b/c/deeper.php", "<?php\n"),
+            ("composer.lock", "{\n}\n"),
+            ("public/app.min.js", &format!("var a=1;{}\n", "x".repeat(2000))),
+        ]);
+        let rules = IgnoreRules::new(dir.path(), &[]);
+        let classifier = Classifier::new(ClassifierSettings::default());
+
+        let stats = collect(&rules, &classifier, dir.path());
+        assert_eq!(stats.files, 5);
+        assert_eq!(stats.languages.len(), 1);
+        assert_eq!(stats.languages[0].language, "php");
+        assert_eq!(stats.totals, LineCounts { code: 4, comment: 1, blank: 0 });
+
+        let classes: Vec<FileClass> = stats.classes.iter().map(|c| c.class).collect();
+        assert_eq!(classes, [FileClass::Minified, FileClass::Lockfile]);
+        let shares: f64 = stats.classes.iter().map(|c| c.share).sum();
+        let classified = stats.classes.iter().map(|c| c.bytes).sum::<u64>() as f64;
+        assert!((shares - classified / stats.total_bytes as f64).abs() < 1e-9);
+
+        assert_eq!(stats.deepest_directories[0].path, "src/a/b/c");
+        assert_eq!((stats.deepest_directories[0].depth, stats.deepest_directories[0].files), (4, 2));
+        assert_eq!(stats.deepest_directories.iter().map(|d| d.path.as_str()).collect::<Vec<_>>(), ["src/a/b/c", "public", "src"]);
+
+        // Counts come from the cache while the hash matches
+        let app = dir.path().join("src/app.php").to_string_lossy().to_string();
+        let fake = LineCounts { code: 100, comment: 0, blank: 0 };
+        STATS_CACHE.lock().unwrap().get_mut(&app).unwrap().1 = fake;
+        assert_eq!(collect(&rules, &classifier, &dir.path().join("src")).totals.code, 102);
+
+        fs::write(&app, "<?php\necho 1;\necho 2;\n").unwrap();
+        assert_eq!(collect(&rules, &classifier, dir.path()).totals.code, 5);
+    }
+}
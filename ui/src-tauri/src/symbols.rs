@@ -0,0 +1,826 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tree_sitter::{Language, Node, Parser};
+
+use crate::classifier::Classifier;
+use crate::commands::detect_language;
+use crate::file_reader;
+use crate::ignore_rules::{self, IgnoreRules};
+use crate::scanner::{self, SCAN_POOL};
+use crate::watcher;
+use crate::workspace::{Access, FsError, Workspace};
+
+/// Larger files are not parsed
+const MAX_PARSE_BYTES: u64 = 2 * 1024 * 1024;
+const DEFAULT_FIND_LIMIT: usize = 50;
+const MAX_FIND_LIMIT: usize = 500;
+/// Changed paths in one batch above which the index is rebuilt instead
+const MAX_INCREMENTAL_CHANGES: usize = 500;
+
+/// Calls that register a route: `Route::get(...)`, `$app->post(...)`, `router.get(...)`
+const ROUTE_VERBS: &[&str] = &["get", "post", "put", "patch", "delete", "options", "head", "any", "all"];
+/// Objects routes are registered on; names ending in one also count (`apiRouter`, `$this->app`)
+const ROUTE_RECEIVERS: &[&str] = &["app", "router", "routes", "route", "server", "fastify", "express"];
+/// Inline handlers that make `<anything>.get('/path', ...)` a route
+const HANDLER_KINDS: &[&str] =
+    &["arrow_function", "function_expression", "function", "anonymous_function", "anonymous_function_creation_expression"];
+
+// Symbol index of each project root
+static INDEXES: LazyLock<Mutex<HashMap<PathBuf, ProjectIndex>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Class,
+    Interface,
+    Trait,
+    Enum,
+    Type,
+    Function,
+    Method,
+    Constant,
+    Route,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Symbol {
+    /// For routes, the URL pattern
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Class, interface, trait or enum the symbol belongs to
+    pub container: Option<String>,
+    /// HTTP method of a route
+    pub detail: Option<String>,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, in characters
+    pub column: usize,
+    pub end_line: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FileOutline {
+    pub path: String,
+    /// `None` when the file's language has no symbol parser
+    pub language: Option<String>,
+    /// In source order
+    pub symbols: Vec<Symbol>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SymbolMatch {
+    pub path: String,
+    /// Relative to the project root
+    pub relative: String,
+    #[serde(flatten)]
+    pub symbol: Symbol,
+}
+
+struct IndexedFile {
+    relative: String,
+    size: u64,
+    modified_at: Option<u64>,
+    symbols: Vec<Symbol>,
+}
+
+/// Decides which files of a project are indexed and parses them; shared with
+/// the builds and updates that run outside the `INDEXES` lock
+struct Indexer {
+    rules: IgnoreRules,
+    classifier: Classifier,
+}
+
+struct ProjectIndex {
+    indexer: Arc<Indexer>,
+    files: HashMap<PathBuf, IndexedFile>,
+    /// Changes were missed (watch overflow, new ignore rules); rebuild before use
+    stale: bool,
+    /// Set while a build runs; changes arriving meanwhile wait here
+    queued: Option<Vec<PathBuf>>,
+    /// A watcher was running when the last build started, so no change since was missed
+    watched: bool,
+}
+
+/// Grammar for a file, by the language `detect_language` gives its name
+fn grammar(language: &str, name: &str) -> Option<Language> {
+    let language = match language {
+        "php" => tree_sitter_php::LANGUAGE_PHP,
+        "javascript" | "jsx" => tree_sitter_javascript::LANGUAGE,
+        "typescript" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+        "tsx" => tree_sitter_typescript::LANGUAGE_TSX,
+        _ => return None,
+    };
+    // .d.ts files only declare what lives elsewhere
+    if name.ends_with(".d.ts") {
+        return None;
+    }
+    Some(language.into())
+}
+
+/// Walks a syntax tree and collects the symbols of PHP, JavaScript and TypeScript
+struct Extractor<'a> {
+    source: &'a str,
+    symbols: Vec<Symbol>,
+}
+
+impl<'a> Extractor<'a> {
+    fn text(&self, node: Node) -> &'a str {
+        node.utf8_text(self.source.as_bytes()).unwrap_or_default()
+    }
+
+    fn push(&mut self, node: Node, name_node: Node, kind: SymbolKind, container: Option<&str>, detail: Option<String>) {
+        let name = self.text(name_node).to_string();
+        if name.is_empty() {
+            return;
+        }
+        let start = name_node.start_position();
+        let line_start = name_node.start_byte() - start.column;
+        self.symbols.push(Symbol {
+            name,
+            kind,
+            container: container.map(str::to_string),
+            detail,
+            line: start.row + 1,
+            column: self.source.get(line_start..name_node.start_byte()).map_or(start.column, |s| s.chars().count()) + 1,
+            end_line: node.end_position().row + 1,
+        });
+    }
+
+    /// Value of a string literal argument, `None` for anything else
+    fn string_value(&self, node: Node) -> Option<String> {
+        let node = if node.kind() == "argument" { node.named_child(0)? } else { node };
+        match node.kind() {
+            "string" | "encapsed_string" | "template_string" => {
+                let text = self.text(node);
+                Some(text.trim_matches(|c| c == '\'' || c == '"' || c == '`').to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// First argument of a call if it is a string literal
+    fn first_string_argument(&self, call: Node) -> Option<String> {
+        let arguments = call.child_by_field_name("arguments")?;
+        let mut cursor = arguments.walk();
+        let first = arguments.named_children(&mut cursor).next()?;
+        self.string_value(first)
+    }
+
+    /// Whether `receiver` names a router or app object
+    fn is_route_receiver(&self, receiver: Node) -> bool {
+        let text = self.text(receiver).to_lowercase();
+        let name = text.rsplit(['.', '>', '$']).next().unwrap_or_default();
+        ROUTE_RECEIVERS.iter().any(|known| name.ends_with(known))
+    }
+
+    /// Record a route registered by `call`. Method calls on other objects
+    /// (`receiver`) only count with a URL path and, unless the object is
+    /// named like a router, an inline handler: `axios.get('/x')` is a request.
+    fn route(&mut self, call: Node, verb_node: Node, receiver: Option<Node>) {
+        let verb = self.text(verb_node).to_lowercase();
+        if !ROUTE_VERBS.contains(&verb.as_str()) {
+            return;
+        }
+        let Some(path) = self.first_string_argument(call) else {
+            return;
+        };
+
+        let arguments = call.child_by_field_name("arguments").unwrap_or(call);
+        let mut cursor = arguments.walk();
+        let mut arguments = arguments.named_children(&mut cursor);
+        let Some(path_node) = arguments.next() else {
+            return;
+        };
+        if let Some(receiver) = receiver {
+            // `$cache->get('key')` and `map.get('key')` are not routes
+            if !path.starts_with('/') {
+                return;
+            }
+            let handler = arguments.next().map(|arg| if arg.kind() == "argument" { arg.named_child(0).unwrap_or(arg) } else { arg });
+            if !self.is_route_receiver(receiver) && !handler.is_some_and(|h| HANDLER_KINDS.contains(&h.kind())) {
+                return;
+            }
+        }
+        let start = path_node.start_position();
+        let line_start = path_node.start_byte() - start.column;
+        self.symbols.push(Symbol {
+            name: path,
+            kind: SymbolKind::Route,
+            container: None,
+            detail: Some(verb.to_uppercase()),
+            line: start.row + 1,
+            column: self.source.get(line_start..path_node.start_byte()).map_or(start.column, |s| s.chars().count()) + 1,
+            end_line: call.end_position().row + 1,
+        });
+    }
+
+    fn children(&mut self, node: Node, container: Option<&str>, top_level: bool) {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        for child in children {
+            self.visit(child, container, top_level);
+        }
+    }
+
+    fn visit(&mut self, node: Node, container: Option<&str>, top_level: bool) {
+        let name = node.child_by_field_name("name");
+        let kind = match node.kind() {
+            "class_declaration" | "abstract_class_declaration" => Some(SymbolKind::Class),
+            "interface_declaration" => Some(SymbolKind::Interface),
+            "trait_declaration" => Some(SymbolKind::Trait),
+            "enum_declaration" => Some(SymbolKind::Enum),
+            _ => None,
+        };
+        if let (Some(kind), Some(name)) = (kind, name) {
+            self.push(node, name, kind, container, None);
+            let owner = self.text(name).to_string();
+            if let Some(body) = node.child_by_field_name("body") {
+                self.children(body, Some(&owner), false);
+            }
+            return;
+        }
+
+        match node.kind() {
+            "type_alias_declaration" => {
+                if let Some(name) = name {
+                    self.push(node, name, SymbolKind::Type, container, None);
+                }
+            }
+            "function_definition" | "function_declaration" | "generator_function_declaration" => {
+                if let Some(name) = name {
+                    self.push(node, name, SymbolKind::Function, container, None);
+                }
+                self.children(node, None, false);
+            }
+            "method_declaration" | "method_definition" | "abstract_method_signature" | "method_signature" => {
+                // Methods of object literals have no owner and are left out
+                if let (Some(name), Some(_)) = (name, container) {
+                    self.push(node, name, SymbolKind::Method, container, None);
+                }
+                self.children(node, None, false);
+            }
+            // PHP `const A = 1;`, at the top level or in a class
+            "const_element" => {
+                if let Some(name) = node.named_child(0).filter(|n| n.kind() == "name") {
+                    self.push(node, name, SymbolKind::Constant, container, None);
+                }
+            }
+            // JS class fields holding functions: `handle = () => {}`
+            "field_definition" | "public_field_definition" => {
+                let value = node.child_by_field_name("value");
+                if let (Some(name), Some(value)) = (node.child_by_field_name("property").or(name), value) {
+                    if matches!(value.kind(), "arrow_function" | "function_expression" | "function") {
+                        self.push(node, name, SymbolKind::Method, container, None);
+                    }
+                    self.children(value, None, false);
+                }
+            }
+            "lexical_declaration" | "variable_declaration" => {
+                let is_const = node.child(0).is_some_and(|keyword| keyword.kind() == "const");
+                let mut cursor = node.walk();
+                let declarators: Vec<Node> = node.named_children(&mut cursor).collect();
+                for declarator in declarators {
+                    let (Some(name), value) = (declarator.child_by_field_name("name"), declarator.child_by_field_name("value")) else {
+                        continue;
+                    };
+                    if name.kind() != "identifier" {
+                        continue;
+                    }
+                    match value.map(|v| v.kind()) {
+                        Some("arrow_function" | "function_expression" | "function" | "generator_function") => {
+                            self.push(declarator, name, SymbolKind::Function, container, None);
+                        }
+                        _ if is_const && top_level => self.push(declarator, name, SymbolKind::Constant, container, None),
+                        _ => {}
+                    }
+                    if let Some(value) = value {
+                        self.visit(value, None, false);
+                    }
+                }
+            }
+            // PHP `define('NAME', value)`
+            "function_call_expression" => {
+                let is_define = node.child_by_field_name("function").is_some_and(|f| self.text(f).eq_ignore_ascii_case("define"));
+                let first = node.child_by_field_name("arguments").and_then(|args| {
+                    let mut cursor = args.walk();
+                    let first = args.named_children(&mut cursor).next();
+                    first
+                });
+                if let (true, Some(first)) = (is_define, first) {
+                    let literal = first.named_child(0).unwrap_or(first);
+                    let content = literal.named_child(0).filter(|n| n.kind() == "string_content");
+                    if let Some(content) = content {
+                        self.push(node, content, SymbolKind::Constant, container, None);
+                    }
+                }
+                self.children(node, container, false);
+            }
+            // Laravel `Route::get('/users', ...)`
+            "scoped_call_expression" => {
+                let is_route = node.child_by_field_name("scope").is_some_and(|scope| self.text(scope) == "Route");
+                if let (true, Some(name)) = (is_route, name) {
+                    self.route(node, name, None);
+                }
+                self.children(node, container, false);
+            }
+            // Slim and friends: `$app->get('/users', ...)`
+            "member_call_expression" => {
+                if let (Some(name), Some(object)) = (name, node.child_by_field_name("object")) {
+                    self.route(node, name, Some(object));
+                }
+                self.children(node, container, false);
+            }
+            // Express: `app.get('/users', ...)`, `router.post('/login', ...)`
+            "call_expression" => {
+                let member = node.child_by_field_name("function").filter(|f| f.kind() == "member_expression");
+                let parts = member.and_then(|f| Some((f.child_by_field_name("property")?, f.child_by_field_name("object")?)));
+                if let Some((property, object)) = parts {
+                    self.route(node, property, Some(object));
+                }
+                self.children(node, container, false);
+            }
+            "program" | "export_statement" | "namespace_definition" | "compound_statement" | "expression_statement"
+            | "const_declaration" => self.children(node, container, top_level),
+            _ => self.children(node, container, false),
+        }
+    }
+}
+
+/// Symbols of a PHP, JavaScript or TypeScript source, in source order
+pub fn extract_symbols(language: Language, source: &str) -> Vec<Symbol> {
+    let mut parser = Parser::new();
+    if parser.set_language(&language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+
+    let mut extractor = Extractor { source, symbols: Vec::new() };
+    extractor.visit(tree.root_node(), None, true);
+    extractor.symbols.sort_by_key(|symbol| (symbol.line, symbol.column));
+    extractor.symbols
+}
+
+/// Read a source file and extract its symbols; `None` if it has no grammar
+fn parse_file(path: &Path) -> Option<(String, Vec<Symbol>)> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let language = detect_language(&name)?;
+    let grammar = grammar(&language, &name)?;
+
+    let bytes = fs::read(path).ok()?;
+    let (_, text) = file_reader::decode_text(&bytes)?;
+    Some((language, extract_symbols(grammar, &text)))
+}
+
+impl Indexer {
+    fn for_path(app: &AppHandle, root: &Path) -> Indexer {
+        Indexer { rules: IgnoreRules::for_path(app, root), classifier: Classifier::for_path(app, root) }
+    }
+
+    /// Whether `path` belongs in the index: a parsable, hand-written source file
+    fn wants(&self, path: &Path, metadata: &fs::Metadata) -> bool {
+        let Ok(relative) = path.strip_prefix(self.rules.root()) else {
+            return false;
+        };
+        let name = relative.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        metadata.is_file()
+            && metadata.len() <= MAX_PARSE_BYTES
+            && detect_language(&name).is_some_and(|language| grammar(&language, &name).is_some())
+            && !self.rules.is_ignored_path(path, false)
+            && !self.classifier.classify_file(path, relative).is_some_and(|c| self.classifier.skips_in_analysis(&c))
+    }
+
+    fn index_file(&self, path: &Path, metadata: &fs::Metadata) -> Option<IndexedFile> {
+        let (_, symbols) = parse_file(path)?;
+        Some(IndexedFile {
+            relative: path.strip_prefix(self.rules.root()).unwrap_or(path).to_string_lossy().replace('\\', "/"),
+            size: metadata.len(),
+            modified_at: scanner::modified_millis(metadata),
+            symbols,
+        })
+    }
+
+    /// Walk the project and parse the files whose size or mtime differ from
+    /// `stamps`. Returns every wanted file and the ones that were parsed.
+    fn build(&self, stamps: &HashMap<PathBuf, (u64, Option<u64>)>) -> (HashSet<PathBuf>, Vec<(PathBuf, IndexedFile)>) {
+        let mut candidates = Vec::new();
+        scanner::collect_files(self.rules.root(), Path::new(""), &self.rules, &mut candidates);
+        candidates.retain(|candidate| self.wants(&candidate.path, &candidate.metadata));
+
+        let parsed = SCAN_POOL.install(|| {
+            candidates
+                .par_iter()
+                .filter(|candidate| {
+                    stamps.get(&candidate.path)
+                        != Some(&(candidate.metadata.len(), scanner::modified_millis(&candidate.metadata)))
+                })
+                .filter_map(|candidate| {
+                    self.index_file(&candidate.path, &candidate.metadata).map(|file| (candidate.path.clone(), file))
+                })
+                .collect()
+        });
+        (candidates.into_iter().map(|c| c.path).collect(), parsed)
+    }
+
+    /// Parse the changed paths; a new or moved-in directory brings all its files
+    fn parse_changes(&self, paths: &[PathBuf]) -> Vec<(PathBuf, IndexedFile)> {
+        let mut candidates = Vec::new();
+        for path in paths {
+            let Ok(metadata) = fs::metadata(path) else {
+                continue;
+            };
+            let Ok(relative) = path.strip_prefix(self.rules.root()) else {
+                continue;
+            };
+            if !metadata.is_dir() {
+                candidates.push(scanner::Candidate { path: path.clone(), relative: relative.to_path_buf(), metadata });
+            } else if !self.rules.is_ignored_path(path, true) {
+                scanner::collect_files(path, relative, &self.rules, &mut candidates);
+            }
+        }
+
+        SCAN_POOL.install(|| {
+            candidates
+                .par_iter()
+                .filter(|candidate| self.wants(&candidate.path, &candidate.metadata))
+                .filter_map(|candidate| {
+                    self.index_file(&candidate.path, &candidate.metadata).map(|file| (candidate.path.clone(), file))
+                })
+                .collect()
+        })
+    }
+}
+
+impl ProjectIndex {
+    fn new(indexer: Indexer) -> ProjectIndex {
+        ProjectIndex { indexer: Arc::new(indexer), files: HashMap::new(), stale: true, queued: None, watched: false }
+    }
+
+    /// Drop the changed paths, with everything below them, then add what was parsed
+    fn apply(&mut self, changed: &[PathBuf], parsed: Vec<(PathBuf, IndexedFile)>) {
+        self.files.retain(|indexed, _| !changed.iter().any(|path| indexed.starts_with(path)));
+        self.files.extend(parsed);
+    }
+}
+
+/// Fold a batch of watcher changes into the indexes of the affected projects.
+/// `overflow` means the watcher lost track and the index must be rebuilt.
+pub fn apply_changes(paths: &[PathBuf], overflow: bool) {
+    let mut updates = Vec::new();
+    {
+        let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+        for (root, index) in indexes.iter_mut() {
+            let changed: Vec<PathBuf> = paths.iter().filter(|path| path.starts_with(root)).cloned().collect();
+            if changed.is_empty() && !overflow {
+                continue;
+            }
+            if overflow || changed.len() > MAX_INCREMENTAL_CHANGES || changed.iter().any(|path| ignore_rules::is_ignore_file(path)) {
+                index.stale = true;
+            } else if let Some(queued) = index.queued.as_mut() {
+                queued.extend(changed);
+            } else {
+                updates.push((root.clone(), index.indexer.clone(), changed));
+            }
+        }
+    }
+
+    // Parse without holding the lock, so lookups in other projects never wait
+    for (root, indexer, changed) in updates {
+        let parsed = indexer.parse_changes(&changed);
+        let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = indexes.get_mut(&root) {
+            match index.queued.as_mut() {
+                // A build started meanwhile and may have listed the old files
+                Some(queued) => queued.extend(changed),
+                None => index.apply(&changed, parsed),
+            }
+        }
+    }
+}
+
+/// Run `f` on the up-to-date index of the project at `root`, building or
+/// refreshing it first when it is missing, stale or not kept current by a watcher
+fn with_index<T>(app: &AppHandle, root: &Path, f: impl FnOnce(&ProjectIndex) -> T) -> T {
+    with_index_from(root, || Indexer::for_path(app, root), f)
+}
+
+/// `with_index` with the indexer made by `load`. The build parses outside the
+/// lock while the old index keeps answering; a second caller meanwhile gets
+/// the old index rather than starting another build.
+fn with_index_from<T>(root: &Path, load: impl Fn() -> Indexer, f: impl FnOnce(&ProjectIndex) -> T) -> T {
+    let build = {
+        let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+        let index = indexes.entry(root.to_path_buf()).or_insert_with(|| ProjectIndex::new(load()));
+        let watched = watcher::is_watched(root);
+        // An index built before the watcher started may have missed changes
+        if index.queued.is_none() && (index.stale || !watched || !index.watched) {
+            if index.stale {
+                // Settings or ignore files may have changed since the last build
+                index.indexer = Arc::new(load());
+            }
+            // Cleared now, so an overflow during the build marks it stale again
+            index.stale = false;
+            index.watched = watched;
+            index.queued = Some(Vec::new());
+            let stamps = index.files.iter().map(|(path, file)| (path.clone(), (file.size, file.modified_at))).collect();
+            Some((index.indexer.clone(), stamps))
+        } else {
+            None
+        }
+    };
+
+    if let Some((indexer, stamps)) = build {
+        let (listed, parsed) = indexer.build(&stamps);
+        let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = indexes.get_mut(root) {
+            index.files.retain(|path, _| listed.contains(path));
+            index.files.extend(parsed);
+        }
+        // Changes that arrived during the build, also parsed outside the lock
+        while let Some(index) = indexes.get_mut(root) {
+            let queued = index.queued.take().unwrap_or_default();
+            if queued.is_empty() {
+                break;
+            }
+            index.queued = Some(Vec::new());
+            drop(indexes);
+            let parsed = indexer.parse_changes(&queued);
+            indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(index) = indexes.get_mut(root) {
+                index.apply(&queued, parsed);
+            }
+        }
+    }
+
+    let indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+    f(&indexes[root])
+}
+
+/// Lower is better; `None` when the symbol does not match
+fn match_rank(symbol: &Symbol, query: &str, container: Option<&str>) -> Option<u8> {
+    if let Some(container) = container {
+        if !symbol.container.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(container)) {
+            return None;
+        }
+    }
+
+    let name = symbol.name.to_lowercase();
+    let lower = query.to_lowercase();
+    if symbol.name == query {
+        Some(0)
+    } else if name == lower {
+        Some(1)
+    } else if name.starts_with(&lower) {
+        Some(2)
+    } else if name.contains(&lower) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// Outline of one PHP, JavaScript or TypeScript file: its classes, functions,
+/// methods, routes and constants with their locations
+#[tauri::command]
+pub async fn file_outline(app: AppHandle, file_path: String) -> Result<FileOutline, FsError> {
+    let real = Workspace::current(&app).resolve(&file_path, Access::Read)?;
+    let metadata = fs::metadata(&real).map_err(|e| FsError::io(&real, "read", e))?;
+    if !metadata.is_file() {
+        return Err(FsError::InvalidPath { message: format!("Path is not a file: {}", file_path), path: file_path });
+    }
+    if metadata.len() > MAX_PARSE_BYTES {
+        return Err(FsError::InvalidPath {
+            message: format!("File is too large to outline ({} bytes): {}", metadata.len(), file_path),
+            path: file_path,
+        });
+    }
+
+    let (language, symbols) = match parse_file(&real) {
+        Some((language, symbols)) => (Some(language), symbols),
+        None => (None, Vec::new()),
+    };
+    Ok(FileOutline { path: file_path, language, symbols })
+}
+
+/// Find symbols by name across a project, best matches first.
+///
+/// Exact names rank above prefixes and substrings. `Class::method`,
+/// `Class.method` and `Class@method` narrow the search to one container; for
+/// routes the name is the URL pattern. The index is built on first use and
+/// then kept current by the directory watcher.
+#[tauri::command]
+pub async fn find_symbol(
+    app: AppHandle,
+    project_path: String,
+    query: String,
+    kind: Option<SymbolKind>,
+    limit: Option<usize>,
+) -> Result<Vec<SymbolMatch>, FsError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(FsError::InvalidPath { message: "Symbol query is empty".to_string(), path: project_path });
+    }
+    let dir = Workspace::current(&app).resolve(&project_path, Access::Read)?;
+    if !dir.is_dir() {
+        return Err(FsError::InvalidPath { message: format!("Path is not a directory: {}", project_path), path: project_path });
+    }
+
+    let (container, name) = match ["::", "@", "."].iter().find_map(|sep| query.split_once(sep)) {
+        Some((container, name)) if kind != Some(SymbolKind::Route) && !query.starts_with('/') => (Some(container), name),
+        _ => (None, query),
+    };
+    let limit = limit.unwrap_or(DEFAULT_FIND_LIMIT).clamp(1, MAX_FIND_LIMIT);
+
+    let mut matches: Vec<(u8, SymbolMatch)> = with_index(&app, &dir, |index| {
+        index
+            .files
+            .iter()
+            .flat_map(|(path, file)| {
+                file.symbols
+                    .iter()
+                    .filter(|symbol| kind.is_none_or(|kind| symbol.kind == kind))
+                    .filter_map(|symbol| {
+                        let rank = match_rank(symbol, name, container)?;
+                        Some((
+                            rank,
+                            SymbolMatch {
+                                path: path.to_string_lossy().to_string(),
+                                relative: file.relative.clone(),
+                                symbol: symbol.clone(),
+                            },
+                        ))
+                    })
+            })
+            .collect()
+    });
+    matches.sort_by(|(rank_a, a), (rank_b, b)| {
+        rank_a
+            .cmp(rank_b)
+            .then_with(|| a.relative.cmp(&b.relative))
+            .then_with(|| a.symbol.line.cmp(&b.symbol.line))
+    });
+    matches.truncate(limit);
+
+    println!("🧭 find_symbol {:?} in {}: {} matches", query, project_path, matches.len());
+    Ok(matches.into_iter().map(|(_, m)| m).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ClassifierSettings;
+    use crate::test_support::project_dir;
+
+    fn outline(language: Language, source: &str) -> Vec<(SymbolKind, String, Option<String>)> {
+        extract_symbols(language, source)
+            .into_iter()
+            .map(|s| (s.kind, s.name, s.container.or(s.detail)))
+            .collect()
+    }
+
+    #[test]
+    fn php_classes_methods_constants_and_routes() {
+        let source = r#"<?php
+const VERSION = '1.0';
+define('APP_ROOT', __DIR__);
+class UserController extends Controller {
+    const PER_PAGE = 20;
+    public function show($id) { return $this->cache->get('user'); }
+}
+function helper() {}
+Route::get('/users/{id}', [UserController::class, 'show']);
+"#;
+        use SymbolKind::*;
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(
+            outline(tree_sitter_php::LANGUAGE_PHP.into(), source),
+            [
+                (Constant, "VERSION".to_string(), None),
+                (Constant, "APP_ROOT".to_string(), None),
+                (Class, "UserController".to_string(), None),
+                (Constant, "PER_PAGE".to_string(), some("UserController")),
+                (Method, "show".to_string(), some("UserController")),
+                (Function, "helper".to_string(), None),
+                (Route, "/users/{id}".to_string(), some("GET")),
+            ]
+        );
+    }
+
+    #[test]
+    fn javascript_and_typescript_symbols() {
+        let js = "export class Api {\n  fetch() {}\n  handle = () => {};\n}\nexport const LIMIT = 5;\nconst load = async () => {};\nfunction main() { const local = 1; }\nrouter.post('/login', load);\nmap.get('key');\naxios.get('/x');\nhttp.get('/y', { params });\napiRouter.get('/users', list);\nfoo.get('/health', (req, res) => res.send());\n";
+        use SymbolKind::*;
+        let names: Vec<(SymbolKind, String)> =
+            outline(tree_sitter_javascript::LANGUAGE.into(), js).into_iter().map(|(k, n, _)| (k, n)).collect();
+        assert_eq!(
+            names,
+            [
+                (Class, "Api".to_string()),
+                (Method, "fetch".to_string()),
+                (Method, "handle".to_string()),
+                (Constant, "LIMIT".to_string()),
+                (Function, "load".to_string()),
+                (Function, "main".to_string()),
+                (Route, "/login".to_string()),
+                (Route, "/users".to_string()),
+                (Route, "/health".to_string()),
+            ]
+        );
+
+        let ts = "interface Props { a(): void }\ntype Id = string;\nenum Color { Red }\nabstract class Base { abstract run(): void; }\n";
+        let kinds: Vec<SymbolKind> = outline(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(), ts).into_iter().map(|(k, _, _)| k).collect();
+        assert_eq!(kinds, [Interface, Method, Type, Enum, Class, Method]);
+    }
+
+    fn indexer(root: &Path) -> Indexer {
+        Indexer {
+            rules: IgnoreRules::new(root, &["vendor/".to_string()]),
+            classifier: Classifier::new(ClassifierSettings::default()),
+        }
+    }
+
+    fn indexed(root: &Path) -> Vec<String> {
+        let indexes = INDEXES.lock().unwrap();
+        let mut files: Vec<String> = indexes[root].files.values().map(|f| f.relative.clone()).collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn changes_update_files_and_moved_directories() {
+        let dir = project_dir(&[("a.php", "<?php\nclass A {}\n"), ("notes.txt", "")]);
+        let outside = project_dir(&[("lib/c.php", "<?php\nfunction c() {}\n"), ("vendor/v.php", "<?php\nclass V {}\n")]);
+        let root = dir.path();
+        with_index_from(root, || indexer(root), |_| ());
+        assert_eq!(indexed(root), ["a.php"]);
+
+        fs::write(root.join("b.php"), "<?php\nclass B {}\n").unwrap();
+        apply_changes(&[root.join("b.php")], false);
+        assert_eq!(indexed(root), ["a.php", "b.php"]);
+
+        // Directories moved in are walked; ignored ones are not
+        fs::rename(outside.path().join("lib"), root.join("lib")).unwrap();
+        fs::rename(outside.path().join("vendor"), root.join("vendor")).unwrap();
+        apply_changes(&[root.join("lib"), root.join("vendor")], false);
+        assert_eq!(indexed(root), ["a.php", "b.php", "lib/c.php"]);
+
+        fs::remove_dir_all(root.join("lib")).unwrap();
+        fs::write(root.join("a.php"), "<?php\nclass A2 {}\n").unwrap();
+        apply_changes(&[root.join("lib"), root.join("a.php")], false);
+        assert_eq!(indexed(root), ["a.php", "b.php"]);
+        let indexes = INDEXES.lock().unwrap();
+        assert_eq!(indexes[root].files[&root.join("a.php")].symbols[0].name, "A2");
+    }
+
+    #[test]
+    fn changes_during_a_build_are_queued() {
+        let dir = project_dir(&[("a.php", "<?php\nclass A {}\n")]);
+        let root = dir.path();
+        with_index_from(root, || indexer(root), |_| ());
+        INDEXES.lock().unwrap().get_mut(root).unwrap().queued = Some(Vec::new());
+
+        fs::write(root.join("b.php"), "<?php\nclass B {}\n").unwrap();
+        apply_changes(&[root.join("b.php")], false);
+        assert_eq!(indexed(root), ["a.php"]);
+        {
+            let mut indexes = INDEXES.lock().unwrap();
+            let index = indexes.get_mut(root).unwrap();
+            assert_eq!(index.queued.as_deref(), Some(&[root.join("b.php")][..]));
+            index.queued = None;
+        }
+
+        apply_changes(&[], true);
+        assert!(INDEXES.lock().unwrap()[root].stale);
+        let files = with_index_from(root, || indexer(root), |index| index.files.len());
+        assert_eq!(files, 2);
+        assert!(!INDEXES.lock().unwrap()[root].stale);
+    }
+
+    #[test]
+    fn exact_names_rank_first() {
+        let symbol = |name: &str, container: Option<&str>| Symbol {
+            name: name.to_string(),
+            kind: SymbolKind::Method,
+            container: container.map(str::to_string),
+            detail: None,
+            line: 1,
+            column: 1,
+            end_line: 1,
+        };
+        let show = symbol("showUser", Some("UserController"));
+        assert_eq!(match_rank(&show, "showUser", None), Some(0));
+        assert_eq!(match_rank(&show, "showuser", None), Some(1));
+        assert_eq!(match_rank(&show, "show", None), Some(2));
+        assert_eq!(match_rank(&show, "User", None), Some(3));
+        assert_eq!(match_rank(&show, "delete", None), None);
+        assert_eq!(match_rank(&show, "show", Some("usercontroller")), Some(2));
+        assert_eq!(match_rank(&show, "show", Some("Post")), None);
+        assert_eq!(match_rank(&symbol("show", None), "show", Some("User")), None);
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+use regex::Regex;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::classifier::Classifier;
+use crate::ignore_rules::IgnoreRules;
+use crate::scanner::{self, FileScan};
+use crate::workspace::{Access, FsError, Workspace};
+
+/// Languages that say nothing about what a project is written in
+const NON_CODE_LANGUAGES: &[&str] = &[
+    "text", "markdown", "json", "yaml", "toml", "xml", "ini", "dotenv", "gitignore", "apacheconf",
+];
+
+static GEM_LINE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"^\s*gem\s+['"]([^'"]+)['"]"#).unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Ecosystem {
+    Composer,
+    Npm,
+    Pip,
+    Cargo,
+    Gem,
+}
+
+impl Ecosystem {
+    fn manifest(self) -> &'static str {
+        match self {
+            Ecosystem::Composer => "composer.json",
+            Ecosystem::Npm => "package.json",
+            Ecosystem::Pip => "requirements.txt",
+            Ecosystem::Cargo => "Cargo.toml",
+            Ecosystem::Gem => "Gemfile",
+        }
+    }
+
+    /// Dependency names declared by a manifest of this ecosystem
+    fn parse(self, content: &str) -> Vec<String> {
+        match self {
+            Ecosystem::Composer | Ecosystem::Npm => {
+                let sections: &[&str] = if self == Ecosystem::Composer {
+                    &["require", "require-dev"]
+                } else {
+                    &["dependencies", "devDependencies", "peerDependencies"]
+                };
+                let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+                    return Vec::new();
+                };
+                sections
+                    .iter()
+                    .filter_map(|section| json.get(section)?.as_object())
+                    .flat_map(|deps| deps.keys().map(|name| name.to_lowercase()))
+                    .collect()
+            }
+            Ecosystem::Pip => content
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or("").trim())
+                .filter(|line| !line.is_empty() && !line.starts_with('-'))
+                .filter_map(|line| line.split(|c: char| "<>=!~;[ ".contains(c)).next())
+                .map(|name| name.to_lowercase().replace('_', "-"))
+                .collect(),
+            Ecosystem::Cargo => {
+                let mut deps = Vec::new();
+                let mut in_dependencies = false;
+                for line in content.lines().map(str::trim) {
+                    if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                        // [dependencies], [target.'cfg(..)'.dev-dependencies], [dependencies.serde]
+                        let (table, name) = match table.rsplit_once('.') {
+                            Some((parent, name)) if parent.ends_with("dependencies") => (parent, Some(name)),
+                            _ => (table, None),
+                        };
+                        in_dependencies = table.ends_with("dependencies") && name.is_none();
+                        if let Some(name) = name.filter(|_| table.ends_with("dependencies")) {
+                            deps.push(name.trim_matches('"').to_string());
+                        }
+                    } else if in_dependencies {
+                        if let Some((name, _)) = line.split_once('=') {
+                            deps.push(name.trim().trim_matches('"').to_string());
+                        }
+                    }
+                }
+                deps
+            }
+            Ecosystem::Gem => content
+                .lines()
+                .filter_map(|line| GEM_LINE.captures(line).map(|c| c[1].to_lowercase()))
+                .collect(),
+        }
+    }
+}
+
+/// A framework, recognised by a dependency or a marker file at the root.
+/// Listed in priority order: the first detected one is the project's framework.
+struct FrameworkRule {
+    id: &'static str,
+    name: &'static str,
+    dependencies: &'static [(Ecosystem, &'static str)],
+    markers: &'static [&'static str],
+}
+
+const FRAMEWORKS: &[FrameworkRule] = &[
+    FrameworkRule { id: "laravel", name: "Laravel", dependencies: &[(Ecosystem::Composer, "laravel/framework")], markers: &["artisan"] },
+    FrameworkRule { id: "symfony", name: "Symfony", dependencies: &[(Ecosystem::Composer, "symfony/framework-bundle")], markers: &["bin/console", "symfony.lock"] },
+    FrameworkRule {
+        id: "wordpress",
+        name: "WordPress",
+        dependencies: &[(Ecosystem::Composer, "johnpbloch/wordpress"), (Ecosystem::Composer, "roots/wordpress")],
+        markers: &["wp-config.php", "wp-config-sample.php", "wp-includes/version.php"],
+    },
+    FrameworkRule { id: "django", name: "Django", dependencies: &[(Ecosystem::Pip, "django")], markers: &["manage.py"] },
+    FrameworkRule { id: "rails", name: "Ruby on Rails", dependencies: &[(Ecosystem::Gem, "rails")], markers: &["bin/rails"] },
+    FrameworkRule { id: "nextjs", name: "Next.js", dependencies: &[(Ecosystem::Npm, "next")], markers: &["next.config.js", "next.config.mjs", "next.config.ts"] },
+    FrameworkRule { id: "nuxt", name: "Nuxt", dependencies: &[(Ecosystem::Npm, "nuxt")], markers: &["nuxt.config.js", "nuxt.config.ts"] },
+    FrameworkRule { id: "angular", name: "Angular", dependencies: &[(Ecosystem::Npm, "@angular/core")], markers: &["angular.json"] },
+    FrameworkRule { id: "tauri", name: "Tauri", dependencies: &[(Ecosystem::Cargo, "tauri"), (Ecosystem::Npm, "@tauri-apps/api")], markers: &[] },
+    FrameworkRule { id: "flask", name: "Flask", dependencies: &[(Ecosystem::Pip, "flask")], markers: &[] },
+    FrameworkRule { id: "fastapi", name: "FastAPI", dependencies: &[(Ecosystem::Pip, "fastapi")], markers: &[] },
+    FrameworkRule { id: "express", name: "Express", dependencies: &[(Ecosystem::Npm, "express")], markers: &[] },
+    FrameworkRule { id: "react", name: "React", dependencies: &[(Ecosystem::Npm, "react")], markers: &[] },
+    FrameworkRule { id: "vue", name: "Vue.js", dependencies: &[(Ecosystem::Npm, "vue")], markers: &[] },
+    FrameworkRule { id: "svelte", name: "Svelte", dependencies: &[(Ecosystem::Npm, "svelte")], markers: &[] },
+    FrameworkRule { id: "actix", name: "Actix Web", dependencies: &[(Ecosystem::Cargo, "actix-web")], markers: &[] },
+    FrameworkRule { id: "axum", name: "Axum", dependencies: &[(Ecosystem::Cargo, "axum")], markers: &[] },
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LanguageShare {
+    pub language: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// Fraction of all source bytes, 0.0 to 1.0
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DetectedFramework {
+    /// Matches the values of the `general.framework` setting where one exists
+    pub id: String,
+    pub name: String,
+    /// What gave it away, e.g. "composer.json requires laravel/framework"
+    pub evidence: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TechStack {
+    pub root: String,
+    /// Source languages by byte share, largest first
+    pub languages: Vec<LanguageShare>,
+    pub primary_language: Option<String>,
+    /// In priority order; the first one is `framework`
+    pub frameworks: Vec<DetectedFramework>,
+    pub framework: Option<String>,
+    /// Manifests that were read, relative to the root
+    pub manifests: Vec<String>,
+}
+
+/// Languages counted together for the byte shares
+fn language_family(language: &str) -> &str {
+    match language {
+        "jsx" => "javascript",
+        "tsx" => "typescript",
+        "blade" => "php",
+        other => other,
+    }
+}
+
+/// Byte shares of hand-written source files; files the classifier leaves
+/// out of analysis are not counted
+fn language_shares(files: &[FileScan], classifier: &Classifier) -> Vec<LanguageShare> {
+    let mut totals: HashMap<&str, (usize, u64)> = HashMap::new();
+    let counted = |f: &&FileScan| !f.binary && !f.classification.as_ref().is_some_and(|c| classifier.skips_in_analysis(c));
+    for file in files.iter().filter(counted) {
+        let Some(language) = file.language.as_deref().map(language_family) else {
+            continue;
+        };
+        if NON_CODE_LANGUAGES.contains(&language) {
+            continue;
+        }
+        let total = totals.entry(language).or_default();
+        total.0 += 1;
+        total.1 += file.size;
+    }
+
+    let all_bytes: u64 = totals.values().map(|(_, bytes)| bytes).sum();
+    let mut shares: Vec<LanguageShare> = totals
+        .into_iter()
+        .map(|(language, (files, bytes))| LanguageShare {
+            language: language.to_string(),
+            files,
+            bytes,
+            share: if all_bytes == 0 { 0.0 } else { bytes as f64 / all_bytes as f64 },
+        })
+        .collect();
+    shares.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.language.cmp(&b.language)));
+    shares
+}
+
+/// Detect languages and frameworks of the project at `dir`, using the files
+/// of a scan of it
+pub fn detect(dir: &Path, files: &[FileScan], classifier: &Classifier) -> TechStack {
+    // Manifests and markers count at the root and one level below (ui/, app/, ...)
+    let shallow: BTreeSet<&str> = files
+        .iter()
+        .map(|f| f.relative.as_str())
+        .filter(|relative| relative.matches('/').count() <= 1)
+        .collect();
+
+    let ecosystems = [Ecosystem::Composer, Ecosystem::Npm, Ecosystem::Pip, Ecosystem::Cargo, Ecosystem::Gem];
+    let mut manifests = Vec::new();
+    let mut dependencies: Vec<(Ecosystem, String, String)> = Vec::new();
+    for relative in &shallow {
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        let Some(ecosystem) = ecosystems.iter().copied().find(|e| e.manifest() == name) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(dir.join(relative)) else {
+            continue;
+        };
+        manifests.push(relative.to_string());
+        for dependency in ecosystem.parse(&content) {
+            dependencies.push((ecosystem, dependency, relative.to_string()));
+        }
+    }
+
+    let frameworks: Vec<DetectedFramework> = FRAMEWORKS
+        .iter()
+        .filter_map(|rule| {
+            let mut evidence: Vec<String> = dependencies
+                .iter()
+                .filter(|(ecosystem, name, _)| rule.dependencies.iter().any(|(e, dep)| e == ecosystem && dep == name))
+                .map(|(_, name, manifest)| format!("{} requires {}", manifest, name))
+                .collect();
+            evidence.extend(rule.markers.iter().filter(|m| shallow.contains(**m)).map(|m| format!("{} found", m)));
+            (!evidence.is_empty()).then(|| DetectedFramework {
+                id: rule.id.to_string(),
+                name: rule.name.to_string(),
+                evidence,
+            })
+        })
+        .collect();
+
+    let languages = language_shares(files, classifier);
+    TechStack {
+        root: dir.to_string_lossy().to_string(),
+        primary_language: languages.first().map(|l| l.language.clone()),
+        languages,
+        framework: frameworks.first().map(|f| f.id.clone()),
+        frameworks,
+        manifests,
+    }
+}
+
+/// Detect a project's languages (by byte share of source files) and its
+/// frameworks (from composer.json, package.json, requirements.txt,
+/// Cargo.toml and Gemfile, plus marker files such as Laravel's `artisan`).
+/// Backs the `autoDetectTechStack`, `primaryLanguage` and `framework` settings.
+#[tauri::command]
+pub async fn detect_tech_stack(app: AppHandle, project_path: String) -> Result<TechStack, FsError> {
+    let dir = Workspace::current(&app).resolve(&project_path, Access::Read)?;
+    if !dir.is_dir() {
+        return Err(FsError::InvalidPath { message: format!("Path is not a directory: {}", project_path), path: project_path });
+    }
+
+    let rules = IgnoreRules::for_path(&app, &dir);
+    let classifier = Classifier::for_path(&app, &dir);
+    let scan = scanner::scan(&rules, &classifier, &dir);
+    let stack = detect(&dir, &scan.files, &classifier);
+    println!(
+        "🧭 Tech stack of {}: {} / {}",
+        project_path,
+        stack.primary_language.as_deref().unwrap_or("unknown"),
+        stack.framework.as_deref().unwrap_or("no framework")
+    );
+    Ok(stack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ClassifierSettings;
+    use crate::test_support::project_dir;
+    use tempfile::TempDir;
+
+    fn project(files: &[(&str, &str)]) -> (TempDir, TechStack) {
+        let dir = project_dir(files);
+        let rules = IgnoreRules::new(dir.path(), &[]);
+        let classifier = Classifier::new(ClassifierSettings::default());
+        let scan = scanner::scan(&rules, &classifier, dir.path());
+        let stack = detect(dir.path(), &scan.files, &classifier);
+        (dir, stack)
+    }
+
+    #[test]
+    fn laravel_from_manifest_and_marker() {
+        let (_dir, stack) = project(&[
+            ("composer.json", r#"{"require": {"php": "^8.2", "laravel/framework": "^11.0"}}"#),
+            ("artisan", "#!/usr/bin/env php\n<?php\nrequire __DIR__.'/vendor/autoload.php';\n"),
+            ("app/User.php", "<?php\n\nclass User {}\n"),
+            ("resources/js/app.js", "console.log('hi');\n"),
+            ("resources/views/home.blade.php", "<h1>{{ $title }}</h1>\n"),
+        ]);
+
+        assert_eq!(stack.framework.as_deref(), Some("laravel"));
+        assert_eq!(stack.frameworks[0].evidence, ["composer.json requires laravel/framework", "artisan found"]);
+        assert_eq!(stack.primary_language.as_deref(), Some("php"));
+        assert_eq!(stack.manifests, ["composer.json"]);
+    }
+
+    #[test]
+    fn parses_requirements_and_cargo_dependencies() {
+        assert_eq!(
+            Ecosystem::Pip.parse("Django>=4.2  # web\n-r base.txt\nfastapi[all]==0.1\n"),
+            ["django", "fastapi"]
+        );
+        assert_eq!(
+            Ecosystem::Cargo.parse("[package]\nname = \"x\"\n[dependencies]\ntauri = \"2\"\nserde = { version = \"1\" }\n[dependencies.axum]\nversion = \"0.7\"\n"),
+            ["tauri", "serde", "axum"]
+        );
+    }
+}
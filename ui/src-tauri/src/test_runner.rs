@@ -0,0 +1,518 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::TestResult;
+use crate::history;
+use crate::job_queue;
+use crate::limits::{self, ResourceLimit, RunLimits};
+use crate::report::TestReport;
+
+/// Emitted for every line a test process writes to stdout or stderr
+pub const OUTPUT_EVENT: &str = "test-run-output";
+/// Emitted once per run, after the process has exited
+pub const COMPLETE_EVENT: &str = "test-run-complete";
+
+/// How long a cancelled or timed-out run gets to exit after SIGTERM before SIGKILL
+const CANCEL_GRACE: Duration = Duration::from_secs(3);
+const KILL_WAIT: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Finished runs kept in the registry for `list_runs` / `get_run_status`
+const MAX_FINISHED_RUNS: usize = 100;
+
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// Every spawned test process, keyed by run id
+static RUNS: LazyLock<Mutex<HashMap<String, RunInfo>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    Queued,
+    Running,
+    Cancelling,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl RunState {
+    pub fn is_finished(self) -> bool {
+        matches!(self, RunState::Succeeded | RunState::Failed | RunState::Cancelled)
+    }
+}
+
+/// What a run is testing, recorded alongside its process
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunMeta {
+    pub test_type: String,
+    pub project_path: String,
+    /// Remote host for runs executed over SSH
+    pub host: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunInfo {
+    pub run_id: String,
+    pub meta: RunMeta,
+    pub state: RunState,
+    /// PID of the test process, which also leads its process group
+    pub pid: Option<u32>,
+    pub queued_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub exit_code: Option<i32>,
+    /// Timeout or resource limit that stopped the run
+    pub limit_exceeded: Option<ResourceLimit>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RunOutputEvent {
+    pub run_id: String,
+    pub stream: OutputStream,
+    /// Monotonic across both streams of a run, so the UI can restore ordering
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub line: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RunCompleteEvent {
+    pub run_id: String,
+    pub state: RunState,
+    pub exit_code: Option<i32>,
+    pub result: TestResult,
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn next_run_id() -> String {
+    format!("run-{}-{}", now_millis(), RUN_COUNTER.fetch_add(1, Ordering::SeqCst))
+}
+
+/// Register a run that has not started yet and return its id
+pub fn reserve_run(meta: RunMeta) -> String {
+    let run_id = next_run_id();
+    register_run(RunInfo {
+        run_id: run_id.clone(),
+        meta,
+        state: RunState::Queued,
+        pid: None,
+        queued_at: now_millis(),
+        started_at: None,
+        finished_at: None,
+        exit_code: None,
+        limit_exceeded: None,
+    });
+    run_id
+}
+
+/// Spawn `command` for a reserved run in its own process group and stream its
+/// output as Tauri events. The process is killed once it outlives
+/// `limits.timeout_secs`; the remaining limits are applied as rlimits on Linux.
+///
+/// Returns as soon as the process has started. `on_finish` is called exactly
+/// once with the `COMPLETE_EVENT` payload, including when the spawn fails.
+pub fn start_run<F>(app: AppHandle, run_id: &str, mut command: Command, limits: RunLimits, on_finish: F) -> Result<(), String>
+where
+    F: FnOnce(RunCompleteEvent) + Send + 'static,
+{
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // Own process group, so cancelling also reaches anything the script spawns
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    limits.apply(&mut command);
+
+    // Cancelled between leaving the queue and getting here
+    if run_info(run_id).is_some_and(|r| r.state == RunState::Cancelling) {
+        on_finish(finish_unstarted(&app, run_id, RunState::Cancelled, "Run cancelled before it started".to_string()));
+        return Ok(());
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            let error = format!("Failed to execute test: {}", e);
+            on_finish(finish_unstarted(&app, run_id, RunState::Failed, error.clone()));
+            return Err(error);
+        }
+    };
+
+    let pid = child.id();
+    println!("▶️  Started run {} (PID: {})", run_id, pid);
+
+    let mut cancel_requested = false;
+    if let Ok(mut runs) = RUNS.lock() {
+        if let Some(run) = runs.get_mut(run_id) {
+            run.pid = Some(pid);
+            run.started_at = Some(now_millis());
+            match run.state {
+                RunState::Queued => run.state = RunState::Running,
+                RunState::Cancelling => cancel_requested = true,
+                _ => {}
+            }
+        }
+    }
+    if cancel_requested {
+        signal_run(pid, false);
+    }
+
+    let watchdog = limits
+        .timeout_secs
+        .map(|secs| spawn_watchdog(run_id.to_string(), pid, Duration::from_secs(secs)));
+
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let seq = Arc::new(AtomicU64::new(0));
+    let id = run_id.to_string();
+
+    thread::spawn(move || {
+        let stdout_reader = stdout.map(|s| spawn_reader(app.clone(), id.clone(), OutputStream::Stdout, s, seq.clone()));
+        let stderr_reader = stderr.map(|s| spawn_reader(app.clone(), id.clone(), OutputStream::Stderr, s, seq.clone()));
+
+        let (output, line_times) = stdout_reader.and_then(|h| h.join().ok()).unwrap_or_default();
+        let (errors, _) = stderr_reader.and_then(|h| h.join().ok()).unwrap_or_default();
+
+        let (status, wait_error) = match child.wait() {
+            Ok(status) => (Some(status), None),
+            Err(e) => (None, Some(format!("Failed to wait for test process: {}", e))),
+        };
+        // Stops the watchdog
+        drop(watchdog);
+
+        let success = status.is_some_and(|s| s.success());
+        let exit_code = status.and_then(|s| s.code());
+        let detected = limits::detect(&limits, status, &output, &errors);
+        let (state, limit_exceeded) = finish_run(&id, success, exit_code, detected);
+
+        let error = if state == RunState::Cancelled {
+            Some(format!("Run cancelled\n{}", errors).trim_end().to_string())
+        } else if let Some(limit) = limit_exceeded {
+            Some(format!("{}\n{}", limits.describe(limit), errors).trim_end().to_string())
+        } else {
+            match (wait_error, errors.is_empty()) {
+                (Some(e), _) => Some(e),
+                (None, true) => None,
+                (None, false) => Some(errors),
+            }
+        };
+
+        let report = TestReport::parse_timed(line_times.into_iter().zip(output.lines()));
+
+        println!("✅ Run {} finished as {:?} (exit code: {:?}, limit: {:?}, {} bytes of output)", id, state, exit_code, limit_exceeded, output.len());
+
+        let event = RunCompleteEvent {
+            run_id: id,
+            state,
+            exit_code,
+            result: TestResult {
+                success: state == RunState::Succeeded,
+                output,
+                error,
+                report: Some(report),
+                limit_exceeded,
+            },
+        };
+        if let Some(info) = run_info(&event.run_id) {
+            history::record(&app, &info, &event.result);
+        }
+        let _ = app.emit(COMPLETE_EVENT, event.clone());
+        on_finish(event);
+    });
+
+    Ok(())
+}
+
+/// Close a run that never got a process and announce it like any other completion
+pub fn finish_unstarted(app: &AppHandle, run_id: &str, state: RunState, error: String) -> RunCompleteEvent {
+    if let Ok(mut runs) = RUNS.lock() {
+        if let Some(run) = runs.get_mut(run_id) {
+            run.state = state;
+            run.finished_at = Some(now_millis());
+        }
+    }
+
+    println!("⏹️  Run {} ended before starting: {}", run_id, error);
+
+    let event = RunCompleteEvent {
+        run_id: run_id.to_string(),
+        state,
+        exit_code: None,
+        result: TestResult {
+            success: false,
+            output: String::new(),
+            error: Some(error),
+            report: None,
+            limit_exceeded: None,
+        },
+    };
+    if let Some(info) = run_info(run_id) {
+        history::record(app, &info, &event.result);
+    }
+    let _ = app.emit(COMPLETE_EVENT, event.clone());
+    event
+}
+
+/// Kill the run's process group if it is still running after `timeout`.
+///
+/// The watchdog exits quietly as soon as the returned sender is dropped.
+fn spawn_watchdog(run_id: String, pid: u32, timeout: Duration) -> mpsc::Sender<()> {
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+
+    thread::spawn(move || {
+        if done_rx.recv_timeout(timeout) != Err(RecvTimeoutError::Timeout) {
+            return;
+        }
+
+        // Leave runs that are already being cancelled to cancel_run
+        let timed_out = match RUNS.lock() {
+            Ok(mut runs) => match runs.get_mut(&run_id) {
+                Some(run) if run.state == RunState::Running => {
+                    run.limit_exceeded = Some(ResourceLimit::Timeout);
+                    true
+                }
+                _ => false,
+            },
+            Err(_) => false,
+        };
+        if !timed_out {
+            return;
+        }
+
+        println!("⏱️ Run {} exceeded its {}s time limit, sending SIGTERM", run_id, timeout.as_secs());
+        signal_run(pid, false);
+
+        if done_rx.recv_timeout(CANCEL_GRACE) == Err(RecvTimeoutError::Timeout) {
+            println!("⚠️ Run {} ignored SIGTERM, sending SIGKILL", run_id);
+            signal_run(pid, true);
+        }
+    });
+
+    done_tx
+}
+
+/// Forward each line of `source` as an output event.
+///
+/// Returns everything read plus the receive time of each line.
+fn spawn_reader<R: Read + Send + 'static>(
+    app: AppHandle,
+    run_id: String,
+    stream: OutputStream,
+    source: R,
+    seq: Arc<AtomicU64>,
+) -> thread::JoinHandle<(String, Vec<u64>)> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(source);
+        let mut collected = String::new();
+        let mut line_times = Vec::new();
+        let mut buffer = Vec::new();
+
+        loop {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let text = String::from_utf8_lossy(&buffer);
+                    collected.push_str(&text);
+
+                    let timestamp = now_millis();
+                    line_times.push(timestamp);
+
+                    let line = text.trim_end_matches(['\n', '\r']).to_string();
+                    let _ = app.emit(OUTPUT_EVENT, RunOutputEvent {
+                        run_id: run_id.clone(),
+                        stream,
+                        seq: seq.fetch_add(1, Ordering::SeqCst),
+                        timestamp,
+                        line,
+                    });
+                }
+            }
+        }
+
+        (collected, line_times)
+    })
+}
+
+fn register_run(info: RunInfo) {
+    if let Ok(mut runs) = RUNS.lock() {
+        // Drop the oldest finished runs so the registry stays bounded
+        let mut finished: Vec<(u64, String)> = runs
+            .values()
+            .filter(|r| r.state.is_finished())
+            .map(|r| (r.queued_at, r.run_id.clone()))
+            .collect();
+        if finished.len() >= MAX_FINISHED_RUNS {
+            finished.sort();
+            for (_, run_id) in finished.iter().take(finished.len() + 1 - MAX_FINISHED_RUNS) {
+                runs.remove(run_id);
+            }
+        }
+
+        runs.insert(info.run_id.clone(), info);
+    }
+}
+
+/// Record the exit of a run and return its final state and the limit that
+/// stopped it, if any. A timeout noted by the watchdog wins over `detected`.
+fn finish_run(run_id: &str, success: bool, exit_code: Option<i32>, detected: Option<ResourceLimit>) -> (RunState, Option<ResourceLimit>) {
+    let exited = if success { RunState::Succeeded } else { RunState::Failed };
+
+    let Ok(mut runs) = RUNS.lock() else { return (exited, detected) };
+    let Some(run) = runs.get_mut(run_id) else { return (exited, detected) };
+
+    if run.state == RunState::Cancelling {
+        run.state = RunState::Cancelled;
+    } else {
+        run.limit_exceeded = run.limit_exceeded.or(detected);
+        run.state = if run.limit_exceeded.is_some() { RunState::Failed } else { exited };
+    }
+    run.finished_at = Some(now_millis());
+    run.exit_code = exit_code;
+    (run.state, run.limit_exceeded)
+}
+
+fn run_info(run_id: &str) -> Option<RunInfo> {
+    RUNS.lock().ok().and_then(|runs| runs.get(run_id).cloned())
+}
+
+/// Send a termination signal to the whole process group of a run
+fn signal_run(pid: u32, force: bool) {
+    #[cfg(unix)]
+    {
+        // A negative PID addresses the process group created at spawn
+        let _ = Command::new("kill")
+            .arg(if force { "-9" } else { "-15" })
+            .arg("--")
+            .arg(format!("-{}", pid))
+            .output();
+    }
+
+    #[cfg(windows)]
+    {
+        let mut taskkill = Command::new("taskkill");
+        taskkill.arg("/T").arg("/PID").arg(pid.to_string());
+        if force {
+            taskkill.arg("/F");
+        }
+        let _ = taskkill.output();
+    }
+}
+
+async fn wait_until_finished(run_id: &str, timeout: Duration) -> Option<RunInfo> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let info = run_info(run_id)?;
+        if info.state.is_finished() || tokio::time::Instant::now() >= deadline {
+            return Some(info);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Kill every run that is still active (used on window close)
+pub fn kill_all_runs() {
+    job_queue::clear();
+
+    let pids: Vec<u32> = match RUNS.lock() {
+        Ok(mut runs) => runs
+            .values_mut()
+            .filter(|r| !r.state.is_finished())
+            .filter_map(|r| {
+                r.state = RunState::Cancelling;
+                r.pid
+            })
+            .collect(),
+        Err(_) => return,
+    };
+
+    for pid in pids {
+        println!("🛑 Killing test run process group {}", pid);
+        signal_run(pid, true);
+    }
+}
+
+/// Cancel a test run: queued runs leave the queue, running ones get SIGTERM
+/// first and SIGKILL if they ignore it
+#[tauri::command]
+pub async fn cancel_run(app: AppHandle, run_id: String) -> Result<RunInfo, String> {
+    if job_queue::cancel_queued(&app, &run_id) {
+        return run_info(&run_id).ok_or_else(|| format!("Unknown run: {}", run_id));
+    }
+
+    let pid = {
+        let mut runs = RUNS.lock().map_err(|e| format!("Run registry unavailable: {}", e))?;
+        let run = runs
+            .get_mut(&run_id)
+            .ok_or_else(|| format!("Unknown run: {}", run_id))?;
+
+        if run.state.is_finished() {
+            return Ok(run.clone());
+        }
+        run.state = RunState::Cancelling;
+        run.pid
+    };
+
+    // Already dequeued but not spawned yet; start_run sees the cancellation
+    let Some(pid) = pid else {
+        return wait_until_finished(&run_id, KILL_WAIT)
+            .await
+            .ok_or_else(|| format!("Unknown run: {}", run_id));
+    };
+
+    println!("🛑 Cancelling run {} (PID: {})", run_id, pid);
+    signal_run(pid, false);
+
+    let info = wait_until_finished(&run_id, CANCEL_GRACE).await;
+    if info.as_ref().is_some_and(|i| i.state.is_finished()) {
+        return info.ok_or_else(|| format!("Unknown run: {}", run_id));
+    }
+
+    println!("⚠️ Run {} ignored SIGTERM, sending SIGKILL", run_id);
+    signal_run(pid, true);
+
+    wait_until_finished(&run_id, KILL_WAIT)
+        .await
+        .ok_or_else(|| format!("Unknown run: {}", run_id))
+}
+
+/// All runs in the registry, oldest first
+#[tauri::command]
+pub async fn list_runs() -> Result<Vec<RunInfo>, String> {
+    let runs = RUNS.lock().map_err(|e| format!("Run registry unavailable: {}", e))?;
+    let mut list: Vec<RunInfo> = runs.values().cloned().collect();
+    list.sort_by(|a, b| a.queued_at.cmp(&b.queued_at).then_with(|| a.run_id.cmp(&b.run_id)));
+    Ok(list)
+}
+
+#[tauri::command]
+pub async fn get_run_status(run_id: String) -> Result<RunInfo, String> {
+    run_info(&run_id).ok_or_else(|| format!("Unknown run: {}", run_id))
+}
@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::installation::{self, Scout94Installation};
+
+/// Manifest shipped with the app
+const BUNDLED_MANIFEST: &str = include_str!("../test-suites.json");
+/// Optional manifest in the Scout94 installation that replaces the bundled one
+const MANIFEST_FILE: &str = "test-suites.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunMode {
+    Local,
+    Remote,
+}
+
+/// One entry of the test suite manifest
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TestSuiteDef {
+    pub id: String,
+    pub label: String,
+    /// Script path relative to the Scout94 installation
+    pub script: String,
+    pub description: String,
+    /// Executables that must be on PATH to run the suite
+    pub required_tools: Vec<String>,
+    pub default_timeout_secs: u64,
+    pub modes: Vec<RunMode>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TestSuiteInfo {
+    #[serde(flatten)]
+    pub suite: TestSuiteDef,
+    /// Required tools not found on this machine's PATH
+    pub missing_tools: Vec<String>,
+}
+
+/// Load the suite manifest, preferring the one in the Scout94 installation
+pub fn load(installation: Option<&Scout94Installation>) -> Result<Vec<TestSuiteDef>, String> {
+    if let Some(installation) = installation {
+        let path = installation.script_path(MANIFEST_FILE);
+        if path.is_file() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read test suite manifest {:?}: {}", path, e))?;
+            return parse_manifest(&content).map_err(|e| format!("{:?}: {}", path, e));
+        }
+    }
+
+    parse_manifest(BUNDLED_MANIFEST)
+}
+
+fn parse_manifest(content: &str) -> Result<Vec<TestSuiteDef>, String> {
+    let suites: Vec<TestSuiteDef> = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid test suite manifest: {}", e))?;
+
+    for (i, suite) in suites.iter().enumerate() {
+        if suites[..i].iter().any(|other| other.id == suite.id) {
+            return Err(format!("Invalid test suite manifest: duplicate suite id '{}'", suite.id));
+        }
+    }
+
+    Ok(suites)
+}
+
+/// Look up a suite by id, rejecting unknown ids and unsupported modes
+pub fn find(suites: &[TestSuiteDef], id: &str, mode: RunMode) -> Result<TestSuiteDef, String> {
+    let suite = suites.iter().find(|s| s.id == id).ok_or_else(|| {
+        let known: Vec<&str> = suites.iter().map(|s| s.id.as_str()).collect();
+        format!("❌ Unknown test suite '{}'. Available suites: {}", id, known.join(", "))
+    })?;
+
+    if !suite.modes.contains(&mode) {
+        return Err(format!("❌ Test suite '{}' cannot run in {:?} mode", id, mode));
+    }
+
+    Ok(suite.clone())
+}
+
+/// Resolve the installation and look up a suite in its manifest
+pub fn resolve(app: &AppHandle, id: &str, mode: RunMode) -> Result<TestSuiteDef, String> {
+    let installation = installation::resolve(app).ok();
+    find(&load(installation.as_ref())?, id, mode)
+}
+
+pub fn missing_tools(suite: &TestSuiteDef) -> Vec<String> {
+    suite
+        .required_tools
+        .iter()
+        .filter(|tool| !tool_on_path(tool))
+        .cloned()
+        .collect()
+}
+
+fn tool_on_path(tool: &str) -> bool {
+    let Some(paths) = std::env::var_os("PATH") else {
+        return false;
+    };
+
+    std::env::split_paths(&paths).any(|dir| {
+        let candidate = dir.join(tool);
+        candidate.is_file() || (cfg!(windows) && Path::new(&candidate).with_extension("exe").is_file())
+    })
+}
+
+/// Test suites the UI can offer, optionally filtered by run mode
+#[tauri::command]
+pub async fn list_test_suites(app: AppHandle, mode: Option<RunMode>) -> Result<Vec<TestSuiteInfo>, String> {
+    let installation = installation::resolve(&app).ok();
+    let suites = load(installation.as_ref())?;
+
+    Ok(suites
+        .into_iter()
+        .filter(|suite| mode.is_none_or(|m| suite.modes.contains(&m)))
+        .map(|suite| TestSuiteInfo {
+            missing_tools: missing_tools(&suite),
+            suite,
+        })
+        .collect())
+}
@@ -0,0 +1,16 @@
+//! Fixtures shared by the unit tests
+
+use std::fs;
+use tempfile::TempDir;
+
+/// A temporary project directory holding `files` as (relative path,
+/// content); missing parent directories are created
+pub fn project_dir(files: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for (path, content) in files {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
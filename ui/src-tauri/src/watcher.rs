@@ -0,0 +1,514 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::classifier::Classifier;
+use crate::ignore_rules::{self, IgnoreRules};
+use crate::symbols;
+use crate::workspace::{Access, FsError, Workspace};
+
+pub const WATCH_EVENT: &str = "fs-changed";
+
+/// Quiet period after the last event before a batch is emitted
+const DEBOUNCE: Duration = Duration::from_millis(250);
+/// Longest a batch is held back while events keep arriving
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(2);
+/// Batches with more changes than this are sent as an overflow listing only
+/// the affected directories (npm install, git checkout, ...)
+const STORM_THRESHOLD: usize = 200;
+
+static WATCHERS: LazyLock<Mutex<HashMap<PathBuf, RecommendedWatcher>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FsChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    /// Previous path of a renamed entry
+    pub from: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FsChangeBatch {
+    pub root: String,
+    pub changes: Vec<FsChange>,
+    /// Too many changes to list (or the OS dropped events); `directories`
+    /// holds the top-most directories that changed and should be reloaded
+    pub overflow: bool,
+    pub directories: Vec<String>,
+}
+
+/// Pending change of one path; `from` is set for renames
+#[derive(Debug, Clone)]
+struct Pending {
+    kind: ChangeKind,
+    from: Option<PathBuf>,
+}
+
+/// Fold a new change into what is already pending for the same path
+fn coalesce(previous: Option<&Pending>, next: Pending) -> Option<Pending> {
+    use ChangeKind::*;
+    let Some(previous) = previous else {
+        return Some(next);
+    };
+
+    match (previous.kind, next.kind) {
+        // Created and gone again before anyone looked
+        (Created, Deleted) => None,
+        (Created, Modified) => Some(previous.clone()),
+        (Deleted, Created) => Some(Pending { kind: Modified, from: None }),
+        (Renamed, Modified) => Some(previous.clone()),
+        _ => Some(next),
+    }
+}
+
+struct Batch {
+    root: PathBuf,
+    changes: HashMap<PathBuf, Pending>,
+    rescan: bool,
+}
+
+impl Batch {
+    fn new(root: PathBuf) -> Batch {
+        Batch { root, changes: HashMap::new(), rescan: false }
+    }
+
+    fn record(&mut self, path: PathBuf, change: Pending) {
+        // A renamed entry deleted again is gone from where it started
+        if change.kind == ChangeKind::Deleted {
+            if let Some(Pending { kind: ChangeKind::Renamed, from: Some(from) }) = self.changes.get(&path).cloned() {
+                self.record(from, Pending { kind: ChangeKind::Deleted, from: None });
+            }
+        }
+        match coalesce(self.changes.get(&path), change) {
+            Some(change) => {
+                self.changes.insert(path, change);
+            }
+            None => {
+                self.changes.remove(&path);
+            }
+        }
+    }
+
+    fn add(&mut self, event: Event) {
+        if event.need_rescan() {
+            self.rescan = true;
+        }
+
+        let pending = |kind| Pending { kind, from: None };
+        match event.kind {
+            EventKind::Create(_) => {
+                for path in event.paths {
+                    self.record(path, pending(ChangeKind::Created));
+                }
+            }
+            EventKind::Remove(_) => {
+                for path in event.paths {
+                    self.record(path, pending(ChangeKind::Deleted));
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                let (from, to) = (event.paths[0].clone(), event.paths[1].clone());
+                let change = match self.changes.remove(&from) {
+                    // A file created in this batch and then renamed is simply created
+                    Some(Pending { kind: ChangeKind::Created, .. }) => Pending { kind: ChangeKind::Created, from: None },
+                    // Renamed twice: report the move from where it started
+                    Some(Pending { kind: ChangeKind::Renamed, from: Some(first) }) => Pending { kind: ChangeKind::Renamed, from: Some(first) },
+                    _ => Pending { kind: ChangeKind::Renamed, from: Some(from) },
+                };
+                self.changes.insert(to, change);
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                for path in event.paths {
+                    self.record(path, pending(ChangeKind::Deleted));
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                for path in event.paths {
+                    self.record(path, pending(ChangeKind::Created));
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                for path in event.paths {
+                    let kind = if path.exists() { ChangeKind::Created } else { ChangeKind::Deleted };
+                    self.record(path, pending(kind));
+                }
+            }
+            EventKind::Modify(_) | EventKind::Any => {
+                for path in event.paths {
+                    self.record(path, pending(ChangeKind::Modified));
+                }
+            }
+            // Access events never change content
+            EventKind::Access(_) | EventKind::Other => {}
+        }
+    }
+
+    /// Same rules as `read_directory_tree`: the project's ignore rules and
+    /// files its classifier hides
+    fn is_ignored(rules: &IgnoreRules, classifier: &Classifier, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        if rules.is_ignored_path(path, is_dir) {
+            return true;
+        }
+        let relative = path.strip_prefix(rules.root()).unwrap_or(path);
+        !is_dir && path.is_file() && classifier.is_hidden(path, relative)
+    }
+
+    fn into_event(self, rules: &IgnoreRules, classifier: &Classifier) -> Option<FsChangeBatch> {
+        let changes: Vec<(PathBuf, Pending)> = self
+            .changes
+            .iter()
+            .filter(|(path, change)| {
+                // Renames out of an ignored directory still matter
+                !Batch::is_ignored(rules, classifier, path)
+                    || change.from.as_deref().is_some_and(|from| !Batch::is_ignored(rules, classifier, from))
+            })
+            .map(|(path, change)| (path.clone(), change.clone()))
+            .collect();
+
+        if changes.is_empty() && !self.rescan {
+            return None;
+        }
+
+        let root = self.root.to_string_lossy().to_string();
+        if self.rescan || changes.len() > STORM_THRESHOLD {
+            return Some(FsChangeBatch {
+                root: root.clone(),
+                changes: Vec::new(),
+                overflow: true,
+                directories: if self.rescan { vec![root] } else { top_directories(&self.root, &changes) },
+            });
+        }
+
+        let mut changes: Vec<FsChange> = changes
+            .into_iter()
+            .map(|(path, change)| FsChange {
+                path: path.to_string_lossy().to_string(),
+                kind: change.kind,
+                from: change.from.map(|from| from.to_string_lossy().to_string()),
+            })
+            .collect();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Some(FsChangeBatch { root, changes, overflow: false, directories: Vec::new() })
+    }
+}
+
+/// Parent directories of the changes, without any that sit inside another
+fn top_directories(root: &Path, changes: &[(PathBuf, Pending)]) -> Vec<String> {
+    let parents: BTreeSet<PathBuf> = changes
+        .iter()
+        .filter_map(|(path, _)| path.parent())
+        .filter(|parent| parent.starts_with(root))
+        .map(Path::to_path_buf)
+        .collect();
+
+    let mut top: Vec<PathBuf> = Vec::new();
+    for parent in parents {
+        if !top.iter().any(|t| parent.starts_with(t)) {
+            top.push(parent);
+        }
+    }
+    top.into_iter().map(|p| p.to_string_lossy().to_string()).collect()
+}
+
+/// Watch `dir` and every directory below it the ignore rules keep, one
+/// level at a time, so node_modules, vendor and the like cost no watches
+fn watch_tree(watcher: &mut RecommendedWatcher, rules: &IgnoreRules, dir: &Path) -> notify::Result<()> {
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // The entry's own type: symlinked directories are not followed
+        if entry.file_type().is_ok_and(|t| t.is_dir()) && !rules.is_ignored_path(&path, true) {
+            if let Err(e) = watch_tree(watcher, rules, &path) {
+                println!("⚠️ Not watching {:?}: {}", path, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Start watching directories created (or moved) into a watched tree
+fn watch_new_directories(root: &Path, rules: &IgnoreRules, event: &Event) {
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))) {
+        return;
+    }
+    let directories: Vec<&PathBuf> = event
+        .paths
+        .iter()
+        .filter(|path| fs::symlink_metadata(path).is_ok_and(|m| m.is_dir()) && !rules.is_ignored_path(path, true))
+        .collect();
+    if directories.is_empty() {
+        return;
+    }
+
+    let mut watchers = WATCHERS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(watcher) = watchers.get_mut(root) else {
+        return;
+    };
+    for dir in directories {
+        if let Err(e) = watch_tree(watcher, rules, dir) {
+            println!("⚠️ Not watching {:?}: {}", dir, e);
+        }
+    }
+}
+
+/// Collect events into debounced batches until the watcher is dropped
+fn run_batcher(app: AppHandle, root: PathBuf, events: Receiver<notify::Result<Event>>) {
+    let mut batch = Batch::new(root.clone());
+    let mut rules = IgnoreRules::for_path(&app, &root);
+    let classifier = Classifier::for_path(&app, &root);
+    let add = |batch: &mut Batch, rules: &IgnoreRules, event: notify::Result<Event>| match event {
+        Ok(event) => {
+            watch_new_directories(&root, rules, &event);
+            batch.add(event);
+        }
+        Err(e) => {
+            println!("⚠️ Watch error in {:?}: {}", root, e);
+            batch.rescan = true;
+        }
+    };
+
+    // Wait for the first event of a batch
+    while let Ok(first) = events.recv() {
+        add(&mut batch, &rules, first);
+        let started = Instant::now();
+        let mut quiet_until = started + DEBOUNCE;
+        let mut disconnected = false;
+
+        loop {
+            let deadline = quiet_until.min(started + MAX_BATCH_DELAY);
+            match events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(event) => {
+                    add(&mut batch, &rules, event);
+                    quiet_until = Instant::now() + DEBOUNCE;
+                }
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+
+        let done = std::mem::replace(&mut batch, Batch::new(root.clone()));
+        let changed: Vec<PathBuf> = done
+            .changes
+            .iter()
+            .flat_map(|(path, change)| std::iter::once(path.clone()).chain(change.from.clone()))
+            .collect();
+        symbols::apply_changes(&changed, done.rescan);
+        if done.changes.keys().any(|path| ignore_rules::is_ignore_file(path)) {
+            rules = IgnoreRules::for_path(&app, &root);
+        }
+        if let Some(event) = done.into_event(&rules, &classifier) {
+            let _ = app.emit(WATCH_EVENT, event);
+        }
+        if disconnected {
+            break;
+        }
+    }
+}
+
+/// Whether `path` is inside a watched directory, so changes below it are seen
+pub fn is_watched(path: &Path) -> bool {
+    WATCHERS.lock().unwrap_or_else(|e| e.into_inner()).keys().any(|root| path.starts_with(root))
+}
+
+/// Watch a directory recursively and emit `fs-changed` batches for it.
+///
+/// Events are debounced and coalesced per path; ignored paths are dropped
+/// with the same rules as the file tree, and ignored directories are not
+/// watched at all. Watching an already watched directory is a no-op.
+#[tauri::command]
+pub async fn watch_directory(app: AppHandle, directory_path: String) -> Result<(), FsError> {
+    let path = Workspace::current(&app).resolve(&directory_path, Access::Read)?;
+    if !path.is_dir() {
+        return Err(FsError::InvalidPath { message: format!("Path is not a directory: {}", directory_path), path: directory_path });
+    }
+
+    if WATCHERS.lock().unwrap_or_else(|e| e.into_inner()).contains_key(&path) {
+        return Ok(());
+    }
+
+    // Built without holding the lock: registering every directory takes a while
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })
+    .map_err(|e| FsError::Io { path: directory_path.clone(), message: format!("Failed to create watcher: {}", e) })?;
+    watch_tree(&mut watcher, &IgnoreRules::for_path(&app, &path), &path)
+        .map_err(|e| FsError::Io { path: directory_path.clone(), message: format!("Failed to watch directory: {}", e) })?;
+
+    let mut watchers = WATCHERS.lock().unwrap_or_else(|e| e.into_inner());
+    if watchers.contains_key(&path) {
+        // Another call won the race; dropping ours also drops its events
+        return Ok(());
+    }
+    // The batcher exits once the watcher (and with it the sender) is dropped
+    let root = path.clone();
+    thread::spawn(move || run_batcher(app, root, rx));
+
+    watchers.insert(path, watcher);
+    println!("👀 Watching {}", directory_path);
+    Ok(())
+}
+
+/// Stop watching a directory; returns whether it was being watched
+#[tauri::command]
+pub async fn unwatch_directory(app: AppHandle, directory_path: String) -> Result<bool, FsError> {
+    let path = Workspace::current(&app).resolve(&directory_path, Access::Read)?;
+    let removed = WATCHERS.lock().unwrap_or_else(|e| e.into_inner()).remove(&path).is_some();
+    if removed {
+        println!("🙈 Stopped watching {}", directory_path);
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ClassifierSettings;
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+    use tempfile::TempDir;
+
+    fn event(kind: EventKind, paths: &[&Path]) -> Event {
+        paths.iter().fold(Event::new(kind), |event, path| event.add_path(path.to_path_buf()))
+    }
+
+    fn changes(batch: Batch, root: &Path) -> Vec<(String, ChangeKind, Option<String>)> {
+        let rules = IgnoreRules::new(root, &["node_modules/".to_string()]);
+        let classifier = Classifier::new(ClassifierSettings::default());
+        let relative = |path: &str| Path::new(path).strip_prefix(root).unwrap().to_string_lossy().to_string();
+        batch
+            .into_event(&rules, &classifier)
+            .map(|event| event.changes)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|change| (relative(&change.path), change.kind, change.from.as_deref().map(relative)))
+            .collect()
+    }
+
+    #[test]
+    fn coalesces_changes_of_one_path() {
+        use ChangeKind::*;
+        let pending = |kind| Pending { kind, from: None };
+        let renamed = Pending { kind: Renamed, from: Some(PathBuf::from("/p/old")) };
+        let kind = |previous: Option<&Pending>, next| coalesce(previous, pending(next)).map(|p| p.kind);
+
+        assert_eq!(kind(None, Modified), Some(Modified));
+        assert_eq!(kind(Some(&pending(Created)), Deleted), None);
+        assert_eq!(kind(Some(&pending(Created)), Modified), Some(Created));
+        assert_eq!(kind(Some(&pending(Deleted)), Created), Some(Modified));
+        assert_eq!(kind(Some(&pending(Modified)), Deleted), Some(Deleted));
+        let kept = coalesce(Some(&renamed), pending(Modified)).unwrap();
+        assert_eq!((kept.kind, kept.from), (Renamed, Some(PathBuf::from("/p/old"))));
+    }
+
+    #[test]
+    fn renames_keep_their_origin() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let (a, b, c) = (root.join("a.php"), root.join("b.php"), root.join("c.php"));
+        let rename = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+
+        // Renamed twice, then edited
+        let mut batch = Batch::new(root.to_path_buf());
+        batch.add(event(rename, &[&a, &b]));
+        batch.add(event(rename, &[&b, &c]));
+        batch.add(event(EventKind::Modify(ModifyKind::Data(DataChange::Content)), &[&c]));
+        assert_eq!(changes(batch, root), vec![("c.php".to_string(), ChangeKind::Renamed, Some("a.php".to_string()))]);
+
+        // Renamed, then deleted: the original path is gone too
+        let mut batch = Batch::new(root.to_path_buf());
+        batch.add(event(rename, &[&a, &b]));
+        batch.add(event(EventKind::Remove(RemoveKind::File), &[&b]));
+        assert_eq!(
+            changes(batch, root),
+            vec![("a.php".to_string(), ChangeKind::Deleted, None), ("b.php".to_string(), ChangeKind::Deleted, None)]
+        );
+
+        // Created and renamed within one batch is a plain creation
+        let mut batch = Batch::new(root.to_path_buf());
+        batch.add(event(EventKind::Create(CreateKind::File), &[&a]));
+        batch.add(event(rename, &[&a, &b]));
+        assert_eq!(changes(batch, root), vec![("b.php".to_string(), ChangeKind::Created, None)]);
+    }
+
+    #[test]
+    fn ignored_paths_and_storms_are_filtered() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let modules = root.join("node_modules");
+        fs::create_dir(&modules).unwrap();
+
+        let mut batch = Batch::new(root.to_path_buf());
+        batch.add(event(EventKind::Create(CreateKind::File), &[&modules.join("lib.js")]));
+        batch.add(event(EventKind::Access(notify::event::AccessKind::Any), &[&root.join("index.php")]));
+        let rules = IgnoreRules::new(root, &["node_modules/".to_string()]);
+        let classifier = Classifier::new(ClassifierSettings::default());
+        assert!(batch.into_event(&rules, &classifier).is_none());
+
+        // Moving a file out of an ignored directory is still reported
+        let mut batch = Batch::new(root.to_path_buf());
+        batch.add(event(EventKind::Modify(ModifyKind::Name(RenameMode::Both)), &[&root.join("app.js"), &modules.join("app.js")]));
+        assert_eq!(changes(batch, root).len(), 1);
+
+        let mut batch = Batch::new(root.to_path_buf());
+        for i in 0..=STORM_THRESHOLD {
+            batch.add(event(EventKind::Create(CreateKind::File), &[&root.join("src").join(format!("{}.php", i))]));
+        }
+        let storm = batch.into_event(&rules, &classifier).unwrap();
+        assert!(storm.overflow && storm.changes.is_empty());
+        assert_eq!(storm.directories, vec![root.join("src").to_string_lossy().to_string()]);
+    }
+
+    #[test]
+    fn ignored_directories_are_not_watched() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src/models")).unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        let rules = IgnoreRules::new(root, &["node_modules/".to_string()]);
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })
+        .unwrap();
+        watch_tree(&mut watcher, &rules, root).unwrap();
+
+        let saw = |path: PathBuf| {
+            fs::write(&path, "x").unwrap();
+            let deadline = Instant::now() + Duration::from_secs(2);
+            while let Ok(Ok(event)) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                if event.paths.contains(&path) {
+                    return true;
+                }
+            }
+            false
+        };
+        assert!(!saw(root.join("node_modules/pkg/index.js")));
+        assert!(saw(root.join("src/models/User.php")));
+    }
+}
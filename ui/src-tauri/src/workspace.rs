@@ -0,0 +1,514 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::app_config;
+use crate::installation;
+
+/// Error returned by the file commands.
+///
+/// Serialized with a `kind` tag so the UI can tell a denied path from an I/O
+/// failure; every variant carries a human-readable `message`.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FsError {
+    /// The path resolves outside every workspace root
+    OutsideWorkspace { path: String, message: String },
+    /// The path looks like it is inside a root but a symlink leads out of it
+    SymlinkEscape { path: String, target: String, message: String },
+    /// Writes are only allowed inside project roots, not the Scout94 install
+    ReadOnly { path: String, message: String },
+    InvalidPath { path: String, message: String },
+    Io { path: String, message: String },
+    /// The file is binary or does not decode losslessly, or the content
+    /// cannot be represented in the file's encoding
+    Encoding { path: String, message: String },
+    /// The file changed on disk since the caller read it
+    Conflict(Box<WriteConflict>),
+}
+
+/// Both versions of a file involved in a write conflict, so the UI can offer
+/// a merge. `current_content` is `None` when the file has been deleted.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct WriteConflict {
+    pub path: String,
+    pub message: String,
+    pub expected_hash: String,
+    pub current_hash: Option<String>,
+    pub current_content: Option<String>,
+    pub proposed_content: String,
+}
+
+impl FsError {
+    pub fn io(path: &Path, action: &str, error: io::Error) -> FsError {
+        FsError::Io {
+            path: path.to_string_lossy().to_string(),
+            message: format!("Failed to {}: {}", action, error),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            FsError::OutsideWorkspace { message, .. }
+            | FsError::SymlinkEscape { message, .. }
+            | FsError::ReadOnly { message, .. }
+            | FsError::InvalidPath { message, .. }
+            | FsError::Io { message, .. }
+            | FsError::Encoding { message, .. } => message,
+            FsError::Conflict(conflict) => &conflict.message,
+        }
+    }
+}
+
+impl fmt::Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl From<FsError> for String {
+    fn from(error: FsError) -> String {
+        error.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone)]
+struct Root {
+    /// The root as registered, lexically normalized
+    given: PathBuf,
+    /// The root with every symlink resolved
+    real: PathBuf,
+    writable: bool,
+}
+
+/// Directories the file commands may touch: registered project roots
+/// (read-write) and the Scout94 installation (read-only)
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    roots: Vec<Root>,
+}
+
+impl Workspace {
+    /// Build a workspace from root directories; roots that do not exist are skipped
+    pub fn new(project_roots: &[PathBuf], read_only_roots: &[PathBuf]) -> Workspace {
+        let roots = project_roots
+            .iter()
+            .map(|p| (p, true))
+            .chain(read_only_roots.iter().map(|p| (p, false)))
+            .filter_map(|(path, writable)| {
+                let real = path.canonicalize().ok()?;
+                Some(Root { given: normalize(path), real, writable })
+            })
+            .collect();
+        Workspace { roots }
+    }
+
+    /// Roots registered in the app config plus the resolved Scout94 installation
+    pub fn current(app: &AppHandle) -> Workspace {
+        let config = app_config::load(app);
+        let projects: Vec<PathBuf> = config.project_roots.iter().map(PathBuf::from).collect();
+        let install: Vec<PathBuf> = installation::resolve(app)
+            .map(|i| vec![PathBuf::from(i.path)])
+            .unwrap_or_default();
+        Workspace::new(&projects, &install)
+    }
+
+    /// Resolve `path` to a real path inside the workspace.
+    ///
+    /// Symlinks are followed for every existing component; the part of the path
+    /// that does not exist yet (for writes) may not contain `..`.
+    pub fn resolve(&self, path: &str, access: Access) -> Result<PathBuf, FsError> {
+        let requested = Path::new(path);
+        let invalid = |reason: &str| FsError::InvalidPath {
+            path: path.to_string(),
+            message: format!("🚫 Invalid path {:?}: {}", path, reason),
+        };
+
+        if path.is_empty() || path.contains('\0') {
+            return Err(invalid("empty or contains NUL"));
+        }
+        if !requested.is_absolute() {
+            return Err(invalid("must be absolute"));
+        }
+
+        let real = resolve_existing(requested).map_err(|e| FsError::io(requested, "resolve path", e))?;
+        let Some(real) = real else {
+            return Err(invalid("'..' after a component that does not exist"));
+        };
+
+        match self.roots.iter().find(|root| real.starts_with(&root.real)) {
+            Some(root) if access == Access::Write && !root.writable => Err(FsError::ReadOnly {
+                path: path.to_string(),
+                message: format!("🚫 {} is in the Scout94 installation, which is read-only", path),
+            }),
+            Some(_) => Ok(real),
+            None => {
+                let lexical = normalize(requested);
+                let looks_inside = self
+                    .roots
+                    .iter()
+                    .any(|root| lexical.starts_with(&root.given) || lexical.starts_with(&root.real));
+
+                if looks_inside {
+                    Err(FsError::SymlinkEscape {
+                        path: path.to_string(),
+                        target: real.to_string_lossy().to_string(),
+                        message: format!("🚫 {} leaves the workspace through a symlink (resolves to {:?})", path, real),
+                    })
+                } else {
+                    Err(FsError::OutsideWorkspace {
+                        path: path.to_string(),
+                        message: format!("🚫 {} is outside the registered project roots and the Scout94 installation", path),
+                    })
+                }
+            }
+        }
+    }
+
+    /// The project root (not the Scout94 install) containing an already resolved path
+    pub fn project_root(&self, real: &Path) -> Option<&Path> {
+        self.roots
+            .iter()
+            .find(|root| root.writable && real.starts_with(&root.real))
+            .map(|root| root.real.as_path())
+    }
+}
+
+/// Dangling symlinks followed while resolving one path, as in `SYMLOOP_MAX`
+const MAX_LINK_DEPTH: usize = 40;
+
+/// Canonicalize the longest existing prefix of `path` and append the rest.
+///
+/// Dangling symlinks are followed by hand, so a path is checked where a write
+/// through it would land rather than by the link's own name. Returns `None`
+/// if the non-existing rest contains `..`, which cannot be resolved safely.
+fn resolve_existing(path: &Path) -> io::Result<Option<PathBuf>> {
+    resolve_links(path, 0)
+}
+
+fn resolve_links(path: &Path, depth: usize) -> io::Result<Option<PathBuf>> {
+    let mut existing = path.to_path_buf();
+    let mut rest: Vec<Component> = Vec::new();
+    let components: Vec<Component> = path.components().collect();
+    let mut index = components.len();
+
+    loop {
+        match existing.canonicalize() {
+            Ok(real) => {
+                let mut resolved = real;
+                for component in rest.iter().rev() {
+                    match component {
+                        Component::Normal(name) => resolved.push(name),
+                        Component::CurDir => {}
+                        _ => return Ok(None),
+                    }
+                }
+                return Ok(Some(resolved));
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound && index > 1 => {
+                // Only symlinks can be read as links; this one points nowhere
+                if let Ok(target) = fs::read_link(&existing) {
+                    if depth >= MAX_LINK_DEPTH {
+                        return Err(io::Error::other("too many levels of symbolic links"));
+                    }
+                    let mut target = existing.parent().map_or_else(|| target.clone(), |parent| parent.join(&target));
+                    for component in rest.iter().rev() {
+                        target.push(component);
+                    }
+                    return resolve_links(&target, depth + 1);
+                }
+                index -= 1;
+                rest.push(components[index]);
+                existing = components[..index].iter().collect();
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Reject roots that would open up far more than one project
+pub fn check_root(path: &Path) -> Result<PathBuf, String> {
+    let real = path
+        .canonicalize()
+        .map_err(|e| format!("❌ Project root {:?} is not accessible: {}", path, e))?;
+    if !real.is_dir() {
+        return Err(format!("❌ Project root {:?} is not a directory", path));
+    }
+    if real.parent().is_none() {
+        return Err("❌ The filesystem root cannot be a project root".to_string());
+    }
+    if let Some(home) = home_dir().and_then(|h| h.canonicalize().ok()) {
+        if home.starts_with(&real) {
+            return Err(format!("❌ {:?} contains the home directory and cannot be a project root", real));
+        }
+        let hidden = real
+            .strip_prefix(&home)
+            .ok()
+            .and_then(|rest| rest.components().next())
+            .is_some_and(|first| first.as_os_str().to_string_lossy().starts_with('.'));
+        if hidden {
+            return Err(format!("❌ {:?} is inside a hidden directory of the home directory", real));
+        }
+    }
+    Ok(real)
+}
+
+/// Add an already checked root to the config; returns every registered root
+pub fn add_project_root(app: &AppHandle, real: &Path) -> Result<Vec<String>, String> {
+    let real = real.to_string_lossy().to_string();
+
+    app_config::modify(app, |config| {
+        if !config.project_roots.contains(&real) {
+            config.project_roots.push(real.clone());
+            println!("📁 Registered project root {}", real);
+        }
+        config.project_roots.clone()
+    })
+}
+
+/// Allow the file commands to access a project directory.
+///
+/// The path comes from the webview, so a root that is not registered yet
+/// needs the user's consent in a native dialog. Folders picked with
+/// `select_project_folder` are registered there without asking again.
+#[tauri::command]
+pub async fn register_project_root(app: AppHandle, path: String) -> Result<Vec<String>, String> {
+    let real = check_root(Path::new(&path))?;
+    let registered = app_config::load(&app).project_roots;
+    if registered.iter().any(|root| Path::new(root) == real) {
+        return Ok(registered);
+    }
+
+    let allowed = app
+        .dialog()
+        .message(format!("Allow Scout94 to read, write and run commands in this folder?\n\n{}", real.display()))
+        .title("Open project folder")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Allow".to_string(), "Cancel".to_string()))
+        .blocking_show();
+    if !allowed {
+        return Err(format!("❌ Access to {:?} was not allowed", real));
+    }
+    add_project_root(&app, &real)
+}
+
+#[tauri::command]
+pub async fn unregister_project_root(app: AppHandle, path: String) -> Result<Vec<String>, String> {
+    let real = Path::new(&path)
+        .canonicalize()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or(path.clone());
+
+    app_config::modify(&app, |config| {
+        config.project_roots.retain(|root| *root != path && *root != real);
+        config.project_roots.clone()
+    })
+}
+
+#[tauri::command]
+pub async fn list_project_roots(app: AppHandle) -> Result<Vec<String>, String> {
+    Ok(app_config::load(&app).project_roots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    struct Fixture {
+        _dir: TempDir,
+        base: PathBuf,
+        workspace: Workspace,
+    }
+
+    /// base/project (root), base/scout94 (read-only root), base/outside/secret.txt
+    fn fixture() -> Fixture {
+        let dir = TempDir::new().expect("temp dir");
+        let base = dir.path().canonicalize().expect("canonical temp dir");
+
+        fs::create_dir_all(base.join("project/src")).unwrap();
+        fs::write(base.join("project/src/index.php"), "<?php").unwrap();
+        fs::create_dir_all(base.join("project-evil")).unwrap();
+        fs::write(base.join("project-evil/x.php"), "<?php").unwrap();
+        fs::create_dir_all(base.join("scout94")).unwrap();
+        fs::write(base.join("scout94/run_all_tests.php"), "<?php").unwrap();
+        fs::create_dir_all(base.join("outside")).unwrap();
+        fs::write(base.join("outside/secret.txt"), "secret").unwrap();
+
+        let workspace = Workspace::new(&[base.join("project")], &[base.join("scout94")]);
+        Fixture { _dir: dir, base, workspace }
+    }
+
+    fn path(base: &Path, rest: &str) -> String {
+        format!("{}/{}", base.display(), rest)
+    }
+
+    #[test]
+    fn allows_paths_inside_roots() {
+        let f = fixture();
+        let resolved = f.workspace.resolve(&path(&f.base, "project/src/index.php"), Access::Read).unwrap();
+        assert_eq!(resolved, f.base.join("project/src/index.php"));
+
+        let new_file = f.workspace.resolve(&path(&f.base, "project/new/dir/file.txt"), Access::Write).unwrap();
+        assert_eq!(new_file, f.base.join("project/new/dir/file.txt"));
+
+        assert!(f.workspace.resolve(&path(&f.base, "scout94/run_all_tests.php"), Access::Read).is_ok());
+    }
+
+    #[test]
+    fn dot_dot_cannot_leave_the_root() {
+        let f = fixture();
+        for attempt in [
+            "project/../outside/secret.txt",
+            "project/src/../../outside/secret.txt",
+            "project/src/./../../../etc/passwd",
+        ] {
+            let result = f.workspace.resolve(&path(&f.base, attempt), Access::Read);
+            assert!(
+                matches!(result, Err(FsError::OutsideWorkspace { .. }) | Err(FsError::Io { .. })),
+                "{} was not denied: {:?}",
+                attempt,
+                result
+            );
+        }
+
+        // `..` inside the root is fine
+        assert!(f.workspace.resolve(&path(&f.base, "project/src/../src/index.php"), Access::Read).is_ok());
+    }
+
+    #[test]
+    fn dot_dot_in_missing_components_is_rejected() {
+        let f = fixture();
+        let result = f.workspace.resolve(&path(&f.base, "project/missing/../../outside/new.txt"), Access::Write);
+        assert!(matches!(result, Err(FsError::InvalidPath { .. })), "{:?}", result);
+    }
+
+    #[test]
+    fn sibling_with_common_prefix_is_outside() {
+        let f = fixture();
+        let result = f.workspace.resolve(&path(&f.base, "project-evil/x.php"), Access::Read);
+        assert!(matches!(result, Err(FsError::OutsideWorkspace { .. })), "{:?}", result);
+    }
+
+    #[test]
+    fn relative_paths_are_invalid() {
+        let f = fixture();
+        let result = f.workspace.resolve("project/src/index.php", Access::Read);
+        assert!(matches!(result, Err(FsError::InvalidPath { .. })), "{:?}", result);
+    }
+
+    #[test]
+    fn install_is_read_only() {
+        let f = fixture();
+        let result = f.workspace.resolve(&path(&f.base, "scout94/run_all_tests.php"), Access::Write);
+        assert!(matches!(result, Err(FsError::ReadOnly { .. })), "{:?}", result);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_root_are_detected() {
+        use std::os::unix::fs::symlink;
+
+        let f = fixture();
+        symlink(f.base.join("outside/secret.txt"), f.base.join("project/secret-link")).unwrap();
+        symlink(f.base.join("outside"), f.base.join("project/src/escape")).unwrap();
+
+        let file_link = f.workspace.resolve(&path(&f.base, "project/secret-link"), Access::Read);
+        assert!(matches!(file_link, Err(FsError::SymlinkEscape { .. })), "{:?}", file_link);
+
+        // Writing a new file below a symlinked directory
+        let dir_link = f.workspace.resolve(&path(&f.base, "project/src/escape/new.txt"), Access::Write);
+        assert!(matches!(dir_link, Err(FsError::SymlinkEscape { .. })), "{:?}", dir_link);
+
+        // `..` after a symlink is resolved physically, not lexically
+        let dot_dot = f.workspace.resolve(&path(&f.base, "project/src/escape/../outside/secret.txt"), Access::Read);
+        assert!(dot_dot.is_err(), "{:?}", dot_dot);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dangling_symlinks_resolve_to_their_target() {
+        use std::os::unix::fs::symlink;
+
+        let f = fixture();
+        symlink(f.base.join("outside/missing.xml"), f.base.join("project/out.xml")).unwrap();
+        symlink(f.base.join("outside/missing-dir"), f.base.join("project/src/gone")).unwrap();
+        symlink("new.php", f.base.join("project/src/later.php")).unwrap();
+
+        for attempt in ["project/out.xml", "project/src/gone/new.txt"] {
+            let result = f.workspace.resolve(&path(&f.base, attempt), Access::Write);
+            assert!(matches!(result, Err(FsError::SymlinkEscape { .. })), "{} was not denied: {:?}", attempt, result);
+        }
+
+        // A dangling link inside the root points at the file a write would create
+        let inside = f.workspace.resolve(&path(&f.base, "project/src/later.php"), Access::Write).unwrap();
+        assert_eq!(inside, f.base.join("project/src/new.php"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_within_the_root_are_allowed() {
+        use std::os::unix::fs::symlink;
+
+        let f = fixture();
+        symlink(f.base.join("project/src/index.php"), f.base.join("project/index-link.php")).unwrap();
+
+        let resolved = f.workspace.resolve(&path(&f.base, "project/index-link.php"), Access::Read).unwrap();
+        assert_eq!(resolved, f.base.join("project/src/index.php"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_root_is_followed() {
+        use std::os::unix::fs::symlink;
+
+        let f = fixture();
+        symlink(f.base.join("project"), f.base.join("project-link")).unwrap();
+        let workspace = Workspace::new(&[f.base.join("project-link")], &[]);
+
+        assert!(workspace.resolve(&path(&f.base, "project-link/src/index.php"), Access::Read).is_ok());
+        assert!(workspace.resolve(&path(&f.base, "project/src/index.php"), Access::Read).is_ok());
+        let result = workspace.resolve(&path(&f.base, "project-link/../outside/secret.txt"), Access::Read);
+        assert!(matches!(result, Err(FsError::OutsideWorkspace { .. })), "{:?}", result);
+    }
+
+    #[test]
+    fn broad_roots_are_rejected() {
+        assert!(check_root(Path::new("/")).is_err());
+        if let Some(home) = home_dir().filter(|h| h.is_dir()) {
+            assert!(check_root(&home).is_err());
+        }
+    }
+}
@@ -1,20 +1,37 @@
-import React, { useState } from 'react';
+import React, { useEffect, useState } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
 import { ChevronDown, ChevronUp } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
+import { runScout94Test } from '../utils/testRunner';
 
-const commands = [
-  { id: 'all', label: '🚀 Run All Tests', color: 'bg-blue-600 hover:bg-blue-700', testType: 'all' },
-  { id: 'visual', label: '📸 Visual Test', color: 'bg-purple-600 hover:bg-purple-700', testType: 'visual' },
-  { id: 'routing', label: '🔍 Test Routes', color: 'bg-green-600 hover:bg-green-700', testType: 'routing' },
-  { id: 'audit', label: '📊 Run Audit', color: 'bg-orange-600 hover:bg-orange-700', testType: 'audit' },
-  { id: 'visitor', label: '👥 Visitor Journey', color: 'bg-emerald-600 hover:bg-emerald-700', testType: 'visitor' },
-  { id: 'user', label: '🔐 User Journey', color: 'bg-cyan-600 hover:bg-cyan-700', testType: 'user' },
-];
+// Button colors per suite id; suites themselves come from the backend registry
+const suiteColors = {
+  all: 'bg-blue-600 hover:bg-blue-700',
+  visual: 'bg-purple-600 hover:bg-purple-700',
+  routing: 'bg-green-600 hover:bg-green-700',
+  audit: 'bg-orange-600 hover:bg-orange-700',
+  visitor: 'bg-emerald-600 hover:bg-emerald-700',
+  user: 'bg-cyan-600 hover:bg-cyan-700',
+};
+const defaultColor = 'bg-slate-600 hover:bg-slate-700';
 
 export default function CommandButtons({ onCommand, onTestResult }) {
   const [isExpanded, setIsExpanded] = useState(true);
   const [runningTest, setRunningTest] = useState(null);
+  const [commands, setCommands] = useState([]);
+
+  useEffect(() => {
+    invoke('list_test_suites', { mode: 'local' })
+      .then((suites) => setCommands(suites.map((suite) => ({
+        id: suite.id,
+        label: suite.label,
+        color: suiteColors[suite.id] || defaultColor,
+        testType: suite.id,
+        description: suite.description,
+        missingTools: suite.missing_tools,
+      }))))
+      .catch((error) => console.error('❌ Failed to load test suites:', error));
+  }, []);
 
   const handleRunTest = async (cmd) => {
     setRunningTest(cmd.id);
@@ -23,9 +40,10 @@ export default function CommandButtons({ onCommand, onTestResult }) {
       console.log(`🚀 Running REAL test: ${cmd.testType}`);
       
       // REAL Tauri command - runs actual PHP tests
-      const result = await invoke('run_scout94_test', {
+      const result = await runScout94Test({
         projectPath: '/Users/mac/CascadeProjects/Viz Venture Group',
-        testType: cmd.testType
+        testType: cmd.testType,
+        onOutput: (line) => console.log(`[${cmd.testType}] ${line.line}`)
       });
       
       console.log('✅ Real test result:', result);
@@ -36,6 +54,7 @@ export default function CommandButtons({ onCommand, onTestResult }) {
           success: result.success,
           output: result.output,
           error: result.error,
+          report: result.report,
           testType: cmd.testType
         });
       }
@@ -82,7 +101,8 @@ export default function CommandButtons({ onCommand, onTestResult }) {
                 whileHover={{ scale: 1.05 }}
                 whileTap={{ scale: 0.95 }}
                 onClick={() => handleRunTest(cmd)}
-                disabled={runningTest === cmd.id}
+                disabled={runningTest === cmd.id || cmd.missingTools.length > 0}
+                title={cmd.missingTools.length > 0 ? `Missing: ${cmd.missingTools.join(', ')}` : cmd.description}
                 className={`px-4 py-2 rounded-lg text-sm font-semibold text-white ${cmd.color} transition shadow-lg disabled:opacity-50 disabled:cursor-not-allowed relative`}
               >
                 {runningTest === cmd.id && (
@@ -1,7 +1,9 @@
-import React, { useState, useEffect } from 'react';
+import React, { useState, useEffect, useRef } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
 import { FileText, Code, Terminal, CheckCircle, XCircle, AlertCircle, ChevronRight, FileCode, FolderOpen, Search, ChevronDown, File } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { searchProject } from '../utils/projectSearch';
 import ReactMarkdown from 'react-markdown';
 import remarkGfm from 'remark-gfm';
 import { Light as SyntaxHighlighter } from 'react-syntax-highlighter';
@@ -24,9 +26,20 @@ export default function IDEPane({ isRunning, messages, projectPath }) {
   const [isDragging, setIsDragging] = useState(false);
   const [markdownContent, setMarkdownContent] = useState(null);
   const [markdownFilePath, setMarkdownFilePath] = useState(null);
-  const [fileTree, setFileTree] = useState([]);
+  // Children listed so far per folder path: { items, nextCursor }
+  const [folderChildren, setFolderChildren] = useState({});
+  const folderChildrenRef = useRef(folderChildren);
+  folderChildrenRef.current = folderChildren;
+  const fileTree = folderChildren[projectPath]?.items || [];
   const [isLoadingTree, setIsLoadingTree] = useState(false);
   const [fileContents, setFileContents] = useState({});
+  // Hash of each file as last read or written, sent back with saves
+  // Hash, encoding and BOM of each fully read file, sent back when saving it
+  const [fileStamps, setFileStamps] = useState({});
+  const [searchText, setSearchText] = useState('');
+  const [searchResults, setSearchResults] = useState(null);
+  const [searchSummary, setSearchSummary] = useState(null);
+  const activeSearchRef = useRef(null);
   
   // Listen for file display messages from WebSocket
   useEffect(() => {
@@ -84,7 +97,11 @@ export default function IDEPane({ isRunning, messages, projectPath }) {
   
   const loadMarkdownFile = async (filePath) => {
     try {
-      const content = await invoke('read_file_content', { filePath });
+      const { content, hash, encoding, bom } = await invoke('read_file_content', { filePath });
+      // Binary or inexact text has no encoding and cannot be saved back
+      if (encoding) {
+        setFileStamps(prev => ({ ...prev, [filePath]: { hash, encoding, bom } }));
+      }
       setMarkdownContent(content);
       setMarkdownFilePath(filePath);
       
@@ -115,7 +132,7 @@ export default function IDEPane({ isRunning, messages, projectPath }) {
       id: tabId,
       path: filePath,
       name: fileName,
-      language: language || 'text'
+      language: language || null
     };
     
     setOpenTabs(prev => {
@@ -150,44 +167,162 @@ export default function IDEPane({ isRunning, messages, projectPath }) {
     loadFileTree();
   }, [projectPath]);
   
-  const loadFileTree = async () => {
+  // Reload the tree when files change on disk
+  useEffect(() => {
     if (!projectPath) return;
     
-    setIsLoadingTree(true);
+    const unlistenPromise = listen('fs-changed', (event) => {
+      const { changes, overflow } = event.payload;
+      // Changed files must be read again when next opened
+      setFileContents(prev => {
+        if (overflow) return {};
+        const next = { ...prev };
+        changes.forEach(change => delete next[change.path]);
+        return next;
+      });
+      loadFileTree({ preserveExpanded: true });
+    });
+    invoke('watch_directory', { directoryPath: projectPath })
+      .catch(error => console.error('Failed to watch project:', error));
+    
+    return () => {
+      unlistenPromise.then(unlisten => unlisten());
+      invoke('unwatch_directory', { directoryPath: projectPath }).catch(() => {});
+    };
+  }, [projectPath]);
+  
+  const cancelSearch = () => {
+    if (activeSearchRef.current) {
+      activeSearchRef.current.cancel().catch(() => {});
+      activeSearchRef.current = null;
+    }
+  };
+  
+  const runSearch = async () => {
+    cancelSearch();
+    if (!projectPath || !searchText.trim()) {
+      setSearchResults(null);
+      setSearchSummary(null);
+      return;
+    }
+    
+    setSearchResults([]);
+    setSearchSummary(null);
     try {
-      const tree = await invoke('read_directory_tree', { 
-        directoryPath: projectPath,
-        maxDepth: 4 
+      const search = await searchProject({
+        projectPath,
+        query: { pattern: searchText, context_lines: 0 },
+        onMatches: (file) => setSearchResults(prev => [...(prev || []), file]),
       });
+      activeSearchRef.current = search;
+      const summary = await search.done;
+      if (activeSearchRef.current === search) {
+        activeSearchRef.current = null;
+        setSearchSummary(summary);
+      }
+    } catch (error) {
+      console.error('Search failed:', error);
+      setSearchSummary({ error: String(error) });
+    }
+  };
+  
+  const clearSearch = () => {
+    cancelSearch();
+    setSearchText('');
+    setSearchResults(null);
+    setSearchSummary(null);
+  };
+  
+  // Stop a running search when the project changes
+  useEffect(() => clearSearch, [projectPath]);
+  
+  const CHILDREN_PAGE_SIZE = 200;
+  // list_children never returns more than this per call
+  const MAX_CHILDREN_PAGE_SIZE = 1000;
+  
+  const convertEntry = (entry) => ({
+    type: entry.is_directory ? 'folder' : 'file',
+    name: entry.name,
+    path: entry.path,
+    language: entry.language || 'text',
+    childCount: entry.child_count
+  });
+  
+  // Load one page of a folder's children; `append` continues after the last page
+  const loadChildren = async (folderPath, { append = false, limit = CHILDREN_PAGE_SIZE } = {}) => {
+    const loaded = folderChildrenRef.current[folderPath];
+    const cursor = append ? loaded?.nextCursor : null;
+    const page = await invoke('list_children', { path: folderPath, cursor, limit });
+    const items = page.entries.map(convertEntry);
+    
+    setFolderChildren(prev => ({
+      ...prev,
+      [folderPath]: {
+        items: append ? [...(prev[folderPath]?.items || []), ...items] : items,
+        nextCursor: page.next_cursor
+      }
+    }));
+    return items;
+  };
+  
+  // Reload a folder's first `count` children, paging through as many calls as that takes
+  const reloadChildren = async (folderPath, count) => {
+    let items = [];
+    let cursor = null;
+    do {
+      const limit = Math.min(MAX_CHILDREN_PAGE_SIZE, Math.max(CHILDREN_PAGE_SIZE, count - items.length));
+      const page = await invoke('list_children', { path: folderPath, cursor, limit });
+      items = [...items, ...page.entries.map(convertEntry)];
+      cursor = page.next_cursor;
+    } while (cursor && items.length < count);
+    
+    setFolderChildren(prev => ({
+      ...prev,
+      [folderPath]: { items, nextCursor: cursor }
+    }));
+  };
+  
+  const loadFileTree = async ({ preserveExpanded = false } = {}) => {
+    if (!projectPath) return;
+    
+    if (!preserveExpanded) setIsLoadingTree(true);
+    try {
+      if (preserveExpanded) {
+        // Refresh every folder listed so far, keeping how far it was paged
+        const loaded = Object.entries(folderChildrenRef.current);
+        await Promise.all(loaded.map(([folderPath, { items }]) =>
+          reloadChildren(folderPath, items.length)
+            .catch(() => setFolderChildren(prev => {
+              // The folder is gone
+              const next = { ...prev };
+              delete next[folderPath];
+              return next;
+            }))
+        ));
+        return;
+      }
       
-      // Convert from Rust format to component format
-      const convertedTree = tree.map(node => convertNode(node));
-      setFileTree(convertedTree);
+      setFolderChildren({});
+      const topLevel = await loadChildren(projectPath);
       
       // Auto-expand first level folders
-      const topLevelFolders = tree
-        .filter(node => node.is_directory)
-        .map(node => node.path);
+      const topLevelFolders = topLevel
+        .filter(item => item.type === 'folder')
+        .map(item => item.path);
+      await Promise.all(topLevelFolders.map(folderPath => loadChildren(folderPath).catch(() => {})));
       setExpandedFolders(topLevelFolders);
     } catch (error) {
       console.error('Failed to load file tree:', error);
-      setFileTree([]);
+      setFolderChildren({});
     } finally {
       setIsLoadingTree(false);
     }
   };
-  
-  const convertNode = (node) => {
-    return {
-      type: node.is_directory ? 'folder' : 'file',
-      name: node.name,
-      path: node.path,
-      language: node.language || 'text',
-      children: node.children ? node.children.map(child => convertNode(child)) : undefined
-    };
-  };
 
   const toggleFolder = (folderPath) => {
+    if (!expandedFolders.includes(folderPath) && !folderChildren[folderPath]) {
+      loadChildren(folderPath).catch(error => console.error('Failed to load folder:', error));
+    }
     setExpandedFolders((prev) =>
       prev.includes(folderPath) ? prev.filter((f) => f !== folderPath) : [...prev, folderPath]
     );
@@ -200,12 +335,53 @@ export default function IDEPane({ isRunning, messages, projectPath }) {
     }
     
     try {
-      const content = await invoke('read_file_content', { filePath });
+      const chunk = await invoke('read_file_range', { filePath });
+      let content = chunk.binary
+        ? `// Binary file (${chunk.size} bytes) - not shown`
+        : chunk.content;
+      if (!chunk.binary && !chunk.eof) {
+        content += `\n\n// Showing the first ${chunk.end_byte} of ${chunk.size} bytes`;
+      }
       setFileContents(prev => ({ ...prev, [filePath]: content }));
+      // Only a complete text read can be saved back
+      if (!chunk.binary && chunk.eof) {
+        setFileStamps(prev => ({
+          ...prev,
+          [filePath]: { hash: chunk.hash, encoding: chunk.encoding, bom: chunk.bom }
+        }));
+      }
       return content;
     } catch (error) {
       console.error('Failed to load file:', error);
-      return `// Error loading file: ${error}`;
+      return `// Error loading file: ${error?.message ?? error}`;
+    }
+  };
+
+  // Save a file read above in its original encoding. The backend refuses
+  // with a `conflict` error carrying both versions if the file changed on
+  // disk since that read.
+  const saveFileContent = async (filePath, content) => {
+    const loaded = fileStamps[filePath];
+    if (!loaded) {
+      throw new Error(`${filePath} was not fully loaded as text and cannot be saved`);
+    }
+    try {
+      const stamp = await invoke('write_file_content', {
+        filePath,
+        content,
+        expectedHash: loaded.hash,
+        encoding: loaded.encoding,
+        bom: loaded.bom,
+      });
+      setFileStamps(prev => ({ ...prev, [filePath]: { ...loaded, hash: stamp.hash } }));
+      setFileContents(prev => ({ ...prev, [filePath]: content }));
+      return { saved: true };
+    } catch (error) {
+      if (error?.kind === 'conflict') {
+        console.warn(`⚠️ ${error.message}`);
+        return { saved: false, conflict: error };
+      }
+      throw error;
     }
   };
 
@@ -237,12 +413,23 @@ export default function IDEPane({ isRunning, messages, projectPath }) {
               )}
               <FolderOpen className="w-4 h-4 text-yellow-400" />
               <span className="text-sm font-medium">{item.name}</span>
-              {item.children && (
-                <span className="text-xs text-gray-500 ml-auto">{item.children.length}</span>
+              {item.childCount > 0 && (
+                <span className="text-xs text-gray-500 ml-auto">{item.childCount}</span>
               )}
             </div>
-            {expandedFolders.includes(item.path) && item.children && (
-              <div>{renderFileTree(item.children, depth + 1)}</div>
+            {expandedFolders.includes(item.path) && folderChildren[item.path] && (
+              <div>
+                {renderFileTree(folderChildren[item.path].items, depth + 1)}
+                {folderChildren[item.path].nextCursor && (
+                  <div
+                    onClick={() => loadChildren(item.path, { append: true })}
+                    className="px-3 py-1.5 text-xs text-blue-400 hover:bg-white/5 cursor-pointer"
+                    style={{ paddingLeft: `${(depth + 1) * 12 + 28}px` }}
+                  >
+                    Load more…
+                  </div>
+                )}
+              </div>
             )}
           </>
         ) : (
@@ -321,9 +508,54 @@ export default function IDEPane({ isRunning, messages, projectPath }) {
               {projectPath.split('/').pop()}
             </div>
           )}
+          {projectPath && (
+            <div className="flex items-center gap-2 bg-black/30 rounded px-2 py-1">
+              <Search className="w-3.5 h-3.5 text-gray-400 flex-shrink-0" />
+              <input
+                value={searchText}
+                onChange={(e) => setSearchText(e.target.value)}
+                onKeyDown={(e) => {
+                  if (e.key === 'Enter') runSearch();
+                  if (e.key === 'Escape') clearSearch();
+                }}
+                placeholder="Search in files"
+                className="flex-1 min-w-0 bg-transparent text-xs outline-none"
+              />
+              {searchResults !== null && (
+                <button onClick={clearSearch} className="text-xs text-gray-400 hover:text-white" title="Clear search">
+                  ✕
+                </button>
+              )}
+            </div>
+          )}
         </div>
         <div className="flex-1 overflow-y-auto">
-          {isLoadingTree ? (
+          {searchResults !== null ? (
+            <div className="text-xs">
+              <div className="px-3 py-1.5 text-gray-400">
+                {searchSummary === null
+                  ? 'Searching…'
+                  : searchSummary.error
+                    ? `❌ ${searchSummary.error}`
+                    : `${searchSummary.matches} matches in ${searchSummary.files_matched} files${searchSummary.limit_reached ? ' (limit reached)' : ''}`}
+              </div>
+              {searchResults.map((file) => (
+                <div key={file.path}>
+                  <div className="px-3 py-1 text-blue-300 truncate" title={file.path}>{file.relative}</div>
+                  {file.matches.map((match, index) => (
+                    <div
+                      key={index}
+                      onClick={() => openFileInTab(file.path, file.path.split('/').pop())}
+                      className="pl-6 pr-3 py-0.5 hover:bg-white/5 cursor-pointer truncate font-mono text-gray-300"
+                      title={`Line ${match.line}, column ${match.column}`}
+                    >
+                      <span className="text-gray-500">{match.line}: </span>{match.text.trim()}
+                    </div>
+                  ))}
+                </div>
+              ))}
+            </div>
+          ) : isLoadingTree ? (
             <div className="flex items-center justify-center h-32 text-gray-400">
               <div className="animate-spin rounded-full h-8 w-8 border-b-2 border-blue-500"></div>
             </div>
@@ -333,7 +565,17 @@ export default function IDEPane({ isRunning, messages, projectPath }) {
               <span className="text-xs">Select a project to explore</span>
             </div>
           ) : (
-            renderFileTree(fileTree)
+            <>
+              {renderFileTree(fileTree)}
+              {folderChildren[projectPath]?.nextCursor && (
+                <div
+                  onClick={() => loadChildren(projectPath, { append: true })}
+                  className="px-3 py-1.5 text-xs text-blue-400 hover:bg-white/5 cursor-pointer"
+                >
+                  Load more…
+                </div>
+              )}
+            </>
           )}
         </div>
       </div>
@@ -30,6 +30,9 @@ export default function MissionControl() {
 
   useEffect(() => {
     localStorage.setItem('scout94_project_path', projectPath);
+    // File commands only work inside registered project roots
+    invoke('register_project_root', { path: projectPath })
+      .catch((error) => console.warn('Failed to register project root:', error));
   }, [projectPath]);
 
   // Handle panel resize
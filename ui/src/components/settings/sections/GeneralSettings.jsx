@@ -5,6 +5,7 @@
  * Per: ADMIN_SETTINGS_PANEL_TODO.md Section 1
  */
 
+import { useEffect, useState } from 'react';
 import PropTypes from 'prop-types';
 import { FolderOpen } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
@@ -15,18 +16,33 @@ import SettingInput from '../SettingInput';
 
 function GeneralSettings({ config, onChange }) {
   const general = config.general;
+  const [techStack, setTechStack] = useState(null);
+
+  // Show what auto-detect would pick for the current project
+  useEffect(() => {
+    setTechStack(null);
+    if (!general.autoDetectTechStack || !general.projectPath) return;
+
+    let cancelled = false;
+    invoke('detect_tech_stack', { projectPath: general.projectPath })
+      .then((stack) => { if (!cancelled) setTechStack(stack); })
+      .catch((error) => console.error('Failed to detect tech stack:', error));
+    return () => { cancelled = true; };
+  }, [general.autoDetectTechStack, general.projectPath]);
+
+  const detectedLanguage = techStack?.languages[0];
+  const detectedFramework = techStack?.frameworks[0];
 
   // Handle project path selection via existing Tauri command
   const handleBrowseProject = async () => {
     try {
-      // Use existing Tauri command (defined in commands.rs line 81)
+      // Native folder picker; the backend registers the folder as a project root
       const selectedPath = await invoke('select_project_folder');
       if (selectedPath) {
         onChange('general.projectPath', selectedPath);
       }
     } catch (error) {
       console.error('Failed to select folder:', error);
-      alert('Folder picker not yet fully implemented. You can type the path manually for now.');
     }
   };
 
@@ -68,7 +84,7 @@ function GeneralSettings({ config, onChange }) {
               className="w-full px-3 py-2 bg-gray-900 border border-gray-600 rounded-lg text-white text-sm font-mono focus:border-blue-500 focus:outline-none focus:ring-1 focus:ring-blue-500 transition"
             />
             <p className="text-xs text-gray-500 mt-1">
-              Uses Tauri command <code className="bg-gray-800 px-1 rounded">select_project_folder()</code> - currently returns default path
+              Typed paths must be allowed once in a confirmation dialog before Scout94 can open them
             </p>
           </div>
 
@@ -94,7 +110,9 @@ function GeneralSettings({ config, onChange }) {
               { value: 'java', label: 'Java' },
               { value: 'csharp', label: 'C#' }
             ]}
-            helpText="Affects analysis focus and code parsing"
+            helpText={detectedLanguage
+              ? `Affects analysis focus and code parsing. Detected: ${detectedLanguage.language} (${Math.round(detectedLanguage.share * 100)}% of source)`
+              : 'Affects analysis focus and code parsing'}
           />
 
           <SettingDropdown
@@ -112,7 +130,9 @@ function GeneralSettings({ config, onChange }) {
               { value: 'rails', label: 'Ruby on Rails' },
               { value: 'express', label: 'Express' }
             ]}
-            helpText="Auto-detected if possible"
+            helpText={detectedFramework
+              ? `Detected: ${detectedFramework.name} (${detectedFramework.evidence[0]})`
+              : 'Auto-detected if possible'}
           />
 
           <SettingToggle
@@ -1,19 +1,21 @@
 import { useState, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { runScout94Test } from '../utils/testRunner';
 
 export default function useScout94() {
   const [isRunning, setIsRunning] = useState(false);
   const [currentProject, setCurrentProject] = useState('/Users/mac/CascadeProjects/Viz Venture Group');
 
-  const runTest = useCallback(async (testType) => {
+  const runTest = useCallback(async (testType, onOutput) => {
     setIsRunning(true);
     
     try {
       console.log(`🚀 Running ${testType} test on ${currentProject}`);
       
-      const result = await invoke('run_scout94_test', {
+      const result = await runScout94Test({
         projectPath: currentProject,
-        testType: testType
+        testType: testType,
+        onOutput
       });
 
       console.log('✅ Test result:', result);
@@ -32,6 +34,7 @@ export default function useScout94() {
 
   const selectProject = useCallback(async () => {
     try {
+      // The backend registers the picked folder as a project root
       const path = await invoke('select_project_folder');
       setCurrentProject(path);
       return path;
@@ -12,6 +12,7 @@
  * - Per-section reset
  */
 
+import { invoke } from '@tauri-apps/api/core';
 import { defaultConfig } from './configSchema';
 
 const CONFIG_KEY = 'scout94_settings';
@@ -77,6 +78,9 @@ class ConfigManager {
       localStorage.setItem(CONFIG_KEY, JSON.stringify(configToSave));
       this.config = configToSave;
       
+      // Push settings the Tauri backend enforces
+      this.syncBackend(configToSave);
+      
       // Notify listeners
       this.notifyListeners();
       
@@ -87,6 +91,49 @@ class ConfigManager {
     }
   }
 
+  /**
+   * Forward backend-enforced settings to the Tauri side
+   * Failures are logged only (e.g. when running in a plain browser)
+   */
+  syncBackend(config) {
+    const maxParallelRuns = config.general?.parallelProcesses;
+    if (maxParallelRuns && maxParallelRuns !== this.syncedParallelRuns) {
+      this.syncedParallelRuns = maxParallelRuns;
+      invoke('set_max_parallel_runs', { maxParallelRuns })
+        .catch((error) => console.warn('Failed to sync parallel runs:', error));
+    }
+
+    const projectPath = config.general?.projectPath;
+    if (projectPath && projectPath !== this.syncedProjectPath) {
+      this.syncedProjectPath = projectPath;
+      invoke('register_project_root', { path: projectPath })
+        .catch((error) => console.warn('Failed to register project root:', error));
+    }
+
+    const maxExecutionMinutes = config.general?.maxExecutionTime;
+    const memoryLimitMb = config.general?.memoryLimitMB;
+    const limitsKey = `${maxExecutionMinutes}:${memoryLimitMb}`;
+    if ((maxExecutionMinutes || memoryLimitMb) && limitsKey !== this.syncedRunLimits) {
+      this.syncedRunLimits = limitsKey;
+      invoke('set_run_limits', {
+        settings: {
+          max_execution_minutes: maxExecutionMinutes || null,
+          memory_limit_mb: memoryLimitMb || null,
+          max_open_files: null,
+          max_processes: null
+        }
+      }).catch((error) => console.warn('Failed to sync run limits:', error));
+    }
+
+    const ignoreGlobs = config.general?.ignoreGlobs ?? null;
+    const ignoreKey = JSON.stringify(ignoreGlobs);
+    if (ignoreKey !== this.syncedIgnoreGlobs) {
+      this.syncedIgnoreGlobs = ignoreKey;
+      invoke('set_ignore_globs', { globs: ignoreGlobs })
+        .catch((error) => console.warn('Failed to sync ignore globs:', error));
+    }
+  }
+
   /**
    * Get a specific setting value
    * Path format: 'section.subsection.key' (e.g., 'general.executionMode')
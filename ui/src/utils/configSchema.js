@@ -22,6 +22,7 @@ export const defaultConfig = {
     framework: 'auto-detect',
     autoDetectTechStack: true,
     cacheProjectStructure: true,
+    ignoreGlobs: null, // gitignore-style globs hidden from the tree (null = built-in list, ! re-includes)
     
     executionMode: 'audit', // basic | audit | clinic | visual | comprehensive
     autoRunBackground: false,
@@ -0,0 +1,59 @@
+// Search project files through the Tauri backend and follow streamed matches
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+/**
+ * Start a project search and return `{ searchId, done, cancel }`.
+ *
+ * Matches arrive per file through `onMatches`; `done` resolves with the
+ * summary once the search finishes, hits the result cap or is cancelled.
+ * As with test runs, listeners are attached before invoking and early
+ * events are buffered until the search id is known.
+ */
+export async function searchProject({ projectPath, query, onMatches }) {
+  let searchId = null;
+  const pending = [];
+  let resolveDone;
+  const done = new Promise((resolve) => { resolveDone = resolve; });
+
+  const handle = (event) => {
+    if (event.event === 'search-complete') {
+      resolveDone(event.payload);
+    } else if (onMatches) {
+      onMatches(event.payload);
+    }
+  };
+
+  const onEvent = (event) => {
+    if (searchId === null) {
+      pending.push(event);
+    } else if (event.payload.search_id === searchId) {
+      handle(event);
+    }
+  };
+
+  const unlistenMatch = await listen('search-match', onEvent);
+  const unlistenComplete = await listen('search-complete', onEvent);
+  const cleanup = () => {
+    unlistenMatch();
+    unlistenComplete();
+  };
+
+  try {
+    searchId = await invoke('search_project', { projectPath, query });
+  } catch (error) {
+    cleanup();
+    throw error;
+  }
+
+  for (const event of pending) {
+    if (event.payload.search_id === searchId) handle(event);
+  }
+
+  return {
+    searchId,
+    done: done.finally(cleanup),
+    cancel: () => invoke('cancel_search', { searchId }),
+  };
+}
@@ -72,8 +72,20 @@ export function formatTestResult(result) {
     timestamp: new Date()
   });
   
-  // Parse output
-  if (result.output) {
+  // Prefer the structured report parsed by the backend
+  if (result.report) {
+    for (const suite of result.report.suites) {
+      for (const check of suite.checks) {
+        const icon = check.status === 'passed' ? '✅' : check.status === 'failed' ? '❌' : '⚠️';
+        messages.push({
+          agent: check.status === 'failed' ? 'auditor' : agent,
+          text: `${icon} ${suite.name}: ${check.name}`,
+          type: check.status === 'passed' ? 'success' : check.status === 'failed' ? 'error' : 'message',
+          timestamp: new Date()
+        });
+      }
+    }
+  } else if (result.output) {
     const parsed = parseTestOutput(result.output);
     parsed.forEach(msg => {
       messages.push({
@@ -0,0 +1,56 @@
+// Run Scout94 tests through the Tauri backend and follow their live output
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+/**
+ * Start a test run and resolve with its final TestResult.
+ *
+ * The backend returns a run id immediately and streams output as events.
+ * Listeners are attached before invoking, and events that arrive before the
+ * run id is known are buffered so no early lines are lost.
+ */
+export async function runScout94Test({ projectPath, testType, onOutput }) {
+  let runId = null;
+  const pending = [];
+  let resolveDone;
+  const done = new Promise((resolve) => { resolveDone = resolve; });
+
+  const handleOutput = (payload) => {
+    if (onOutput) onOutput(payload);
+  };
+
+  const unlistenOutput = await listen('test-run-output', (event) => {
+    if (runId === null) {
+      pending.push(event);
+    } else if (event.payload.run_id === runId) {
+      handleOutput(event.payload);
+    }
+  });
+
+  const unlistenComplete = await listen('test-run-complete', (event) => {
+    if (runId === null) {
+      pending.push(event);
+    } else if (event.payload.run_id === runId) {
+      resolveDone(event.payload.result);
+    }
+  });
+
+  try {
+    runId = await invoke('run_scout94_test', { projectPath, testType });
+
+    for (const event of pending) {
+      if (event.payload.run_id !== runId) continue;
+      if (event.event === 'test-run-complete') {
+        resolveDone(event.payload.result);
+      } else {
+        handleOutput(event.payload);
+      }
+    }
+
+    return await done;
+  } finally {
+    unlistenOutput();
+    unlistenComplete();
+  }
+}